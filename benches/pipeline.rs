@@ -0,0 +1,97 @@
+//! Benchmarks of the quote request queue, the channel every ticker fetches its price through: the round trip of
+//! a single request, and a burst of the requests of 200 tickers ticking together, once for 200 assets and once
+//! for a single asset they all show, e.g. on several servers. Run with `cargo bench --bench pipeline`.
+
+use async_trait::async_trait;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use discord_price_ticker::config::Config;
+use discord_price_ticker::quote::error::QuoteRequestError;
+use discord_price_ticker::quote::provider::QuoteProvider;
+use discord_price_ticker::quote::req_consumer::consume_quote_requests;
+use discord_price_ticker::quote::request::AssetQuoteRequest;
+use discord_price_ticker::quote::request_queue::{quote_request_queue, QuoteRequestSender};
+use discord_price_ticker::quote::response::AssetQuoteResponse;
+use discord_price_ticker::shutdown::stop_channel;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
+
+const TICKERS: usize = 200;
+
+/// Answers right away, so that only the queue and the channels are measured
+struct InstantProvider;
+
+#[async_trait]
+impl QuoteProvider for InstantProvider {
+    fn name(&self) -> &str {
+        "Instant"
+    }
+
+    async fn fetch_quote(&self, id: &str, _vs_currency: &str) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        Ok(AssetQuoteResponse {
+            name: id.to_string(),
+            price: "65761.12".parse().unwrap(),
+            price_change_24h: "1.5".parse().unwrap(),
+            ..Default::default()
+        })
+    }
+}
+
+/// Queue with its consumer running on `runtime`, as large as a burst so that none of its requests is dropped
+fn start_queue(runtime: &Runtime) -> QuoteRequestSender {
+    let (sender, receiver) = quote_request_queue(TICKERS);
+    runtime.spawn(consume_quote_requests(
+        receiver,
+        Arc::new(InstantProvider),
+        Duration::from_secs(30),
+        Config::DEFAULT_MAX_CONCURRENT_QUOTE_REQUESTS,
+        stop_channel().1,
+    ));
+    sender
+}
+
+/// Sends the requests for `names` at once and waits for all of their responses
+async fn request_quotes(sender: &QuoteRequestSender, names: &[String]) {
+    let mut receivers = Vec::with_capacity(names.len());
+    for name in names {
+        let (resp_sender, resp_receiver) = mpsc::unbounded_channel();
+        sender
+            .send(AssetQuoteRequest {
+                name: name.clone(),
+                vs_currency: "usd".to_string(),
+                resp_sender,
+            })
+            .await
+            .unwrap();
+        receivers.push(resp_receiver);
+    }
+    for mut receiver in receivers {
+        receiver.recv().await.unwrap().unwrap();
+    }
+}
+
+fn quote_request_queue_benchmark(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let sender = start_queue(&runtime);
+
+    let mut group = c.benchmark_group("quote_request_queue");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("round_trip", |b| {
+        let names = vec!["bitcoin".to_string()];
+        b.to_async(&runtime).iter(|| request_quotes(&sender, &names));
+    });
+
+    group.throughput(Throughput::Elements(TICKERS as u64));
+    let distinct_assets: Vec<String> = (0..TICKERS).map(|i| format!("asset-{}", i)).collect();
+    let same_asset = vec!["bitcoin".to_string(); TICKERS];
+    for (label, names) in [("distinct_assets", &distinct_assets), ("same_asset", &same_asset)] {
+        group.bench_with_input(BenchmarkId::new("burst", label), names, |b, names| {
+            b.to_async(&runtime).iter(|| request_quotes(&sender, names));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, quote_request_queue_benchmark);
+criterion_main!(benches);
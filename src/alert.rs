@@ -0,0 +1,182 @@
+use crate::config::AlertConfig;
+use crate::discord::client::DiscordClient;
+use reqwest::header;
+use serenity::all::{ChannelId, Http};
+use std::error::Error;
+use tracing::{debug, warn};
+
+/// Discord rejects messages longer than this
+const MAX_MESSAGE_LENGTH: usize = 2000;
+
+/// Sends operator alerts to the configured Discord webhook and/or channel
+#[derive(Clone, Debug)]
+pub struct AlertSender {
+    http_client: reqwest::Client,
+    config: AlertConfig,
+}
+
+impl AlertSender {
+    pub fn new(http_client: reqwest::Client, config: AlertConfig) -> Self {
+        Self {
+            http_client,
+            config,
+        }
+    }
+
+    pub fn failure_threshold(&self) -> u32 {
+        self.config.failure_threshold()
+    }
+
+    /// Posts `message` to the alert webhook, and to the alert channel using the bot of `discord_client`
+    pub async fn send(&self, discord_client: Option<&DiscordClient>, message: &str) {
+        self.send_with_http(discord_client.map(DiscordClient::http), message).await;
+    }
+
+    /// Same as `send`, for event handlers which only have the HTTP client of the bot
+    pub async fn send_with_http(&self, http: Option<&Http>, message: &str) {
+        let message = truncate(message, MAX_MESSAGE_LENGTH);
+
+        if let Some(webhook_url) = &self.config.webhook_url {
+            let body = serde_json::json!({ "content": message }).to_string();
+            let result = self
+                .http_client
+                .post(webhook_url.expose())
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(body)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                // the URL holds the token of the webhook
+                .map_err(reqwest::Error::without_url);
+
+            match result {
+                Ok(_) => debug!("Sent alert to webhook: {}", message),
+                Err(e) => warn!("Error sending alert to webhook: {}", e),
+            }
+        }
+
+        if let (Some(channel_id), Some(http)) = (self.config.channel_id, http) {
+            match ChannelId::new(channel_id).say(http, &message).await {
+                Ok(_) => debug!("Sent alert to channel {}: {}", channel_id, message),
+                Err(e) => warn!("Error sending alert to channel {}: {}", channel_id, e),
+            }
+        }
+    }
+}
+
+/// Counts consecutive failures, telling when an alert or a recovery message is due
+#[derive(Debug)]
+pub struct FailureStreak {
+    threshold: u32,
+    count: u32,
+}
+
+impl FailureStreak {
+    pub fn new(threshold: u32) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            count: 0,
+        }
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Returns true when the streak has just reached the threshold
+    pub fn record_failure(&mut self) -> bool {
+        self.count += 1;
+        self.count == self.threshold
+    }
+
+    /// Resets the streak, returns true if it had reached the threshold, i.e. this is a recovery
+    pub fn record_success(&mut self) -> bool {
+        let recovered = self.count >= self.threshold;
+        self.count = 0;
+        recovered
+    }
+}
+
+/// Formats the error followed by all of its sources, e.g. `Error sending request: ...: connection refused`.
+/// A source whose message ends the chain already, e.g. the one a `QuoteRequestError` shows in its own, is not repeated.
+pub fn error_chain(error: &dyn Error) -> String {
+    let mut chain = error.to_string();
+    let mut source = error.source();
+
+    while let Some(error) = source {
+        let message = error.to_string();
+        if !chain.ends_with(&message) {
+            chain.push_str(": ");
+            chain.push_str(&message);
+        }
+        source = error.source();
+    }
+
+    chain
+}
+
+fn truncate(message: &str, max_length: usize) -> String {
+    if message.chars().count() <= max_length {
+        return message.to_string();
+    }
+
+    let mut truncated: String = message.chars().take(max_length - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quote::error::QuoteRequestError;
+
+    #[test]
+    fn test_failure_streak() {
+        let mut streak = FailureStreak::new(3);
+        assert!(!streak.record_failure());
+        assert!(!streak.record_success());
+
+        assert!(!streak.record_failure());
+        assert!(!streak.record_failure());
+        assert!(streak.record_failure());
+        assert!(!streak.record_failure());
+        assert_eq!(4, streak.count());
+
+        assert!(streak.record_success());
+        assert_eq!(0, streak.count());
+        assert!(!streak.record_success());
+    }
+
+    /// An error that does not show its source in its own message
+    #[derive(Debug)]
+    struct Outer(QuoteRequestError);
+
+    impl std::fmt::Display for Outer {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "Error updating the bot")
+        }
+    }
+
+    impl Error for Outer {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    #[test]
+    fn test_error_chain() {
+        let error = QuoteRequestError::from(serde_json::from_str::<u32>("x").unwrap_err());
+        assert_eq!("JSON Parse Error: expected value at line 1 column 1", error_chain(&error));
+
+        assert_eq!(
+            "Error updating the bot: JSON Parse Error: expected value at line 1 column 1",
+            error_chain(&Outer(error))
+        );
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!("abc", truncate("abc", 3));
+        assert_eq!("ab…", truncate("abcd", 3));
+    }
+}
@@ -0,0 +1,1204 @@
+use std::collections::{HashMap, HashSet};
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+use std::time;
+use std::future::Future;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
+use tracing::debug;
+use tracing::trace;
+use tracing::warn;
+use tracing::info;
+use tracing::error;
+
+use crate::quote::alternative_me::FearAndGreedProvider;
+use crate::quote::beaconchain::BeaconChainProvider;
+use crate::quote::api_keys::ApiKeys;
+use crate::quote::binance::BinanceProvider;
+use crate::quote::binance_futures::{uses_derivatives, BinanceFuturesProvider};
+use crate::quote::chainlink::ChainlinkProvider;
+use crate::quote::budget::{ApiUsage, BudgetedProvider};
+use crate::quote::cache::CachedProvider;
+use crate::quote::circuit_breaker::CircuitBreakerProvider;
+use crate::quote::coincap::CoinCapProvider;
+use crate::quote::coingecko::{CoinGeckoGlobalProvider, CoinGeckoProvider};
+use crate::quote::custom_http::CustomHttpProvider;
+use crate::quote::defillama::DefiLlamaProvider;
+use crate::quote::etherscan::EtherscanGasProvider;
+use crate::quote::exec::ExecProvider;
+use crate::quote::geckoterminal::GeckoTerminalProvider;
+use crate::quote::opensea::OpenSeaProvider;
+use crate::quote::coin_list::CoinList;
+use crate::quote::error::QuoteRequestError;
+use crate::quote::req_consumer::consume_quote_requests;
+use crate::quote::provider::QuoteProvider;
+use crate::quote::request_queue::{quote_request_queue, QuoteRequestSender};
+use crate::quote::twse::TwseProvider;
+use crate::quote::yahoo::YahooFinanceProvider;
+use crate::reload::TickerChanges;
+use crate::shutdown::{child_stop_channel, stop_channel, StopSignal, StopTrigger};
+use crate::alert::AlertSender;
+use crate::bot_update::{consume_bot_updates, BotUpdateInfo, BotUpdateTarget, TickerSinks};
+use crate::discord::board::PriceBoard;
+use crate::discord::client::DiscordClient;
+use crate::discord::commands::{CommandHandler, TickerCommandHandler};
+use crate::discord::onboarding::GuildJoinHandler;
+use crate::discord::quarantine::GuildQuarantine;
+use crate::discord::stagger::UpdateStagger;
+use crate::price_alert::PriceAlertStore;
+use crate::discord::webhook::DiscordWebhook;
+use crate::config::{BoardConfig, Config, DisplayMode, QuoteSource, SinkConfig, TelegramTargetConfig, TickerConfig, TickerKind};
+#[cfg(feature = "mqtt")]
+use crate::sink::mqtt::MqttSink;
+#[cfg(feature = "mqtt")]
+use rumqttc::MqttOptions;
+use crate::sink::output::OutputSink;
+use crate::sink::price_alert::PriceAlertSink;
+use crate::sink::stdout::StdoutSink;
+use crate::sink::telegram::{TelegramSink, TelegramTarget};
+use crate::health::{FailedTickers, Liveness, TickerHealth};
+use crate::http::{DiscordHttpClient, HttpClientFactory};
+use crate::supervisor;
+#[cfg(feature = "systemd")]
+use crate::systemd;
+use crate::ticker::{run_periodic_fetch_job_loop, QuoteJobSenders};
+use crate::ticker_control::{TickerControls, TickerOverride};
+
+#[derive(Debug)]
+pub enum TickerBotError {
+    MissingConfig,
+    HttpClient(reqwest::Error),
+    DiscordHttpClient(reqwest_011::Error),
+    CoinList(QuoteRequestError),
+    UnknownCoinIds(Vec<String>),
+    ShutdownTimeout(Vec<String>),
+    FailedTickers(Vec<String>),
+}
+
+impl fmt::Display for TickerBotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TickerBotError::MissingConfig => write!(f, "No config is given to the ticker bot"),
+            TickerBotError::HttpClient(ref err) => write!(f, "Error creating HTTP client: {}", err),
+            TickerBotError::DiscordHttpClient(ref err) => write!(f, "Error creating HTTP client for Discord: {}", err),
+            TickerBotError::CoinList(ref err) => {
+                write!(f, "Error fetching the list of coins from CoinGecko: {}", err)
+            }
+            TickerBotError::UnknownCoinIds(ref ids) => {
+                write!(f, "Unknown CoinGecko coin ids: {}", ids.join(", "))
+            }
+            TickerBotError::ShutdownTimeout(ref tasks) => {
+                write!(f, "Tasks did not stop within the shutdown timeout: {}", tasks.join(", "))
+            }
+            TickerBotError::FailedTickers(ref tickers) => {
+                write!(f, "Fetching the price or updating the bot failed for: {}", tickers.join(", "))
+            }
+        }
+    }
+}
+
+impl error::Error for TickerBotError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            TickerBotError::MissingConfig => None,
+            TickerBotError::HttpClient(ref err) => Some(err),
+            TickerBotError::DiscordHttpClient(ref err) => Some(err),
+            TickerBotError::CoinList(ref err) => Some(err),
+            TickerBotError::UnknownCoinIds(_) => None,
+            TickerBotError::ShutdownTimeout(_) => None,
+            TickerBotError::FailedTickers(_) => None,
+        }
+    }
+}
+
+/// Fills in the omitted `name` of the tickers and their secondary assets: stock symbols are
+/// taken as is, crypto symbols are resolved to the id of the coin with the highest market cap.
+/// Crypto names are left empty if there is no `coin_list` or the symbol cannot be resolved.
+/// The other way around, the symbols of the crypto ids of portfolios and ratios are looked up for Binance.
+async fn resolve_omitted_names(
+    provider: &CoinGeckoProvider,
+    coin_list: Option<&CoinList>,
+    tickers: &mut [TickerConfig],
+) {
+    for ticker_config in tickers.iter_mut() {
+        // portfolios, ratios, indexes, countdowns, staking APRs and NFT floors are named after their ticker, their assets are given by id;
+        // gas tickers are named after their chain
+        if ticker_config.name.is_empty() {
+            match ticker_config.kind {
+                TickerKind::Asset | TickerKind::GlobalMarket => {}
+                TickerKind::Portfolio
+                | TickerKind::Ratio
+                | TickerKind::FearAndGreed
+                | TickerKind::Countdown
+                | TickerKind::StakingApr
+                | TickerKind::NftFloor => {
+                    ticker_config.name = ticker_config.ticker.clone()
+                }
+                TickerKind::Gas => ticker_config.name = TickerConfig::DEFAULT_GAS_CHAIN_ID.to_string(),
+            }
+        }
+
+        if let (Some(coin_list), TickerKind::Portfolio | TickerKind::Ratio, true) =
+            (coin_list, ticker_config.kind, ticker_config.crypto)
+        {
+            let symbols = ticker_config
+                .quoted_ids()
+                .into_iter()
+                .filter_map(|id| Some((id.clone(), coin_list.symbol_of(id)?.to_string())))
+                .collect();
+            ticker_config.symbols = symbols;
+        }
+
+        let crypto = ticker_config.crypto;
+        let mut assets = vec![(ticker_config.asset_symbol(), &mut ticker_config.name)];
+        for asset in ticker_config.secondary_assets.iter_mut() {
+            assets.push((asset.ticker.to_uppercase(), &mut asset.name));
+        }
+
+        for (symbol, name) in assets.into_iter().filter(|(_, name)| name.is_empty()) {
+            if !crypto {
+                *name = symbol;
+                continue;
+            }
+
+            let Some(coin_list) = coin_list else {
+                error!("Cannot resolve the CoinGecko id of {} without the list of coins", symbol);
+                continue;
+            };
+
+            match provider.resolve_symbol(coin_list, &symbol).await {
+                Ok(Some(id)) => {
+                    info!("Resolved {} to CoinGecko id {}", symbol, id);
+                    *name = id;
+                }
+                Ok(None) => error!("No CoinGecko coin has the symbol {}", symbol),
+                Err(error) => error!("Error resolving the CoinGecko id of {}: {}", symbol, error),
+            }
+        }
+    }
+}
+
+/// Checks the CoinGecko ids of the crypto tickers and their secondary assets against `/coins/list`,
+/// logging every unknown one along with the id it most likely should be. Returns the unknown ids.
+fn find_unknown_coin_ids(coin_list: &CoinList, tickers: &[TickerConfig]) -> HashSet<String> {
+    let mut unknown_ids = HashSet::new();
+
+    for ticker_config in tickers.iter().filter(|ticker_config| ticker_config.crypto) {
+        let ids = ticker_config
+            .quoted_ids()
+            .into_iter()
+            .chain(ticker_config.secondary_assets.iter().map(|asset| &asset.name));
+
+        for id in ids {
+            if coin_list.contains(id) {
+                continue;
+            }
+
+            // unresolved symbols are logged while resolving them
+            if id.is_empty() {
+                unknown_ids.insert(id.clone());
+                continue;
+            }
+
+            match coin_list.suggest(id) {
+                Some(suggestion) => error!(
+                    "Ticker {}: {:?} is not a CoinGecko coin id, did you mean {:?}?",
+                    ticker_config.ticker, id, suggestion
+                ),
+                None => error!(
+                    "Ticker {}: {:?} is not a CoinGecko coin id",
+                    ticker_config.ticker, id
+                ),
+            }
+            unknown_ids.insert(id.clone());
+        }
+    }
+
+    unknown_ids
+}
+
+/// Everything set up from the config before any ticker starts
+struct Prepared {
+    config: Config,
+    http_client: reqwest::Client,
+    discord_http_client: Option<DiscordHttpClient>, // Only given with a proxy
+    coingecko_provider: Arc<CoinGeckoProvider>,
+    coin_list: Option<Arc<CoinList>>,
+    unknown_coin_ids: HashSet<String>,
+}
+
+/// A ticker started by `TickerLauncher`, which a reload can stop on its own
+struct RunningTicker {
+    ticker: String,
+    fingerprint: u64, // See `TickerConfig::fingerprint`
+    stop_trigger: StopTrigger,
+    handle: JoinHandle<()>,
+    discord_client: Option<DiscordClient>, // Only in the bot display mode
+}
+
+impl RunningTicker {
+    /// Stops the fetch loop and shuts the bot down, waiting at most `shutdown_timeout` for either
+    async fn stop(self, shutdown_timeout: time::Duration, liveness: &Liveness) {
+        self.stop_trigger.stop();
+        let mut handle = self.handle;
+        if timeout(shutdown_timeout, &mut handle).await.is_err() {
+            error!("Task for {} did not stop within {:?}, aborted", self.ticker, shutdown_timeout);
+            handle.abort();
+        }
+        // the watchdog would wait for its next beat forever
+        liveness.remove(&self.ticker);
+
+        if let Some(discord_client) = self.discord_client {
+            if timeout(shutdown_timeout, discord_client.shutdown()).await.is_err() {
+                error!("Discord client for ticker {} did not shut down within {:?}", self.ticker, shutdown_timeout);
+            }
+        }
+    }
+}
+
+/// What every ticker is started with besides its config, kept while running to start the tickers of a reload
+struct TickerLauncher {
+    http_client: reqwest::Client,
+    discord_http_client: Option<DiscordHttpClient>,
+    alert_sender: AlertSender,
+    welcome_message: bool,
+    notify_guild_owners: bool,
+    nickname_stagger: Option<UpdateStagger>,
+    telegram_base_url: String,
+    has_board: bool,
+    ticker_controls: Option<Arc<TickerControls>>,
+    operator_ids: Vec<u64>,
+    ticker_health: TickerHealth,
+    api_usage: Arc<ApiUsage>,
+    price_req_senders: HashMap<QuoteSource, QuoteRequestSender>,
+    bot_update_sender: UnboundedSender<BotUpdateInfo>,
+    liveness: Liveness,
+    run_once: Option<FailedTickers>,
+    custom_sinks: Vec<Arc<dyn OutputSink>>,
+    price_alert_sink: Option<Arc<dyn OutputSink>>,
+    ticker_sinks: TickerSinks,
+    coingecko_provider: Arc<CoinGeckoProvider>,
+    coin_list: Option<Arc<CoinList>>,
+    stop_signal: StopSignal,
+}
+
+impl TickerLauncher {
+    /// Starts the fetch loop of a ticker, `None` if its config does not allow it, which is logged
+    async fn launch(&self, ticker_config: TickerConfig, unknown_coin_ids: &HashSet<String>) -> Option<RunningTicker> {
+        debug!(
+            "Loaded config for ticker: {}, is crypto? {}",
+            ticker_config.ticker, ticker_config.crypto
+        );
+
+        if ticker_config.name.is_empty() {
+            error!("Unknown asset for ticker {}, skipping", ticker_config.ticker);
+            return None;
+        }
+
+        if ticker_config.kind == TickerKind::Portfolio && ticker_config.holdings.is_empty() {
+            error!("Portfolio ticker {} has no holdings, skipping", ticker_config.ticker);
+            return None;
+        }
+
+        if ticker_config.kind == TickerKind::Ratio && ticker_config.ratio.is_none() {
+            error!("Ratio ticker {} has no `ratio` assets, skipping", ticker_config.ticker);
+            return None;
+        }
+
+        if ticker_config.kind == TickerKind::Countdown && ticker_config.countdown.is_none() {
+            error!("Countdown ticker {} has no `countdown` target, skipping", ticker_config.ticker);
+            return None;
+        }
+
+        if let Some(unknown_id) = ticker_config
+            .quoted_ids()
+            .into_iter()
+            .find(|id| ticker_config.crypto && unknown_coin_ids.contains(*id))
+        {
+            error!(
+                "Unknown CoinGecko id {} for ticker {}, skipping",
+                unknown_id, ticker_config.ticker
+            );
+            return None;
+        }
+
+        let ticker = ticker_config.ticker.to_string();
+        let mut bot_discord_client = None;
+        let update_target = match ticker_config.display_mode {
+            DisplayMode::Bot => {
+                if !is_bot_token_valid(ticker_config.discord_bot_token.expose()) {
+                    error!(
+                        "Invalid Discord bot token for ticker {}, skipping",
+                        ticker_config.ticker
+                    );
+                    return None;
+                }
+
+                let guild_join_handler =
+                    GuildJoinHandler::new(ticker.clone(), self.welcome_message, self.alert_sender.clone());
+                let guild_quarantine = GuildQuarantine::default();
+                let command_handler = self.ticker_controls.as_ref().map(|ticker_controls| {
+                    TickerCommandHandler::new(
+                        ticker.clone(),
+                        ticker_controls.clone(),
+                        self.operator_ids.clone(),
+                        self.ticker_health.clone(),
+                        guild_quarantine.clone(),
+                        self.api_usage.clone(),
+                    )
+                });
+                let discord_client = DiscordClient::with_event_handlers(
+                    ticker_config.discord_bot_token.expose(),
+                    self.discord_http_client.clone(),
+                    |client_builder| {
+                        let client_builder = client_builder.event_handler(guild_join_handler);
+                        match command_handler {
+                            Some(command_handler) => client_builder.event_handler(command_handler),
+                            None => client_builder,
+                        }
+                    },
+                )
+                .await
+                .notify_guild_owners(self.notify_guild_owners)
+                .guild_quarantine(guild_quarantine)
+                .nickname_stagger(self.nickname_stagger.clone())
+                .nickname_interval(ticker_config.nickname_interval());
+                bot_discord_client = Some(discord_client.clone());
+                BotUpdateTarget::Bot(discord_client)
+            }
+            DisplayMode::Board if self.has_board => BotUpdateTarget::BoardOnly,
+            DisplayMode::Board => {
+                error!(
+                    "Ticker {} is shown only on the price board, but there is no price board, skipping",
+                    ticker_config.ticker
+                );
+                return None;
+            }
+            DisplayMode::Webhook => match &ticker_config.webhook_url {
+                Some(webhook_url) => BotUpdateTarget::Webhook(DiscordWebhook::new(
+                    self.http_client.clone(),
+                    webhook_url.expose(),
+                    ticker_config.webhook_message_id,
+                )),
+                None => {
+                    error!(
+                        "Missing webhook URL for ticker {} in webhook mode, skipping",
+                        ticker_config.ticker
+                    );
+                    return None;
+                }
+            },
+        };
+
+        let sinks = ticker_config
+            .sinks
+            .iter()
+            .map(|sink_config| build_sink(sink_config, &ticker, &self.http_client, &self.telegram_base_url))
+            .chain(self.custom_sinks.iter().cloned())
+            .chain(self.price_alert_sink.clone())
+            .collect();
+        self.ticker_sinks.lock().unwrap().insert(ticker.clone(), sinks);
+
+        let job_senders: QuoteJobSenders = ticker_config
+            .quote_sources()
+            .into_iter()
+            .map(|source| (source, self.price_req_senders[&source].clone()))
+            .collect();
+        let bot_update_sender = self.bot_update_sender.clone();
+        let alert_sender = self.alert_sender.clone();
+        let derivatives_sender = match (ticker_config.derivatives, &ticker_config.status_format) {
+            (true, Some(status_format)) if uses_derivatives(status_format) => {
+                Some(self.price_req_senders[&QuoteSource::BinanceFutures].clone())
+            }
+            (true, _) => {
+                warn!(
+                    "Ticker {} has `derivatives` but no `{{funding}}` or `{{open_interest}}` in its `status_format`, \
+                     not fetching them",
+                    ticker
+                );
+                None
+            }
+            (false, _) => None,
+        };
+        let liveness = self.liveness.clone();
+        let ticker_health = self.ticker_health.clone();
+        let run_once = self.run_once.clone();
+        let control = match &self.ticker_controls {
+            Some(ticker_controls) => ticker_controls.subscribe(&ticker).await,
+            None => watch::channel(TickerOverride::default()).1,
+        };
+        let fingerprint = ticker_config.fingerprint;
+        let (stop_trigger, stop_signal) = child_stop_channel(&self.stop_signal);
+
+        trace!("Spawning task for ticker: {}", ticker);
+        let handle = tokio::spawn(supervisor::supervise(
+            ticker.clone(),
+            stop_signal,
+            move |stop_signal| {
+                run_periodic_fetch_job_loop(
+                    ticker_config.clone(),
+                    stop_signal,
+                    job_senders.clone(),
+                    bot_update_sender.clone(),
+                    update_target.clone(),
+                    alert_sender.clone(),
+                    derivatives_sender.clone(),
+                    liveness.clone(),
+                    ticker_health.clone(),
+                    run_once.clone(),
+                    control.clone(),
+                )
+            },
+        ));
+
+        Some(RunningTicker {
+            ticker,
+            fingerprint,
+            stop_trigger,
+            handle,
+            discord_client: bot_discord_client,
+        })
+    }
+
+    /// Applies the tickers of `reloaded`: stops the removed and changed ones, then starts the added and changed ones
+    async fn reload(&self, reloaded: Config, running: &mut Vec<RunningTicker>, shutdown_timeout: time::Duration) {
+        let fingerprints: Vec<(String, u64)> = running
+            .iter()
+            .map(|ticker| (ticker.ticker.clone(), ticker.fingerprint))
+            .collect();
+        let changes = TickerChanges::between(&fingerprints, &reloaded.tickers);
+
+        let stopped: HashSet<&String> = changes.stopped().collect();
+        let (stopping, kept): (Vec<RunningTicker>, Vec<RunningTicker>) =
+            std::mem::take(running).into_iter().partition(|ticker| stopped.contains(&ticker.ticker));
+        *running = kept;
+        for ticker in stopping {
+            info!("Stopping ticker {} for the reloaded config", ticker.ticker);
+            self.ticker_sinks.lock().unwrap().remove(&ticker.ticker);
+            ticker.stop(shutdown_timeout, &self.liveness).await;
+        }
+
+        let started: HashSet<&String> = changes.started().collect();
+        let mut tickers: Vec<TickerConfig> = reloaded
+            .tickers
+            .into_iter()
+            .filter(|ticker_config| started.contains(&ticker_config.ticker))
+            .collect();
+        resolve_omitted_names(&self.coingecko_provider, self.coin_list.as_deref(), &mut tickers).await;
+        let unknown_coin_ids = self
+            .coin_list
+            .as_ref()
+            .map(|coin_list| find_unknown_coin_ids(coin_list, &tickers))
+            .unwrap_or_default();
+        for ticker_config in tickers {
+            running.extend(self.launch(ticker_config, &unknown_coin_ids).await);
+        }
+
+        info!("Config reloaded, tickers: {}", changes);
+    }
+}
+
+/// The ticker engine: quote providers, the fetch loop of every ticker and the updates of their bots
+pub struct TickerBot {
+    config: Config,
+    custom_providers: Vec<(QuoteSource, Arc<dyn QuoteProvider>)>,
+    custom_sinks: Vec<Arc<dyn OutputSink>>,
+    reloads: Option<UnboundedReceiver<Config>>,
+}
+
+/// Sets up a `TickerBot`, e.g. `TickerBot::builder().config(config).build()?.run(shutdown)`
+#[derive(Default)]
+pub struct TickerBotBuilder {
+    config: Option<Config>,
+    custom_providers: Vec<(QuoteSource, Arc<dyn QuoteProvider>)>,
+    custom_sinks: Vec<Arc<dyn OutputSink>>,
+    reloads: Option<UnboundedReceiver<Config>>,
+}
+
+impl TickerBotBuilder {
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Quotes the tickers of `source` with `provider` instead of the built-in one
+    pub fn provider(mut self, source: QuoteSource, provider: Arc<dyn QuoteProvider>) -> Self {
+        self.custom_providers.retain(|(custom_source, _)| *custom_source != source);
+        self.custom_providers.push((source, provider));
+        self
+    }
+
+    /// Sends the updates of every ticker to `sink` as well, besides the sinks in the config of the ticker
+    pub fn sink(mut self, sink: Arc<dyn OutputSink>) -> Self {
+        self.custom_sinks.push(sink);
+        self
+    }
+
+    /// Applies the tickers of every config received from `reloads` while running, e.g. re-read on SIGHUP: the
+    /// added ones are started, the removed ones stopped and the changed ones restarted. Other settings only take
+    /// effect on a restart.
+    pub fn reloads(mut self, reloads: UnboundedReceiver<Config>) -> Self {
+        self.reloads = Some(reloads);
+        self
+    }
+
+    pub fn build(self) -> Result<TickerBot, TickerBotError> {
+        Ok(TickerBot {
+            config: self.config.ok_or(TickerBotError::MissingConfig)?,
+            custom_providers: self.custom_providers,
+            custom_sinks: self.custom_sinks,
+            reloads: self.reloads,
+        })
+    }
+}
+
+impl TickerBot {
+    pub fn builder() -> TickerBotBuilder {
+        TickerBotBuilder::default()
+    }
+
+    /// Resolves the omitted names and checks the coin ids of the config against CoinGecko, without starting any ticker
+    pub async fn validate(self) -> Result<(), TickerBotError> {
+        let prepared = self.prepare(true).await?;
+        if !prepared.unknown_coin_ids.is_empty() {
+            let mut unknown_coin_ids: Vec<String> = prepared.unknown_coin_ids.into_iter().collect();
+            unknown_coin_ids.sort();
+            return Err(TickerBotError::UnknownCoinIds(unknown_coin_ids));
+        }
+        Ok(())
+    }
+
+    /// Runs every ticker until `shutdown` completes and they all stopped
+    pub async fn run<S>(self, shutdown: S) -> Result<(), TickerBotError>
+    where
+        S: Future<Output = ()> + Send + 'static,
+    {
+        self.run_tickers(shutdown, None).await
+    }
+
+    /// Fetches the price of every ticker once and returns once the updates are applied, e.g. to be run by cron.
+    /// Fails with `TickerBotError::FailedTickers` if fetching the price or updating the bot failed for any ticker.
+    pub async fn run_once<S>(self, shutdown: S) -> Result<(), TickerBotError>
+    where
+        S: Future<Output = ()> + Send + 'static,
+    {
+        let failed_tickers = FailedTickers::default();
+        self.run_tickers(shutdown, Some(failed_tickers.clone())).await?;
+
+        let failed_tickers: Vec<String> = failed_tickers.lock().unwrap().iter().cloned().collect();
+        if !failed_tickers.is_empty() {
+            return Err(TickerBotError::FailedTickers(failed_tickers));
+        }
+        Ok(())
+    }
+
+    /// Runs every ticker until `shutdown` completes, or only once if `run_once` is given, see
+    /// `run_periodic_fetch_job_loop`
+    async fn run_tickers<S>(mut self, shutdown: S, run_once: Option<FailedTickers>) -> Result<(), TickerBotError>
+    where
+        S: Future<Output = ()> + Send + 'static,
+    {
+        let custom_providers = self.custom_providers.clone();
+        let custom_sinks = self.custom_sinks.clone();
+        let reloads = self.reloads.take();
+        let Prepared {
+            config,
+            http_client,
+            discord_http_client,
+            coingecko_provider,
+            coin_list,
+            unknown_coin_ids,
+        } = self.prepare(false).await?;
+        let alert_sender = AlertSender::new(http_client.clone(), config.alert.clone());
+        let max_concurrent_quote_requests = config.max_concurrent_quote_requests();
+
+        let mut providers: Vec<(QuoteSource, Arc<dyn QuoteProvider>)> = vec![
+            (QuoteSource::CoinGecko, coingecko_provider.clone()),
+            (
+                QuoteSource::YahooFinance,
+                Arc::new(YahooFinanceProvider::new(http_client.clone(), config.yahoo_finance_base_url())),
+            ),
+            (
+                QuoteSource::Twse,
+                Arc::new(TwseProvider::new(http_client.clone(), config.twse_base_url())),
+            ),
+            (
+                QuoteSource::Binance,
+                Arc::new(BinanceProvider::new(http_client.clone(), config.binance_base_url())),
+            ),
+            (
+                QuoteSource::CoinCap,
+                Arc::new(CoinCapProvider::with_api_keys(
+                    http_client.clone(),
+                    config.coincap_base_url(),
+                    Arc::new(ApiKeys::new("CoinCap", config.coincap_api_keys(), config.api_key_rotation)),
+                )),
+            ),
+            (
+                QuoteSource::Etherscan,
+                Arc::new(EtherscanGasProvider::with_api_keys(
+                    http_client.clone(),
+                    config.etherscan_base_url(),
+                    Arc::new(ApiKeys::new("Etherscan", config.etherscan_api_keys(), config.api_key_rotation)),
+                )),
+            ),
+            (
+                QuoteSource::CoinGeckoGlobal,
+                Arc::new(CoinGeckoGlobalProvider::with_api_keys(
+                    http_client.clone(),
+                    config.coingecko_base_url(),
+                    coingecko_provider.api_keys(),
+                )),
+            ),
+            (
+                QuoteSource::AlternativeMe,
+                Arc::new(FearAndGreedProvider::new(http_client.clone(), config.alternative_me_base_url())),
+            ),
+            (
+                QuoteSource::CustomHttp,
+                Arc::new(CustomHttpProvider::new(http_client.clone(), config.custom_http.clone())),
+            ),
+            (QuoteSource::Exec, Arc::new(ExecProvider::new(config.exec.clone()))),
+            (
+                QuoteSource::Chainlink,
+                Arc::new(ChainlinkProvider::new(http_client.clone(), config.evm_rpc_url())),
+            ),
+            (
+                QuoteSource::GeckoTerminal,
+                Arc::new(GeckoTerminalProvider::new(http_client.clone(), config.geckoterminal_base_url())),
+            ),
+            (
+                QuoteSource::DefiLlama,
+                Arc::new(DefiLlamaProvider::new(http_client.clone(), config.defillama_base_url())),
+            ),
+            (
+                QuoteSource::BeaconChain,
+                Arc::new(BeaconChainProvider::new(http_client.clone(), config.beaconchain_base_url())),
+            ),
+            (
+                QuoteSource::OpenSea,
+                Arc::new(OpenSeaProvider::new(
+                    http_client.clone(),
+                    config.opensea_base_url(),
+                    config.opensea_api_key.clone(),
+                )),
+            ),
+            (
+                QuoteSource::BinanceFutures,
+                Arc::new(BinanceFuturesProvider::new(http_client.clone(), config.binance_futures_base_url())),
+            ),
+        ];
+
+        for (source, provider) in custom_providers {
+            info!("Using custom provider {} for {:?}", provider.name(), source);
+            providers.retain(|(built_in_source, _)| *built_in_source != source);
+            providers.push((source, provider));
+        }
+
+        let (stop_trigger, stop_signal) = stop_channel();
+        let mut tasks = Vec::new();
+
+        let quote_deadline = config.http.deadline();
+        let quote_request_queue_size = config.quote_request_queue_size();
+        let quote_cache_ttl = config.quote_cache_ttl();
+        let circuit_breaker_threshold = config.circuit_breaker_threshold();
+        let api_usage = Arc::new(ApiUsage::load(config.api_usage_file(), config.api_budgets.clone()).await);
+        let mut price_req_senders = HashMap::new();
+        for (source, mut provider) in providers {
+            let (price_req_sender, price_req_receiver) = quote_request_queue(quote_request_queue_size);
+            let price_req_sender = price_req_sender.wait_timeout(quote_deadline);
+
+            // only the calls reaching the provider count against its budget
+            if let Some(budget) = api_usage.budget(source) {
+                let pacing = config.api_budget_stretch().then(|| price_req_sender.pacing().clone());
+                provider = Arc::new(BudgetedProvider::new(provider, source, budget, api_usage.clone(), pacing));
+            }
+            // the cache wraps the breaker, so that cached quotes are still served while the provider is down
+            if circuit_breaker_threshold > 0 {
+                provider = Arc::new(CircuitBreakerProvider::new(
+                    provider,
+                    circuit_breaker_threshold,
+                    config.circuit_breaker_cooldown(),
+                ));
+            }
+            if !quote_cache_ttl.is_zero() {
+                provider = Arc::new(CachedProvider::new(provider, quote_cache_ttl));
+            }
+            price_req_senders.insert(source, price_req_sender);
+
+            trace!("Starting {} price request consumer...", provider.name());
+            tasks.push((
+                format!("{} price request consumer", provider.name()),
+                tokio::spawn(consume_quote_requests(
+                    price_req_receiver,
+                    provider.clone(),
+                    quote_deadline,
+                    max_concurrent_quote_requests,
+                    stop_signal.clone(),
+                )),
+            ));
+        }
+        let (bot_update_sender, bot_update_receiver) = mpsc::unbounded_channel();
+
+        let shutdown_timeout = config.shutdown_timeout();
+        let mut discord_clients = Vec::new();
+
+        let mut board = None;
+        let mut board_refresh_interval = time::Duration::from_secs(BoardConfig::DEFAULT_REFRESH_SECS);
+        if let Some(board_config) = &config.board {
+            if is_bot_token_valid(board_config.discord_bot_token.expose()) {
+                let discord_client =
+                    DiscordClient::new(board_config.discord_bot_token.expose(), discord_http_client.clone()).await;
+                discord_clients.push(("price board".to_string(), discord_client.clone()));
+                board_refresh_interval = board_config.refresh_interval();
+                board = Some(
+                    PriceBoard::load(
+                        discord_client,
+                        board_config.channel_id,
+                        board_config.title(),
+                        board_config.state_file(),
+                    )
+                    .await,
+                );
+            } else {
+                error!("Invalid Discord bot token for the price board, skipping");
+            }
+        }
+
+        // answering commands never ends, a single run has no use for it
+        let mut price_alert_sink: Option<Arc<dyn OutputSink>> = None;
+        match &config.commands {
+            Some(commands_config) if run_once.is_none() => {
+                if is_bot_token_valid(commands_config.discord_bot_token.expose()) {
+                    let price_alerts = Arc::new(
+                        PriceAlertStore::load(commands_config.alerts_file(), commands_config.max_alerts_per_user()).await,
+                    );
+                    let command_handler = CommandHandler::new(
+                        vec![(QuoteSource::CoinGecko, price_req_senders[&QuoteSource::CoinGecko].clone())],
+                        coingecko_provider.clone(),
+                        coin_list.clone(),
+                        price_alerts.clone(),
+                        config
+                            .tickers
+                            .iter()
+                            .map(|ticker_config| (ticker_config.ticker.clone(), ticker_config.vs_currency()))
+                            .collect(),
+                    );
+                    let discord_client = DiscordClient::with_event_handlers(
+                        commands_config.discord_bot_token.expose(),
+                        discord_http_client.clone(),
+                        |client_builder| client_builder.event_handler(command_handler),
+                    )
+                    .await;
+                    discord_clients.push(("slash commands".to_string(), discord_client.clone()));
+                    price_alert_sink = Some(Arc::new(PriceAlertSink::new(price_alerts, discord_client)));
+                } else {
+                    error!("Invalid Discord bot token for the slash commands, skipping");
+                }
+            }
+            _ => {}
+        }
+
+        // `/ticker` comes with the slash commands, and would leave a single run paused for good
+        let ticker_controls = match &config.commands {
+            Some(commands_config) if run_once.is_none() => {
+                Some(Arc::new(TickerControls::load(commands_config.ticker_controls_file()).await))
+            }
+            _ => None,
+        };
+
+        let liveness = Liveness::default();
+        let ticker_sinks = TickerSinks::default();
+        let launcher = TickerLauncher {
+            http_client: http_client.clone(),
+            discord_http_client: discord_http_client.clone(),
+            alert_sender: alert_sender.clone(),
+            welcome_message: config.welcome_message(),
+            notify_guild_owners: config.notify_guild_owners(),
+            nickname_stagger: config.nickname_update_spacing().map(UpdateStagger::new),
+            telegram_base_url: config.telegram_base_url(),
+            // a board whose token is invalid is skipped, its tickers with it
+            has_board: board.is_some(),
+            ticker_controls,
+            operator_ids: config.commands.as_ref().map(|commands| commands.operator_ids.clone()).unwrap_or_default(),
+            ticker_health: TickerHealth::default(),
+            api_usage: api_usage.clone(),
+            price_req_senders,
+            bot_update_sender,
+            liveness: liveness.clone(),
+            run_once: run_once.clone(),
+            custom_sinks,
+            price_alert_sink,
+            ticker_sinks: ticker_sinks.clone(),
+            coingecko_provider,
+            coin_list,
+            stop_signal: stop_signal.clone(),
+        };
+        let mut running = Vec::new();
+        for ticker_config in config.tickers {
+            running.extend(launcher.launch(ticker_config, &unknown_coin_ids).await);
+        }
+        // only the tickers hold senders once the launcher is dropped, so that the consumers see once they all stopped
+        let launcher = match reloads {
+            Some(reloads) if run_once.is_none() => Some((launcher, reloads)),
+            _ => {
+                drop(launcher);
+                None
+            }
+        };
+
+        tasks.push((
+            "bot update consumer".to_string(),
+            tokio::spawn(consume_bot_updates(
+                bot_update_receiver,
+                alert_sender,
+                board,
+                board_refresh_interval,
+                ticker_sinks,
+                run_once.clone(),
+                stop_signal.clone(),
+            )),
+        ));
+
+        #[cfg(feature = "systemd")]
+        {
+            // not a task to wait for, the clients may never connect before the shutdown
+            tokio::spawn(systemd::notify_ready(
+                discord_clients
+                    .iter()
+                    .map(|(_, discord_client)| discord_client.clone())
+                    .chain(running.iter().filter_map(|ticker| ticker.discord_client.clone()))
+                    .collect(),
+            ));
+            // the watchdog runs until the stop signal, a single run does not wait for it
+            if run_once.is_none() {
+                tasks.push((
+                    "systemd watchdog".to_string(),
+                    tokio::spawn(systemd::run_watchdog(liveness, stop_signal.clone())),
+                ));
+            }
+        }
+
+        tokio::spawn(async move {
+            shutdown.await;
+            info!("Shutdown requested. Stopping...");
+            #[cfg(feature = "systemd")]
+            systemd::notify_stopping();
+            stop_trigger.stop();
+        });
+
+        if let Some((launcher, mut reloads)) = launcher {
+            let mut stop_signal = stop_signal.clone();
+            loop {
+                tokio::select! {
+                    _ = stop_signal.stopped() => break,
+                    Some(reloaded) = reloads.recv() => {
+                        launcher.reload(reloaded, &mut running, shutdown_timeout).await;
+                    }
+                }
+            }
+        }
+        for ticker in running {
+            tasks.push((ticker.ticker.clone(), ticker.handle));
+            discord_clients.extend(ticker.discord_client.map(|discord_client| (ticker.ticker, discord_client)));
+        }
+
+        // the grace period only starts with the shutdown, tasks may run for as long as they like before it
+        let mut stop_signal = stop_signal;
+        let shutdown_deadline = async move {
+            stop_signal.stopped().await;
+            tokio::time::sleep(shutdown_timeout).await;
+        };
+
+        info!("Waiting for all tasks to finish...");
+        let stuck_tasks = supervisor::join_until(tasks, shutdown_deadline).await;
+        for task in &stuck_tasks {
+            error!("Task for {} did not stop within {:?}, aborted", task, shutdown_timeout);
+        }
+
+        info!("All tasks finished, shutting down Discord clients...");
+        for (ticker, discord_client) in discord_clients {
+            debug!("Shutting down Discord client for ticker: {}", ticker);
+            if timeout(shutdown_timeout, discord_client.shutdown()).await.is_err() {
+                error!("Discord client for ticker {} did not shut down within {:?}", ticker, shutdown_timeout);
+            }
+        }
+
+        info!("All Discord clients shut down.");
+
+        if !config.api_budgets.is_empty() {
+            api_usage.save().await;
+        }
+
+        if !stuck_tasks.is_empty() {
+            return Err(TickerBotError::ShutdownTimeout(stuck_tasks));
+        }
+        Ok(())
+    }
+
+    /// Fills in the omitted names and finds the unknown coin ids. Without the list of coins the ids
+    /// are not checked, unless `require_coin_list`. Configs with no use for the list do not fetch it.
+    async fn prepare(mut self, require_coin_list: bool) -> Result<Prepared, TickerBotError> {
+        let http_client_factory = HttpClientFactory::new(self.config.http.clone());
+        let http_client = http_client_factory.build().map_err(TickerBotError::HttpClient)?;
+        let discord_http_client = http_client_factory
+            .build_discord()
+            .map_err(TickerBotError::DiscordHttpClient)?;
+
+        let coingecko_provider = Arc::new(CoinGeckoProvider::with_api_keys(
+            http_client.clone(),
+            self.config.coingecko_base_url(),
+            Arc::new(ApiKeys::new(
+                "CoinGecko",
+                self.config.coingecko_api_keys(),
+                self.config.api_key_rotation,
+            )),
+        ));
+
+        // e.g. stocks only, which a CoinGecko outage should not fail
+        let coin_list = if !self.config.needs_coin_list() {
+            debug!("No ticker uses CoinGecko coin ids, not fetching the list of coins");
+            None
+        } else {
+            match coingecko_provider.fetch_coin_list().await {
+                Ok(coin_list) => Some(coin_list),
+                Err(error) if require_coin_list => return Err(TickerBotError::CoinList(error)),
+                Err(error) => {
+                    warn!("Error fetching the list of coins from CoinGecko, coin ids are not validated: {}", error);
+                    None
+                }
+            }
+        };
+
+        resolve_omitted_names(&coingecko_provider, coin_list.as_ref(), &mut self.config.tickers).await;
+        let unknown_coin_ids = coin_list
+            .as_ref()
+            .map(|coin_list| find_unknown_coin_ids(coin_list, &self.config.tickers))
+            .unwrap_or_default();
+
+        Ok(Prepared {
+            config: self.config,
+            http_client,
+            discord_http_client,
+            coingecko_provider,
+            coin_list: coin_list.map(Arc::new),
+            unknown_coin_ids,
+        })
+    }
+}
+
+#[cfg_attr(not(feature = "mqtt"), allow(unused_variables))]
+fn build_sink(
+    sink_config: &SinkConfig,
+    ticker: &str,
+    http_client: &reqwest::Client,
+    telegram_base_url: &str,
+) -> Arc<dyn OutputSink> {
+    match sink_config {
+        SinkConfig::Stdout => Arc::new(StdoutSink),
+        SinkConfig::Telegram(telegram) => Arc::new(TelegramSink::new(
+            http_client.clone(),
+            telegram_base_url.to_string(),
+            telegram.bot_token.expose().to_string(),
+            telegram.chat_id.clone(),
+            match telegram.target {
+                TelegramTargetConfig::Message => TelegramTarget::Message,
+                TelegramTargetConfig::Title => TelegramTarget::Title,
+            },
+            telegram.message_id,
+        )),
+        #[cfg(feature = "mqtt")]
+        SinkConfig::Mqtt(mqtt) => {
+            let mut options = MqttOptions::new(mqtt.client_id(ticker), mqtt.host.clone(), mqtt.port());
+            if let Some(username) = &mqtt.username {
+                options.set_credentials(username.clone(), mqtt.password.as_ref().map(|password| password.expose().to_string()).unwrap_or_default());
+            }
+            Arc::new(MqttSink::new(options, mqtt.topic(), ticker, mqtt.retain()))
+        }
+    }
+}
+
+fn is_bot_token_valid(bot_token: &str) -> bool {
+    // just check if the token is empty
+    !bot_token.trim().is_empty() && bot_token.is_ascii()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quote::mock::MockProvider;
+    use crate::quote::response::AssetQuoteResponse;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn config(coingecko_base_url: &str) -> Config {
+        serde_json::from_value(serde_json::json!({
+            "coingecko_api_key": "",
+            "coingecko_base_url": coingecko_base_url,
+            "tickers": []
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_builder() {
+        assert!(matches!(TickerBot::builder().build(), Err(TickerBotError::MissingConfig)));
+
+        let bot = TickerBot::builder()
+            .config(config("http://localhost"))
+            .provider(QuoteSource::CoinGecko, Arc::new(MockProvider::new(Vec::new())))
+            .provider(QuoteSource::Binance, Arc::new(MockProvider::new(Vec::new())))
+            .provider(QuoteSource::CoinGecko, Arc::new(MockProvider::new(Vec::new())))
+            .sink(Arc::new(StdoutSink))
+            .build()
+            .unwrap();
+        let sources: Vec<QuoteSource> = bot.custom_providers.iter().map(|(source, _)| *source).collect();
+        assert_eq!(vec![QuoteSource::Binance, QuoteSource::CoinGecko], sources);
+        assert_eq!(1, bot.custom_sinks.len());
+    }
+
+    #[tokio::test]
+    async fn test_run_until_shutdown() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/coins/list"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+            .mount(&server)
+            .await;
+
+        let bot = TickerBot::builder().config(config(&server.uri())).build().unwrap();
+        assert!(bot.run(async {}).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_once() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/coins/list"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+            .mount(&server)
+            .await;
+        // the price of AAPL, and the config error of BTC
+        Mock::given(method("POST"))
+            .and(path("/webhook"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id": "1"}"#))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let webhook_url = format!("{}/webhook", server.uri());
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "coingecko_api_key": "",
+            "coingecko_base_url": server.uri(),
+            "tickers": [
+                {"ticker": "AAPL", "display_mode": "webhook", "webhook_url": webhook_url},
+                {"ticker": "BTC", "providers": ["binance"], "display_mode": "webhook", "webhook_url": webhook_url}
+            ]
+        }))
+        .unwrap();
+        let quote = AssetQuoteResponse {
+            name: "AAPL".to_string(),
+            price: "187.5".parse().unwrap(),
+            price_change_24h: "1.5".parse().unwrap(),
+            ..Default::default()
+        };
+
+        let bot = TickerBot::builder()
+            .config(config)
+            .provider(QuoteSource::YahooFinance, Arc::new(MockProvider::new(vec![Ok(quote)])))
+            .provider(
+                QuoteSource::Binance,
+                Arc::new(MockProvider::new(vec![Err(QuoteRequestError::NotFound("BTC".to_string()))])),
+            )
+            .build()
+            .unwrap();
+
+        let result = bot.run_once(std::future::pending()).await;
+        assert!(
+            matches!(&result, Err(TickerBotError::FailedTickers(tickers)) if tickers == &vec!["BTC".to_string()]),
+            "{:?}",
+            result
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reload() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/coins/list"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id": "1"}"#))
+            .mount(&server)
+            .await;
+
+        let config = |tickers: serde_json::Value| -> Config {
+            serde_json::from_value(serde_json::json!({
+                "coingecko_api_key": "",
+                "coingecko_base_url": server.uri(),
+                "tickers": tickers
+            }))
+            .unwrap()
+        };
+        let webhook = |ticker: &str| {
+            serde_json::json!({
+                "ticker": ticker,
+                "display_mode": "webhook",
+                "webhook_url": format!("{}/webhook/{}", server.uri(), ticker)
+            })
+        };
+        let quote = |name: &str| {
+            Ok(AssetQuoteResponse {
+                name: name.to_string(),
+                price: "187.5".parse().unwrap(),
+                price_change_24h: "1.5".parse().unwrap(),
+                ..Default::default()
+            })
+        };
+        let posted = |webhook_path: &'static str| {
+            let server = &server;
+            async move {
+                timeout(time::Duration::from_secs(5), async {
+                    while !server
+                        .received_requests()
+                        .await
+                        .unwrap()
+                        .iter()
+                        .any(|request| request.url.path() == webhook_path)
+                    {
+                        tokio::time::sleep(time::Duration::from_millis(10)).await;
+                    }
+                })
+                .await
+                .is_ok()
+            }
+        };
+
+        let (reload_sender, reload_receiver) = mpsc::unbounded_channel();
+        let (shutdown_sender, shutdown_receiver) = tokio::sync::oneshot::channel::<()>();
+        let bot = TickerBot::builder()
+            .config(config(serde_json::json!([webhook("AAPL")])))
+            .provider(
+                QuoteSource::YahooFinance,
+                Arc::new(MockProvider::new(vec![quote("AAPL"), quote("MSFT")])),
+            )
+            .reloads(reload_receiver)
+            .build()
+            .unwrap();
+        let run = tokio::spawn(bot.run(async {
+            let _ = shutdown_receiver.await;
+        }));
+
+        assert!(posted("/webhook/AAPL").await, "AAPL never updated");
+        reload_sender.send(config(serde_json::json!([webhook("MSFT")]))).unwrap();
+        assert!(posted("/webhook/MSFT").await, "MSFT added by the reload never updated");
+
+        shutdown_sender.send(()).unwrap();
+        assert!(run.await.unwrap().is_ok());
+    }
+}
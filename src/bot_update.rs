@@ -1,8 +1,456 @@
+use crate::alert::{error_chain, AlertSender, FailureStreak};
+use crate::discord::board::PriceBoard;
 use crate::discord::client::DiscordClient;
+use crate::discord::webhook::DiscordWebhook;
+use crate::format::{fits_status, truncate_status, MAX_STATUS_LEN};
+use crate::health::FailedTickers;
+use crate::shutdown::StopSignal;
+use crate::sink::output::OutputSink;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tokio::time::{interval, Instant};
+use tracing::field::Empty;
+use tracing::{debug, instrument, warn, Span};
 
 #[derive(Clone, Debug)]
 pub struct BotUpdateInfo {
+    pub ticker: String,
     pub name: String,
     pub status: String,
-    pub discord_client: DiscordClient,
-}
\ No newline at end of file
+    pub target: BotUpdateTarget,
+    pub channel_name: Option<ChannelNameUpdate>,
+    pub status_rotation: Option<StatusRotation>,
+    pub quote: Option<QuoteData>,
+    pub fetched_at: Option<Instant>, // When fetching the price of the update started, to time the whole update
+}
+
+/// Raw figures behind the name and status of an update, for sinks that publish data rather than text.
+/// Not given for updates that only mark the last price as stale or the market as closed.
+#[derive(Clone, Debug)]
+pub struct QuoteData {
+    pub price: BigDecimal,
+    pub price_change_24h: BigDecimal,
+    pub vs_currency: String,
+}
+
+/// Further statuses the bot cycles through after `BotUpdateInfo::status`, e.g. prices of secondary assets
+#[derive(Clone, Debug)]
+pub struct StatusRotation {
+    pub statuses: Vec<String>,
+    pub interval: Duration,
+}
+
+/// Position of a bot cycling through its statuses
+struct StatusCycle {
+    statuses: Vec<String>,
+    index: usize,
+    interval: Duration,
+    next_at: Instant,
+}
+
+impl StatusCycle {
+    /// Moves to the next status if it's time, returning the status to show
+    fn advance(&mut self, now: Instant) -> Option<&str> {
+        if now < self.next_at {
+            return None;
+        }
+
+        self.index = (self.index + 1) % self.statuses.len();
+        self.next_at = now + self.interval;
+        Some(&self.statuses[self.index])
+    }
+}
+
+/// New name of a channel showing the price, e.g. a locked voice channel
+#[derive(Clone, Debug)]
+pub struct ChannelNameUpdate {
+    pub channel_id: u64,
+    pub name: String,
+}
+
+/// Where the price of a ticker is displayed
+#[derive(Clone, Debug)]
+pub enum BotUpdateTarget {
+    Bot(DiscordClient), // Nickname and activity of a bot
+    Webhook(DiscordWebhook), // A message kept up to date through a webhook
+    BoardOnly, // Nothing but the price board
+}
+
+impl BotUpdateTarget {
+    /// The bot of the ticker, if the ticker has one
+    pub fn discord_client(&self) -> Option<&DiscordClient> {
+        match self {
+            BotUpdateTarget::Bot(discord_client) => Some(discord_client),
+            BotUpdateTarget::Webhook(_) | BotUpdateTarget::BoardOnly => None,
+        }
+    }
+}
+
+#[async_trait]
+impl OutputSink for BotUpdateTarget {
+    fn name(&self) -> &str {
+        match self {
+            BotUpdateTarget::Bot(_) => "Discord bot",
+            BotUpdateTarget::Webhook(_) => "Discord webhook",
+            BotUpdateTarget::BoardOnly => "price board",
+        }
+    }
+
+    async fn publish(&self, update: &BotUpdateInfo) -> Result<(), Box<dyn Error + Send + Sync>> {
+        match self {
+            BotUpdateTarget::Bot(discord_client) => {
+                Ok(discord_client.update_bot(update.name.clone(), update.status.clone()).await?)
+            }
+            BotUpdateTarget::Webhook(webhook) => {
+                webhook.update_message(&format!("{} | {}", update.name, update.status)).await
+            }
+            BotUpdateTarget::BoardOnly => Ok(()),
+        }
+    }
+}
+
+/// How often the consumer checks whether a bot should move on to its next status
+const STATUS_ROTATION_TICK: Duration = Duration::from_secs(1);
+
+/// Maximum number of queued bot updates taken at once, before superseded ones are dropped
+const BOT_UPDATE_BATCH_LIMIT: usize = 64;
+
+/// Cuts the status and the rotated statuses of the update to what Discord accepts, returning whether any was too long
+fn truncate_statuses(update: &mut BotUpdateInfo) -> bool {
+    let rotated = update.status_rotation.iter_mut().flat_map(|rotation| rotation.statuses.iter_mut());
+    let mut truncated = false;
+    for status in std::iter::once(&mut update.status).chain(rotated) {
+        if !fits_status(status) {
+            *status = truncate_status(status);
+            truncated = true;
+        }
+    }
+    truncated
+}
+
+/// The latest update of every ticker among `updates`, in the order the tickers were last updated
+fn latest_updates(updates: impl IntoIterator<Item = BotUpdateInfo>) -> Vec<BotUpdateInfo> {
+    let mut latest: Vec<BotUpdateInfo> = Vec::new();
+
+    for update in updates {
+        latest.retain(|queued| queued.ticker != update.ticker);
+        latest.push(update);
+    }
+
+    latest
+}
+
+/// Further sinks of every ticker, replaced when a reload restarts it
+pub type TickerSinks = Arc<Mutex<HashMap<String, Vec<Arc<dyn OutputSink>>>>>;
+
+/// Sender of the worker of a ticker and the sinks it was started with
+type Worker = (UnboundedSender<BotUpdateInfo>, Vec<Arc<dyn OutputSink>>);
+
+fn same_sinks(a: &[Arc<dyn OutputSink>], b: &[Arc<dyn OutputSink>]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| Arc::ptr_eq(a, b))
+}
+
+/// Puts every update on the price board, if there is one, and hands it over to the worker of its ticker,
+/// so that a bot slow to update doesn't hold up the others. The worker also sends it to the further
+/// `sinks` of the ticker. The board is re-rendered at most once per `board_refresh_interval`.
+///
+/// Once every ticker dropped its sender, e.g. in the run-once mode, the last updates are applied before returning:
+/// the workers are waited for, adding the tickers they failed to update to `failed_tickers`, and the board is
+/// rendered a last time.
+pub async fn consume_bot_updates(
+    mut bot_update_receiver: UnboundedReceiver<BotUpdateInfo>,
+    alert_sender: AlertSender,
+    mut board: Option<PriceBoard>,
+    board_refresh_interval: Duration,
+    sinks: TickerSinks,
+    failed_tickers: Option<FailedTickers>,
+    mut stop_signal: StopSignal,
+) {
+    let mut board_refresh = interval(board_refresh_interval);
+    let mut workers: HashMap<String, Worker> = HashMap::new();
+    let mut worker_handles = Vec::new();
+    let mut received = Vec::new();
+    let mut all_sent = false;
+
+    loop {
+        tokio::select! {
+            count = bot_update_receiver.recv_many(&mut received, BOT_UPDATE_BATCH_LIMIT) => {
+                if count == 0 {
+                    all_sent = true;
+                    break;
+                }
+            }
+            _ = stop_signal.stopped() => {
+                debug!("Stop signal received, quit consuming bot updates");
+                break;
+            }
+            _ = board_refresh.tick(), if board.is_some() => {
+                if let Some(board) = board.as_mut() {
+                    if let Err(e) = board.render().await {
+                        warn!("Error rendering price board: {}", e);
+                    }
+                }
+                continue;
+            }
+        }
+
+        for update in latest_updates(received.drain(..)) {
+            if let Some(board) = board.as_mut() {
+                board.set_row(&update.ticker, &update.name, &update.status);
+            }
+
+            // the worker of a ticker a reload gave other sinks finishes its last update and is replaced
+            let ticker_sinks = sinks.lock().unwrap().get(&update.ticker).cloned().unwrap_or_default();
+            if workers
+                .get(&update.ticker)
+                .is_some_and(|(_, worker_sinks)| !same_sinks(worker_sinks, &ticker_sinks))
+            {
+                workers.remove(&update.ticker);
+            }
+
+            let (worker, _) = workers.entry(update.ticker.clone()).or_insert_with(|| {
+                let (worker_sender, worker_receiver) = mpsc::unbounded_channel();
+                worker_handles.push(tokio::spawn(run_bot_update_worker(
+                    update.ticker.clone(),
+                    worker_receiver,
+                    ticker_sinks.clone(),
+                    alert_sender.clone(),
+                    failed_tickers.clone(),
+                )));
+                (worker_sender, ticker_sinks)
+            });
+
+            if let Err(e) = worker.send(update) {
+                warn!("Bot update worker of {} is gone, restarting it", e.0.ticker);
+                workers.remove(&e.0.ticker);
+            }
+        }
+    }
+
+    if all_sent {
+        debug!("No more bot updates, waiting for the last ones to be applied");
+        drop(workers);
+        for handle in worker_handles {
+            let _ = handle.await;
+        }
+        if let Some(board) = board.as_mut() {
+            if let Err(e) = board.render().await {
+                warn!("Error rendering price board: {}", e);
+            }
+        }
+    }
+}
+
+/// Applies the updates of one ticker, skipping the ones superseded while the previous update was applied,
+/// and alerts operators when they keep failing. Stops once the consumer drops its sender.
+///
+/// The span records how long the last Discord update took and how long after the start of its fetch it was done.
+#[instrument(skip_all, fields(ticker = %ticker, discord_ms = Empty, latency_ms = Empty))]
+async fn run_bot_update_worker(
+    ticker: String,
+    mut update_receiver: UnboundedReceiver<BotUpdateInfo>,
+    sinks: Vec<Arc<dyn OutputSink>>,
+    alert_sender: AlertSender,
+    failed_tickers: Option<FailedTickers>,
+) {
+    let mut failure_streak = FailureStreak::new(alert_sender.failure_threshold());
+    let mut status_rotation: Option<(DiscordClient, StatusCycle)> = None;
+    let mut status_rotation_tick = interval(STATUS_ROTATION_TICK);
+    let mut received = Vec::new();
+    let mut status_truncation_logged = false;
+
+    loop {
+        tokio::select! {
+            count = update_receiver.recv_many(&mut received, BOT_UPDATE_BATCH_LIMIT) => {
+                if count == 0 {
+                    break;
+                }
+            }
+            _ = status_rotation_tick.tick(), if status_rotation.is_some() => {
+                if let Some((discord_client, status_cycle)) = status_rotation.as_mut() {
+                    if let Some(status) = status_cycle.advance(Instant::now()) {
+                        discord_client.set_activity(status).await;
+                    }
+                }
+                continue;
+            }
+        }
+
+        let skipped = received.len().saturating_sub(1);
+        let Some(mut update) = received.pop() else {
+            continue;
+        };
+        received.clear();
+        if skipped > 0 {
+            debug!("Skipped {} superseded bot updates of {}", skipped, ticker);
+        }
+
+        if truncate_statuses(&mut update) && !status_truncation_logged {
+            warn!(
+                "Status of {} is longer than the {} characters Discord accepts, cutting it, e.g. to `{}`",
+                ticker, MAX_STATUS_LEN, update.status
+            );
+            status_truncation_logged = true;
+        }
+
+        status_rotation = match (&update.status_rotation, update.target.discord_client()) {
+            (Some(rotation), Some(discord_client)) if !rotation.statuses.is_empty() => {
+                let mut statuses = vec![update.status.clone()];
+                statuses.extend(rotation.statuses.iter().cloned());
+                Some((
+                    discord_client.clone(),
+                    StatusCycle {
+                        statuses,
+                        index: 0,
+                        interval: rotation.interval,
+                        next_at: Instant::now() + rotation.interval,
+                    },
+                ))
+            }
+            _ => None,
+        };
+
+        if let (Some(channel_name), Some(discord_client)) =
+            (&update.channel_name, update.target.discord_client())
+        {
+            if let Err(e) = discord_client
+                .rename_channel(channel_name.channel_id, &channel_name.name)
+                .await
+            {
+                warn!(
+                    "Error renaming channel {} for {}: {}",
+                    channel_name.channel_id, ticker, e
+                );
+            }
+        }
+
+        for sink in &sinks {
+            if let Err(error) = sink.publish(&update).await {
+                warn!("Error sending the update of {} to {}: {}", ticker, sink.name(), error);
+            }
+        }
+
+        let publish_started_at = Instant::now();
+        let result = update.target.publish(&update).await;
+        let discord_ms = publish_started_at.elapsed().as_millis() as u64;
+        let latency_ms = update.fetched_at.map(|fetched_at| fetched_at.elapsed().as_millis() as u64);
+        Span::current().record("discord_ms", discord_ms);
+        if let Some(latency_ms) = latency_ms {
+            Span::current().record("latency_ms", latency_ms);
+        }
+        debug!(
+            histogram.discord_ms = discord_ms,
+            histogram.latency_ms = latency_ms,
+            "Updated {} of {} in {} ms, {:?} ms after fetching its price",
+            update.target.name(),
+            ticker,
+            discord_ms,
+            latency_ms
+        );
+
+        match result {
+            Ok(_) => {
+                let failure_count = failure_streak.count();
+                if failure_streak.record_success() {
+                    let message = format!(
+                        "✅ {}: updating the Discord bot works again after {} failures",
+                        ticker, failure_count
+                    );
+                    alert_sender.send(update.target.discord_client(), &message).await;
+                }
+            }
+            Err(error) => {
+                warn!("Error updating Discord bot for {}: {}", ticker, error);
+                if let Some(failed_tickers) = &failed_tickers {
+                    failed_tickers.lock().unwrap().insert(ticker.clone());
+                }
+                if failure_streak.record_failure() {
+                    let message = format!(
+                        "⚠️ {}: updating the Discord bot failed {} times in a row: {}",
+                        ticker,
+                        failure_streak.count(),
+                        error_chain(error.as_ref())
+                    );
+                    alert_sender.send(update.target.discord_client(), &message).await;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(ticker: &str, name: &str) -> BotUpdateInfo {
+        BotUpdateInfo {
+            ticker: ticker.to_string(),
+            name: name.to_string(),
+            status: String::new(),
+            target: BotUpdateTarget::BoardOnly,
+            channel_name: None,
+            status_rotation: None,
+            quote: None,
+            fetched_at: None,
+        }
+    }
+
+    #[test]
+    fn test_latest_updates() {
+        let updates = latest_updates(vec![
+            update("BTC", "$65,000"),
+            update("ETH", "$3,400"),
+            update("BTC", "$65,100"),
+            update("SOL", "$150"),
+            update("BTC", "$65,200"),
+        ]);
+
+        let names: Vec<(&str, &str)> = updates
+            .iter()
+            .map(|update| (update.ticker.as_str(), update.name.as_str()))
+            .collect();
+        assert_eq!(vec![("ETH", "$3,400"), ("SOL", "$150"), ("BTC", "$65,200")], names);
+        assert!(latest_updates(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_truncate_statuses() {
+        let mut short = update("BTC", "$65,000");
+        short.status = "+1.50% | BTC".to_string();
+        assert!(!truncate_statuses(&mut short));
+        assert_eq!("+1.50% | BTC", short.status);
+
+        let mut long = update("BTC", "$65,000");
+        long.status_rotation = Some(StatusRotation {
+            statuses: vec!["ETH $3,412".to_string(), "x".repeat(200)],
+            interval: Duration::from_secs(15),
+        });
+        assert!(truncate_statuses(&mut long));
+        let statuses = &long.status_rotation.unwrap().statuses;
+        assert_eq!("ETH $3,412", statuses[0]);
+        assert_eq!(format!("{}…", "x".repeat(127)), statuses[1]);
+    }
+
+    #[test]
+    fn test_status_rotation_advance() {
+        let start = Instant::now();
+        let interval = Duration::from_secs(10);
+        let mut rotation = StatusCycle {
+            statuses: vec!["+1.8% | BTC".to_string(), "ETH $3,412".to_string()],
+            index: 0,
+            interval,
+            next_at: start + interval,
+        };
+
+        assert_eq!(None, rotation.advance(start));
+        assert_eq!(Some("ETH $3,412"), rotation.advance(start + interval));
+        assert_eq!(None, rotation.advance(start + interval));
+        assert_eq!(Some("+1.8% | BTC"), rotation.advance(start + interval * 2));
+    }
+}
@@ -0,0 +1,86 @@
+use crate::config::CountdownConfig;
+use chrono::{DateTime, Duration, Utc};
+
+/// Nickname of a countdown once its target is reached and it does not repeat
+pub const REACHED_NAME: &str = "🎉 now";
+
+/// The next occurrence of the event at or after `now`: `target`, moved on by `repeat_days` until it is ahead if it
+/// repeats, e.g. a weekly options expiry. `None` once a one-off event is over.
+pub fn next_target(countdown: &CountdownConfig, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    if countdown.target >= now {
+        return Some(countdown.target);
+    }
+
+    let period = Duration::days(countdown.repeat_days.filter(|days| *days > 0)?.into());
+    let periods = (now - countdown.target).num_seconds().div_euclid(period.num_seconds()) + 1;
+    Some(countdown.target + period * periods as i32)
+}
+
+/// Renders the time left like `12d 4h`, `4h 31m` or `31m`, rounded up to the minute so that it only reads `0m` once
+/// the event is there
+pub fn format_remaining(remaining: Duration) -> String {
+    let minutes = (remaining.num_seconds().max(0) + 59) / 60;
+    let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+/// Nickname and status of a countdown at `now`, e.g. `12d 4h` and `until Bitcoin halving`
+pub fn countdown_update(countdown: &CountdownConfig, event: &str, now: DateTime<Utc>) -> (String, String) {
+    match next_target(countdown, now) {
+        Some(target) => (format_remaining(target - now), format!("until {}", event)),
+        None => (REACHED_NAME.to_string(), event.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(rfc3339: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(rfc3339).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_format_remaining() {
+        assert_eq!("12d 4h", format_remaining(Duration::days(12) + Duration::minutes(4 * 60 + 31)));
+        assert_eq!("4h 31m", format_remaining(Duration::minutes(4 * 60 + 31)));
+        assert_eq!("31m", format_remaining(Duration::minutes(30) + Duration::seconds(10)));
+        assert_eq!("1m", format_remaining(Duration::seconds(1)));
+        assert_eq!("0m", format_remaining(Duration::zero()));
+    }
+
+    #[test]
+    fn test_countdown_update() {
+        let halving = CountdownConfig {
+            target: time("2028-04-20T00:00:00Z"),
+            repeat_days: None,
+        };
+        assert_eq!(
+            ("12d 4h".to_string(), "until Bitcoin halving".to_string()),
+            countdown_update(&halving, "Bitcoin halving", time("2028-04-07T19:29:00Z"))
+        );
+        assert_eq!(
+            (REACHED_NAME.to_string(), "Bitcoin halving".to_string()),
+            countdown_update(&halving, "Bitcoin halving", time("2028-04-20T00:01:00Z"))
+        );
+
+        // weekly options expiry on Friday 08:00 UTC
+        let expiry = CountdownConfig {
+            target: time("2026-01-02T08:00:00Z"),
+            repeat_days: Some(7),
+        };
+        assert_eq!(Some(time("2026-10-16T08:00:00Z")), next_target(&expiry, time("2026-10-16T07:00:00Z")));
+        assert_eq!(Some(time("2026-10-23T08:00:00Z")), next_target(&expiry, time("2026-10-16T08:00:01Z")));
+        assert_eq!(
+            ("1h 0m".to_string(), "until expiry".to_string()),
+            countdown_update(&expiry, "expiry", time("2026-10-16T07:00:00Z"))
+        );
+    }
+}
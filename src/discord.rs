@@ -1 +1,8 @@
-pub mod client;
\ No newline at end of file
+pub mod board;
+pub mod client;
+pub mod commands;
+pub mod onboarding;
+pub mod quarantine;
+pub mod stagger;
+pub mod throttle;
+pub mod webhook;
@@ -0,0 +1,153 @@
+use crate::discord::client::{is_not_found, DiscordClient};
+use crate::state_file::{load_json_state, save_json_state};
+use serde::{Deserialize, Serialize};
+use serenity::all::CreateEmbed;
+use serenity::prelude::SerenityError;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+/// Discord allows at most this many fields in an embed
+const MAX_EMBED_FIELDS: usize = 25;
+
+/// A single embed message listing the prices of all tickers, edited in place.
+/// The id of the message is persisted so the same message is reused after a restart.
+#[derive(Debug)]
+pub struct PriceBoard {
+    discord_client: DiscordClient,
+    channel_id: u64,
+    title: String,
+    state_file: PathBuf,
+    state: PriceBoardState,
+    rows: Vec<PriceBoardRow>,
+    dirty: bool,
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+struct PriceBoardState {
+    message_id: Option<u64>,
+}
+
+#[derive(Debug)]
+struct PriceBoardRow {
+    ticker: String,
+    name: String,
+    status: String,
+}
+
+impl PriceBoard {
+    pub async fn load(
+        discord_client: DiscordClient,
+        channel_id: u64,
+        title: String,
+        state_file: PathBuf,
+    ) -> Self {
+        let state = load_json_state(&state_file, "price board state").await;
+
+        Self {
+            discord_client,
+            channel_id,
+            title,
+            state_file,
+            state,
+            rows: Vec::new(),
+            dirty: false,
+        }
+    }
+
+    /// Sets the row of the ticker, rows are listed in the order tickers are first seen
+    pub fn set_row(&mut self, ticker: &str, name: &str, status: &str) {
+        match self.rows.iter_mut().find(|row| row.ticker == ticker) {
+            Some(row) => {
+                row.name = name.to_string();
+                row.status = status.to_string();
+            }
+            None => self.rows.push(PriceBoardRow {
+                ticker: ticker.to_string(),
+                name: name.to_string(),
+                status: status.to_string(),
+            }),
+        }
+        self.dirty = true;
+    }
+
+    /// Edits the board message if any row changed since the last render,
+    /// posting and pinning a new message if there is none yet
+    pub async fn render(&mut self) -> Result<(), SerenityError> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        if let Some(message_id) = self.state.message_id {
+            match self
+                .discord_client
+                .edit_embed(self.channel_id, message_id, self.embed())
+                .await
+            {
+                Ok(_) => {
+                    self.dirty = false;
+                    return Ok(());
+                }
+                Err(e) if is_not_found(&e) => {
+                    info!("Price board message {} is gone, posting a new one", message_id);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        let message_id = self
+            .discord_client
+            .send_embed(self.channel_id, self.embed())
+            .await?;
+        info!("Posted price board message {}", message_id);
+        self.dirty = false;
+
+        if let Err(e) = self.discord_client.pin_message(self.channel_id, message_id).await {
+            warn!("Error pinning price board message {}: {}", message_id, e);
+        }
+
+        self.state.message_id = Some(message_id);
+        save_json_state(&self.state_file, "price board state", &self.state).await;
+        Ok(())
+    }
+
+    fn embed(&self) -> CreateEmbed {
+        CreateEmbed::new()
+            .title(&self.title)
+            .fields(embed_fields(&self.rows))
+            .timestamp(serenity::model::Timestamp::now())
+    }
+}
+
+/// A field per row, named after the ticker, as many as an embed holds
+fn embed_fields(rows: &[PriceBoardRow]) -> Vec<(String, String, bool)> {
+    rows.iter()
+        .take(MAX_EMBED_FIELDS)
+        .map(|row| (row.ticker.clone(), format!("{}\n{}", row.name, row.status), true))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embed_fields() {
+        let rows: Vec<PriceBoardRow> = (0..30)
+            .map(|i| PriceBoardRow {
+                ticker: format!("T{}", i),
+                name: format!("${}", i),
+                status: format!("+{}.00% | T{}", i, i),
+            })
+            .collect();
+
+        let fields = embed_fields(&rows[..2]);
+        assert_eq!(
+            vec![
+                ("T0".to_string(), "$0\n+0.00% | T0".to_string(), true),
+                ("T1".to_string(), "$1\n+1.00% | T1".to_string(), true),
+            ],
+            fields
+        );
+        assert_eq!(MAX_EMBED_FIELDS, embed_fields(&rows).len());
+    }
+}
@@ -1,24 +1,57 @@
-use serenity::all::{ActivityData, GuildId, GuildInfo, GuildPagination, Http};
+use serenity::all::{
+    ActivityData, Cache, ChannelId, ClientBuilder, ConnectionStage, CreateEmbed, CreateMessage, EditChannel, EditMessage,
+    GuildId, GuildInfo, GuildPagination, Http, HttpBuilder, MessageId, UserId,
+};
 use serenity::prelude::*;
-use tracing::{debug, trace, warn};
+use tracing::{debug, info, trace, warn};
+use crate::discord::quarantine::{GuildQuarantine, QUARANTINE_AFTER_FAILURES, QUARANTINE_COOLDOWN};
+use crate::discord::stagger::UpdateStagger;
+use crate::discord::throttle::{ChannelThrottle, NicknameThrottle};
+use crate::http::DiscordHttpClient;
+use crate::format::truncate_nickname;
+use std::collections::HashSet;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
 use serenity::gateway::ShardManager;
 use tracing::error;
 
 #[derive(Clone, Debug)]
 pub struct DiscordClient {
     http_client: Arc<Http>,
+    cache: Arc<Cache>,
     shard_manager: Arc<ShardManager>,
+    channel_rename_throttle: Arc<Mutex<ChannelThrottle>>,
+    nickname_throttle: Arc<Mutex<NicknameThrottle>>,
+    missing_permission_guilds: Arc<Mutex<HashSet<GuildId>>>, // Guilds skipped for lacking Change Nickname, warned about once
+    guild_quarantine: GuildQuarantine,
+    notify_guild_owners: bool,
+    nickname_stagger: Option<UpdateStagger>, // Shared with the bots of other tickers
 }
 
 impl DiscordClient {
-    pub async fn new(token: &str) -> Self {
-        let intents = GatewayIntents::default();
-        let mut client = Client::builder(&token, intents)
+    /// Connects a bot, sending its API requests through `http_client` if given, e.g. one going through a proxy
+    pub async fn new(token: &str, http_client: Option<DiscordHttpClient>) -> Self {
+        Self::with_event_handlers(token, http_client, |client_builder| client_builder).await
+    }
+
+    /// Connects a bot whose gateway events, e.g. slash commands, are handled by the event handlers
+    /// `add_event_handlers` adds to the client
+    pub async fn with_event_handlers(
+        token: &str,
+        http_client: Option<DiscordHttpClient>,
+        add_event_handlers: impl FnOnce(ClientBuilder) -> ClientBuilder,
+    ) -> Self {
+        let mut http = HttpBuilder::new(token);
+        if let Some(http_client) = http_client {
+            http = http.client(http_client);
+        }
+        let mut client = add_event_handlers(ClientBuilder::new_with_http(http.build(), GatewayIntents::default()))
             .await
             .expect("Err creating client");
         let shard_manager = client.shard_manager.clone();
         let http_client = client.http.clone();
+        let cache = client.cache.clone();
 
         tokio::spawn(async move {
             // Start two shards. Note that there is an ~5 second ratelimit period between when one shard
@@ -28,17 +61,65 @@ impl DiscordClient {
             }
         });
 
-        Self { http_client: http_client, shard_manager }
+        Self {
+            http_client,
+            cache,
+            shard_manager,
+            channel_rename_throttle: Arc::new(Mutex::new(ChannelThrottle::default())),
+            nickname_throttle: Arc::new(Mutex::new(NicknameThrottle::default())),
+            missing_permission_guilds: Arc::new(Mutex::new(HashSet::new())),
+            guild_quarantine: GuildQuarantine::default(),
+            notify_guild_owners: false,
+            nickname_stagger: None,
+        }
     }
 
-    pub async fn update_bot(&self, name: String, status: String) {
+    /// Spaces the nickname updates of this bot and the others sharing `nickname_stagger`
+    pub fn nickname_stagger(mut self, nickname_stagger: Option<UpdateStagger>) -> Self {
+        self.nickname_stagger = nickname_stagger;
+        self
+    }
+
+    /// Changes the nickname at most once per `nickname_interval`, the activity is still updated every time
+    pub fn nickname_interval(mut self, nickname_interval: Option<Duration>) -> Self {
+        self.nickname_throttle = Arc::new(Mutex::new(NicknameThrottle::new(nickname_interval)));
+        self
+    }
+
+    /// Shares the guilds quarantined while updating the nickname, e.g. with `/status`
+    pub fn guild_quarantine(mut self, guild_quarantine: GuildQuarantine) -> Self {
+        self.guild_quarantine = guild_quarantine;
+        self
+    }
+
+    /// Sends a direct message to the owner of a guild the bot cannot change its nickname in, once per guild
+    pub fn notify_guild_owners(mut self, notify_guild_owners: bool) -> Self {
+        self.notify_guild_owners = notify_guild_owners;
+        self
+    }
+
+    /// Updates the nickname of the bot in every guild and its activity, the nickname is cut to what Discord accepts.
+    /// Guilds where the bot lacks the Change Nickname permission are skipped with a warning, once per guild, and
+    /// guilds rejecting the nickname `QUARANTINE_AFTER_FAILURES` times in a row are skipped for a while.
+    /// Only the activity is updated while the nickname interval, if any, has not passed since the last nickname update.
+    /// Fails if the guilds cannot be listed, or if the nickname could not be updated in any guild it was tried in.
+    pub async fn update_bot(&self, name: String, status: String) -> Result<(), SerenityError> {
+        if !self.nickname_throttle.lock().await.try_acquire(Instant::now()) {
+            debug!("Nickname was updated recently, only update the activity to {}", status);
+            self.set_activity(&status).await;
+            return Ok(());
+        }
+
+        let name = truncate_nickname(&name);
         let guilds = match self.get_guilds().await {
             Ok(guilds) => guilds,
             Err(why) => {
                 warn!("Error getting guilds: {why:?}");
-                return;
+                return Err(why);
             }
         };
+        let mut last_error = None;
+        let mut updated_count = 0;
         debug!("Update nicknames in guilds:");
         for g in &guilds {
             // let roles = match self.http_client.get_guild_roles(g.id).await {
@@ -53,16 +134,86 @@ impl DiscordClient {
             //     debug!("  Role: {}, id = {}", role.name, role.id);
             // }
 
+            if self.guild_quarantine.is_quarantined(g.id.get(), Instant::now()) {
+                trace!("Guild {} is quarantined, skip", g.name);
+                continue;
+            }
+            if !self.check_nickname_permission(g).await {
+                continue;
+            }
+            if let Some(nickname_stagger) = &self.nickname_stagger {
+                nickname_stagger.wait().await;
+            }
+
             match self.http_client.edit_nickname(g.id, Some(&name), None).await {
-                Ok(_) => debug!("Updated nickname for guild {} to {}", g.name, name),
-                Err(why) => warn!("Error updating nickname for guild {}: {why:?}", g.name),
+                Ok(_) => {
+                    debug!("Updated nickname for guild {} to {}", g.name, name);
+                    if self.guild_quarantine.record_success(g.id.get()) {
+                        info!("Guild {} accepts the nickname again, released from quarantine", g.name);
+                    }
+                    updated_count += 1;
+                }
+                Err(why) => {
+                    warn!("Error updating nickname for guild {}: {why:?}", g.name);
+                    if self.guild_quarantine.record_failure(g.id.get(), &g.name, Instant::now()) {
+                        warn!(
+                            "Guild {} rejected the nickname {} times in a row, skipping it for {:?}",
+                            g.name, QUARANTINE_AFTER_FAILURES, QUARANTINE_COOLDOWN
+                        );
+                    }
+                    last_error = Some(why);
+                }
             };
         }
 
-        // Update bot's activity
+        self.set_activity(&status).await;
+
+        match last_error {
+            Some(why) if updated_count == 0 => Err(why),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether the bot may change its nickname in the guild, as far as the cache tells; guilds not cached yet
+    /// are tried anyway. Warns once when the permission goes missing, and tells the owner if asked to.
+    async fn check_nickname_permission(&self, guild: &GuildInfo) -> bool {
+        let permission = self.cache.guild(guild.id).and_then(|cached_guild| {
+            let member = cached_guild.members.get(&self.cache.current_user().id)?;
+            Some((
+                cached_guild.member_permissions(member).change_nickname(),
+                cached_guild.owner_id,
+            ))
+        });
+
+        let mut missing_permission_guilds = self.missing_permission_guilds.lock().await;
+        let Some((false, owner_id)) = permission else {
+            if missing_permission_guilds.remove(&guild.id) {
+                info!("Got the Change Nickname permission in guild {}", guild.name);
+            }
+            return true;
+        };
+        if !missing_permission_guilds.insert(guild.id) {
+            return false;
+        }
+
+        warn!(
+            "Missing the Change Nickname permission in guild {}, skip updating the nickname there",
+            guild.name
+        );
+        if self.notify_guild_owners {
+            let message = missing_permission_notice(&guild.name);
+            if let Err(why) = owner_id.direct_message(self.http_client.as_ref(), CreateMessage::new().content(message)).await {
+                warn!("Error telling the owner of guild {} about the missing permission: {why:?}", guild.name);
+            }
+        }
+        false
+    }
+
+    /// Updates the bot's activity on every shard
+    pub async fn set_activity(&self, status: &str) {
         let shard_runners = self.shard_manager.runners.lock().await;
         for (shard_id, runner) in shard_runners.iter() {
-            let new_activity = ActivityData::custom(&status);
+            let new_activity = ActivityData::custom(status);
             runner.runner_tx.set_activity(Some(new_activity));
             debug!(
                 "Updated activity for shard {} to {}",
@@ -71,6 +222,83 @@ impl DiscordClient {
         }
     }
 
+    /// Renames the channel, unless it was already renamed as often as Discord allows recently.
+    /// Returns whether the channel was renamed.
+    pub async fn rename_channel(&self, channel_id: u64, name: &str) -> Result<bool, SerenityError> {
+        if !self
+            .channel_rename_throttle
+            .lock()
+            .await
+            .try_acquire(channel_id, Instant::now())
+        {
+            debug!("Renaming channel {} is throttled, skip", channel_id);
+            return Ok(false);
+        }
+
+        ChannelId::new(channel_id)
+            .edit(&self.http_client, EditChannel::new().name(name))
+            .await?;
+        debug!("Renamed channel {} to {}", channel_id, name);
+        Ok(true)
+    }
+
+    pub fn http(&self) -> &Http {
+        &self.http_client
+    }
+
+    pub async fn send_message(&self, channel_id: u64, content: &str) -> Result<(), SerenityError> {
+        ChannelId::new(channel_id).say(&self.http_client, content).await?;
+        Ok(())
+    }
+
+    /// Sends a direct message to the user, failing if the user does not accept direct messages from the bot
+    pub async fn send_direct_message(&self, user_id: u64, content: &str) -> Result<(), SerenityError> {
+        UserId::new(user_id)
+            .direct_message(self.http_client.as_ref(), CreateMessage::new().content(content))
+            .await?;
+        Ok(())
+    }
+
+    /// Posts a message with `embed` to the channel and returns the id of the message
+    pub async fn send_embed(&self, channel_id: u64, embed: CreateEmbed) -> Result<u64, SerenityError> {
+        let message = ChannelId::new(channel_id)
+            .send_message(&self.http_client, CreateMessage::new().embed(embed))
+            .await?;
+        Ok(message.id.get())
+    }
+
+    pub async fn edit_embed(
+        &self,
+        channel_id: u64,
+        message_id: u64,
+        embed: CreateEmbed,
+    ) -> Result<(), SerenityError> {
+        ChannelId::new(channel_id)
+            .edit_message(&self.http_client, MessageId::new(message_id), EditMessage::new().embed(embed))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn pin_message(&self, channel_id: u64, message_id: u64) -> Result<(), SerenityError> {
+        ChannelId::new(channel_id)
+            .pin(&self.http_client, MessageId::new(message_id))
+            .await
+    }
+
+    /// Whether every shard is connected to the gateway, false until the shards are started
+    pub async fn is_connected(&self) -> bool {
+        let shard_runners = self.shard_manager.runners.lock().await;
+        !shard_runners.is_empty()
+            && shard_runners
+                .values()
+                .all(|runner| runner.stage == ConnectionStage::Connected)
+    }
+
+    pub async fn shutdown(&self) {
+        debug!("Shutting down all shards...");
+        self.shard_manager.shutdown_all().await;
+    }
+
     async fn get_guilds(&self) -> Result<Vec<GuildInfo>, SerenityError> {
         let mut guilds = Vec::<GuildInfo>::new();
         let mut retry_count = 3;
@@ -105,4 +333,19 @@ impl DiscordClient {
 
         Ok(guilds)
     }
-}
\ No newline at end of file
+}
+
+fn missing_permission_notice(guild_name: &str) -> String {
+    format!(
+        "👋 I cannot show the price as my nickname in {}: I am missing the **Change Nickname** permission there.",
+        guild_name
+    )
+}
+
+/// Whether the request failed because the target (e.g. a message) doesn't exist anymore
+pub fn is_not_found(error: &SerenityError) -> bool {
+    match error {
+        SerenityError::Http(error) => error.status_code().map(|code| code.as_u16()) == Some(404),
+        _ => false,
+    }
+}
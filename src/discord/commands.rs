@@ -0,0 +1,672 @@
+use crate::config::{QuoteSource, TickerConfig};
+use crate::format::{format_price, PriceFormat};
+use crate::discord::quarantine::GuildQuarantine;
+use crate::health::{FetchReport, TickerHealth};
+use crate::price_alert::{AlertDelivery, PriceAlertStore, PriceDirection};
+use crate::quote::budget::{ApiUsage, BudgetReport};
+use crate::quote::coin_list::CoinList;
+use crate::quote::coingecko::CoinGeckoProvider;
+use crate::ticker::{request_quote_with_fallback, QuoteJobSenders};
+use crate::ticker_control::TickerControls;
+use bigdecimal::{BigDecimal, Zero};
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption, CreateEmbed,
+    CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage, EditInteractionResponse,
+    EventHandler, Interaction, Permissions, Ready, ResolvedOption, ResolvedValue,
+};
+use serenity::async_trait;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Answers the slash commands of the bot of the `commands` section, e.g. `/convert 0.5 btc usd` or
+/// `/alert add btc above 70000`.
+/// The commands are registered in every guild the bot is in once it connects, guilds joined later get
+/// them after a restart.
+pub struct CommandHandler {
+    job_senders: QuoteJobSenders,
+    coingecko_provider: Arc<CoinGeckoProvider>,
+    coin_list: Option<Arc<CoinList>>,
+    resolved_ids: Mutex<HashMap<String, Option<String>>>, // CoinGecko id of every asset asked for, `None` if it is no coin
+    price_alerts: Arc<PriceAlertStore>,
+    tickers: Vec<(String, String)>, // Tickers alerts can be set on, with the currency of their price
+}
+
+impl CommandHandler {
+    /// Quotes are requested through `job_senders`, so that they share the cache and the limits of the tickers.
+    /// Alerts are evaluated against the prices the running `tickers` fetch.
+    pub fn new(
+        job_senders: QuoteJobSenders,
+        coingecko_provider: Arc<CoinGeckoProvider>,
+        coin_list: Option<Arc<CoinList>>,
+        price_alerts: Arc<PriceAlertStore>,
+        tickers: Vec<(String, String)>,
+    ) -> Self {
+        Self {
+            job_senders,
+            coingecko_provider,
+            coin_list,
+            resolved_ids: Mutex::new(HashMap::new()),
+            price_alerts,
+            tickers,
+        }
+    }
+
+    /// CoinGecko id of a coin given by its symbol like `btc` or its id like `bitcoin`, `None` if it is no coin,
+    /// e.g. a fiat currency. Without the list of coins every asset is taken as an id.
+    async fn resolve_coin_id(&self, asset: &str) -> Option<String> {
+        if let Some(id) = self.resolved_ids.lock().unwrap().get(asset) {
+            return id.clone();
+        }
+
+        let id = match &self.coin_list {
+            Some(coin_list) if !coin_list.ids_of_symbol(asset).is_empty() => {
+                match self.coingecko_provider.resolve_symbol(coin_list, asset).await {
+                    Ok(id) => id,
+                    Err(error) => {
+                        // not remembered, the next command tries again
+                        warn!("Error resolving the CoinGecko id of {}: {}", asset, error);
+                        return coin_list.ids_of_symbol(asset).first().map(|id| id.to_string());
+                    }
+                }
+            }
+            Some(coin_list) => coin_list.contains(asset).then(|| asset.to_string()),
+            None => Some(asset.to_string()),
+        };
+
+        self.resolved_ids.lock().unwrap().insert(asset.to_string(), id.clone());
+        id
+    }
+
+    /// Converts `amount` of `from` into `to`, quoting `from` in `to`, or `to` in `from` if only `to` is a coin,
+    /// e.g. `/convert 100 usd btc`. Returns the converted amount, the rate of one `from` and the provider.
+    async fn convert(
+        &self,
+        amount: &BigDecimal,
+        from: &str,
+        to: &str,
+    ) -> Result<(BigDecimal, BigDecimal, QuoteSource), String> {
+        if let Some(id) = self.resolve_coin_id(from).await {
+            let (source, quote) = request_quote_with_fallback(&self.job_senders, |_| id.clone(), to)
+                .await
+                .map_err(|error| format!("Cannot quote {} in {}: {}", from, to, error))?;
+            return Ok((amount * &quote.price, quote.price, source));
+        }
+
+        let Some(id) = self.resolve_coin_id(to).await else {
+            return Err(format!("Neither {} nor {} is a known coin", from, to));
+        };
+        let (source, quote) = request_quote_with_fallback(&self.job_senders, |_| id.clone(), from)
+            .await
+            .map_err(|error| format!("Cannot quote {} in {}: {}", to, from, error))?;
+        if quote.price.is_zero() {
+            return Err(format!("Price of {} in {} is zero", to, from));
+        }
+        let rate = BigDecimal::from(1) / &quote.price;
+        Ok((amount * &rate, rate, source))
+    }
+
+    async fn run_convert(&self, ctx: &Context, command: &CommandInteraction) {
+        let mut amount = "";
+        let mut from = "";
+        let mut to = "";
+        for option in command.data.options() {
+            if let ResolvedValue::String(value) = option.value {
+                match option.name {
+                    "amount" => amount = value,
+                    "from" => from = value,
+                    "to" => to = value,
+                    _ => {}
+                }
+            }
+        }
+        let from = from.trim().to_lowercase();
+        let to = to.trim().to_lowercase();
+
+        // quotes may take longer than the 3 seconds Discord waits for an answer
+        if let Err(error) = command.defer(ctx).await {
+            warn!("Error deferring the answer to /convert: {}", error);
+            return;
+        }
+
+        let response = match parse_amount(amount) {
+            Ok(amount) => match self.convert(&amount, &from, &to).await {
+                Ok((converted, rate, source)) => {
+                    EditInteractionResponse::new().embed(conversion_embed(&amount, &from, &to, &converted, &rate, source))
+                }
+                Err(message) => EditInteractionResponse::new().content(format!("⚠️ {}", message)),
+            },
+            Err(message) => EditInteractionResponse::new().content(format!("⚠️ {}", message)),
+        };
+
+        if let Err(error) = command.edit_response(ctx, response).await {
+            warn!("Error answering /convert: {}", error);
+        }
+    }
+
+    async fn run_alert(&self, ctx: &Context, command: &CommandInteraction) {
+        let user_id = command.user.id.get();
+        let content = match command.data.options().first() {
+            Some(ResolvedOption {
+                name: "add",
+                value: ResolvedValue::SubCommand(options),
+                ..
+            }) => self.add_alert(user_id, command.channel_id.get(), options).await,
+            Some(ResolvedOption { name: "list", .. }) => self.list_alerts(user_id).await,
+            Some(ResolvedOption {
+                name: "remove",
+                value: ResolvedValue::SubCommand(options),
+                ..
+            }) => {
+                let id = options.iter().find_map(|option| match option.value {
+                    ResolvedValue::Integer(id) if option.name == "id" => u64::try_from(id).ok(),
+                    _ => None,
+                });
+                match id {
+                    Some(id) if self.price_alerts.remove(user_id, id).await => format!("Removed alert #{}", id),
+                    _ => "⚠️ You have no such alert, see `/alert list`".to_string(),
+                }
+            }
+            _ => {
+                warn!("Unknown subcommand of /alert");
+                return;
+            }
+        };
+
+        // only the member who asked sees the answer
+        let response = CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new().content(content).ephemeral(true),
+        );
+        if let Err(error) = command.create_response(ctx, response).await {
+            warn!("Error answering /alert: {}", error);
+        }
+    }
+
+    async fn add_alert(&self, user_id: u64, channel_id: u64, options: &[ResolvedOption<'_>]) -> String {
+        let mut ticker = "";
+        let mut direction = "";
+        let mut price = "";
+        let mut notify = "";
+        let mut currency = "";
+        for option in options {
+            if let ResolvedValue::String(value) = option.value {
+                match option.name {
+                    "ticker" => ticker = value,
+                    "direction" => direction = value,
+                    "price" => price = value,
+                    "notify" => notify = value,
+                    "currency" => currency = value,
+                    _ => {}
+                }
+            }
+        }
+
+        let (ticker, vs_currency) = match find_alert_ticker(&self.tickers, ticker, currency) {
+            Ok(ticker) => ticker,
+            Err(message) => return format!("⚠️ {}", message),
+        };
+        let direction = match direction {
+            "below" => PriceDirection::Below,
+            _ => PriceDirection::Above,
+        };
+        let delivery = match notify {
+            "mention" => AlertDelivery::Mention,
+            _ => AlertDelivery::Dm,
+        };
+        let price = match parse_amount(price) {
+            Ok(price) => price,
+            Err(message) => return format!("⚠️ {}", message),
+        };
+
+        match self.price_alerts.add(user_id, channel_id, ticker, vs_currency, direction, price, delivery).await {
+            Ok(alert) => format!(
+                "Alert #{} set, you will be notified once {} is {} {} {}",
+                alert.id,
+                alert.ticker,
+                alert.direction.name(),
+                format_amount(&alert.price),
+                alert.vs_currency.to_uppercase()
+            ),
+            Err(message) => format!("⚠️ {}", message),
+        }
+    }
+
+    async fn list_alerts(&self, user_id: u64) -> String {
+        let alerts = self.price_alerts.list(user_id).await;
+        if alerts.is_empty() {
+            return "You have no alerts, set one with `/alert add`".to_string();
+        }
+
+        alerts
+            .iter()
+            .map(|alert| {
+                format!(
+                    "#{}: {} {} {} {}",
+                    alert.id,
+                    alert.ticker,
+                    alert.direction.name(),
+                    format_amount(&alert.price),
+                    alert.vs_currency.to_uppercase()
+                )
+                .trim_end()
+                .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[async_trait]
+impl EventHandler for CommandHandler {
+    async fn ready(&self, ctx: Context, ready: Ready) {
+        register_commands(&ctx, &ready, commands()).await;
+    }
+
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        let Interaction::Command(command) = interaction else {
+            return;
+        };
+
+        debug!("Slash command /{} from {}", command.data.name, command.user.name);
+        match command.data.name.as_str() {
+            "convert" => self.run_convert(&ctx, &command).await,
+            "alert" => self.run_alert(&ctx, &command).await,
+            name => warn!("Unknown slash command /{}", name),
+        }
+    }
+}
+
+/// Answers the commands of the bot of a ticker: `/ticker`, letting the operators of the bot pause, resume and change
+/// the frequency of the ticker without editing the config, and `/status`, showing how fetching its price goes.
+/// The changes are kept in `TickerControls` and survive restarts.
+pub struct TickerCommandHandler {
+    ticker: String,
+    controls: Arc<TickerControls>,
+    operator_ids: Vec<u64>, // Users allowed to use `/ticker`
+    health: TickerHealth,
+    guild_quarantine: GuildQuarantine,
+    api_usage: Arc<ApiUsage>,
+}
+
+impl TickerCommandHandler {
+    pub fn new(
+        ticker: String,
+        controls: Arc<TickerControls>,
+        operator_ids: Vec<u64>,
+        health: TickerHealth,
+        guild_quarantine: GuildQuarantine,
+        api_usage: Arc<ApiUsage>,
+    ) -> Self {
+        Self {
+            ticker,
+            controls,
+            operator_ids,
+            health,
+            guild_quarantine,
+            api_usage,
+        }
+    }
+
+    fn run_status(&self) -> String {
+        match self.health.report(&self.ticker) {
+            Some(report) => format_status(
+                &self.ticker,
+                &report,
+                &self.guild_quarantine.quarantined(tokio::time::Instant::now()),
+                report.source.and_then(|source| self.api_usage.report(source, chrono::Utc::now())).as_ref(),
+            ),
+            None => format!("{} has not started yet", self.ticker),
+        }
+    }
+
+    async fn run_ticker(&self, command: &CommandInteraction) -> String {
+        // the ticker is shared by every guild the bot is in, so owning one of them is not enough to control it
+        if !self.operator_ids.contains(&command.user.id.get()) {
+            return "⚠️ Only the operators of the bot can control the ticker".to_string();
+        }
+
+        let ticker = &self.ticker;
+        match command.data.options().first() {
+            Some(ResolvedOption { name: "pause", .. }) => {
+                self.controls.update(ticker, |ticker_override| ticker_override.paused = true).await;
+                info!("{} paused by {}", ticker, command.user.name);
+                format!("Paused {}, `/ticker resume` to fetch its price again", ticker)
+            }
+            Some(ResolvedOption { name: "resume", .. }) => {
+                self.controls.update(ticker, |ticker_override| ticker_override.paused = false).await;
+                info!("{} resumed by {}", ticker, command.user.name);
+                format!("Resumed {}", ticker)
+            }
+            Some(ResolvedOption {
+                name: "frequency",
+                value: ResolvedValue::SubCommand(options),
+                ..
+            }) => {
+                let secs = options.iter().find_map(|option| match option.value {
+                    ResolvedValue::Integer(secs) if option.name == "secs" => u64::try_from(secs).ok(),
+                    _ => None,
+                });
+                match secs {
+                    Some(secs) if secs > 0 => {
+                        self.controls
+                            .update(ticker, |ticker_override| ticker_override.frequency = Some(secs))
+                            .await;
+                        info!("Frequency of {} set to {}s by {}", ticker, secs, command.user.name);
+                        format!("{} now fetches its price every {} seconds", ticker, secs)
+                    }
+                    _ => "⚠️ The frequency is a number of seconds above 0".to_string(),
+                }
+            }
+            _ => "⚠️ Unknown subcommand of /ticker".to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl EventHandler for TickerCommandHandler {
+    async fn ready(&self, ctx: Context, ready: Ready) {
+        register_commands(&ctx, &ready, vec![ticker_command(), status_command()]).await;
+    }
+
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        let Interaction::Command(command) = interaction else {
+            return;
+        };
+
+        debug!("Slash command /{} for {} from {}", command.data.name, self.ticker, command.user.name);
+        let content = match command.data.name.as_str() {
+            "ticker" => self.run_ticker(&command).await,
+            "status" => self.run_status(),
+            name => {
+                warn!("Unknown slash command /{}", name);
+                return;
+            }
+        };
+
+        let response = CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new().content(content).ephemeral(true),
+        );
+        if let Err(error) = command.create_response(&ctx, response).await {
+            warn!("Error answering /{}: {}", command.data.name, error);
+        }
+    }
+}
+
+/// Registers `commands` in every guild the bot is in
+async fn register_commands(ctx: &Context, ready: &Ready, commands: Vec<CreateCommand>) {
+    for guild in &ready.guilds {
+        if let Err(error) = guild.id.set_commands(ctx, commands.clone()).await {
+            warn!("Error registering the slash commands in guild {}: {}", guild.id, error);
+        }
+    }
+    info!("Registered the slash commands of {} in {} guilds", ready.user.name, ready.guilds.len());
+}
+
+/// `/ticker`, shown to administrators only, while answered for the operators of the bot only
+fn ticker_command() -> CreateCommand {
+    CreateCommand::new("ticker")
+        .description("Control the ticker of this bot")
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "pause", "Stop fetching the price"))
+        .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "resume", "Fetch the price again"))
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "frequency", "Change how often the price is fetched")
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Integer, "secs", "Seconds between fetches")
+                        .min_int_value(1)
+                        .max_int_value(TickerConfig::MAX_FREQUENCY_SECS)
+                        .required(true),
+                ),
+        )
+}
+
+/// `/status`, shown to the members who can manage the guild
+fn status_command() -> CreateCommand {
+    CreateCommand::new("status")
+        .description("Show how fetching the price of this bot goes")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+}
+
+/// Answer of `/status`, with times shown by Discord relative to now, e.g. `2 minutes ago`, and the guilds
+/// `quarantined` for rejecting the nickname with the minutes left, if any
+fn format_status(
+    ticker: &str,
+    report: &FetchReport,
+    quarantined: &[(String, Duration)],
+    budget: Option<&BudgetReport>,
+) -> String {
+    let last_fetch = match (report.last_fetch_at, report.source) {
+        (Some(fetched_at), Some(source)) => format!("<t:{}:R> from {:?}", fetched_at.timestamp(), source),
+        _ => "never".to_string(),
+    };
+    let frequency = match (report.paused, report.frequency) {
+        (true, _) => "paused".to_string(),
+        (false, Some(secs)) => format!("every {} seconds", secs),
+        (false, None) => "on its cron schedule".to_string(),
+    };
+    let errors = match &report.last_error {
+        Some(error) if report.error_streak > 0 => format!("{} in a row, last: {}", report.error_streak, error),
+        _ => "none".to_string(),
+    };
+
+    let mut status = format!(
+        "**{}**\nLast fetch: {}\nFrequency: {}\nErrors: {}",
+        ticker, last_fetch, frequency, errors
+    );
+    if let Some(budget) = budget {
+        status.push_str(&format!(
+            "\nAPI budget: {} of {} calls made this month, {} projected",
+            budget.calls, budget.budget, budget.projected
+        ));
+    }
+    if !quarantined.is_empty() {
+        let guilds: Vec<String> = quarantined
+            .iter()
+            .map(|(name, left)| format!("{} ({} min left)", name, left.as_secs().div_ceil(60)))
+            .collect();
+        status.push_str(&format!("\nQuarantined guilds: {}", guilds.join(", ")));
+    }
+    status
+}
+
+/// Every slash command the bot of the `commands` section answers
+fn commands() -> Vec<CreateCommand> {
+    vec![
+        CreateCommand::new("convert")
+            .description("Convert an amount between two assets, e.g. 0.5 btc usd")
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::String, "amount", "Amount to convert").required(true),
+            )
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::String, "from", "Coin or currency of the amount, e.g. btc")
+                    .required(true),
+            )
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::String, "to", "Coin or currency to convert to, e.g. usd")
+                    .required(true),
+            ),
+        CreateCommand::new("alert")
+            .description("Get notified once a price crosses a level")
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::SubCommand, "add", "Set an alert, e.g. btc above 70000")
+                    .add_sub_option(
+                        CreateCommandOption::new(CommandOptionType::String, "ticker", "Ticker, e.g. btc").required(true),
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(CommandOptionType::String, "direction", "Notify above or below the price")
+                            .required(true)
+                            .add_string_choice("above", "above")
+                            .add_string_choice("below", "below"),
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(CommandOptionType::String, "price", "Price to notify at").required(true),
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(CommandOptionType::String, "notify", "How to notify, dm by default")
+                            .add_string_choice("dm", "dm")
+                            .add_string_choice("mention", "mention"),
+                    )
+                    .add_sub_option(CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "currency",
+                        "Currency of the price, needed if the ticker is shown in several, e.g. usd",
+                    )),
+            )
+            .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "list", "List your alerts"))
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::SubCommand, "remove", "Remove one of your alerts")
+                    .add_sub_option(
+                        CreateCommandOption::new(CommandOptionType::Integer, "id", "Id of the alert, see /alert list")
+                            .required(true),
+                    ),
+            ),
+    ]
+}
+
+/// Ticker `ticker` of `tickers` and the currency of its price, `currency` telling apart the same ticker shown in
+/// several currencies. Either may be written in any case.
+fn find_alert_ticker<'a>(
+    tickers: &'a [(String, String)],
+    ticker: &str,
+    currency: &str,
+) -> Result<(&'a str, &'a str), String> {
+    let ticker = ticker.trim();
+    let currency = currency.trim();
+    let same_ticker: Vec<&(String, String)> =
+        tickers.iter().filter(|(name, _)| name.eq_ignore_ascii_case(ticker)).collect();
+    if same_ticker.is_empty() {
+        let mut names: Vec<&str> = Vec::new();
+        for (name, _) in tickers {
+            if !names.contains(&name.as_str()) {
+                names.push(name);
+            }
+        }
+        return Err(format!("No ticker {}, alerts can be set on {}", ticker, names.join(", ")));
+    }
+
+    let currencies = || {
+        same_ticker
+            .iter()
+            .map(|(_, vs_currency)| vs_currency.to_uppercase())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let matching: Vec<&&(String, String)> = same_ticker
+        .iter()
+        .filter(|(_, vs_currency)| currency.is_empty() || vs_currency.eq_ignore_ascii_case(currency))
+        .collect();
+    match matching[..] {
+        [(name, vs_currency)] => Ok((name, vs_currency)),
+        [] => Err(format!("{} is not shown in {}, only in {}", same_ticker[0].0, currency.to_uppercase(), currencies())),
+        _ => Err(format!("{} is shown in {}, pick one with `currency`", same_ticker[0].0, currencies())),
+    }
+}
+
+/// Amount given to a command, a plain decimal so that no digit is lost, e.g. `0.5` or `1,000`
+fn parse_amount(amount: &str) -> Result<BigDecimal, String> {
+    BigDecimal::from_str(&amount.trim().replace(',', ""))
+        .map_err(|_| format!("`{}` is not a number", amount))
+}
+
+/// Renders an amount with 2 decimals, or with 4 significant digits below 1, grouped by thousands
+pub(crate) fn format_amount(amount: &BigDecimal) -> String {
+    let format = PriceFormat {
+        thousands_separator: true,
+        significant_digits: (amount.abs() < BigDecimal::from(1)).then_some(4),
+        ..Default::default()
+    };
+    format_price(amount, 2, &format)
+}
+
+fn conversion_embed(
+    amount: &BigDecimal,
+    from: &str,
+    to: &str,
+    converted: &BigDecimal,
+    rate: &BigDecimal,
+    source: QuoteSource,
+) -> CreateEmbed {
+    let (from, to) = (from.to_uppercase(), to.to_uppercase());
+    CreateEmbed::new()
+        .title(format!("{} {} = {} {}", amount.normalized(), from, format_amount(converted), to))
+        .description(format!("1 {} = {} {}", from, format_amount(rate), to))
+        .footer(CreateEmbedFooter::new(format!("Quoted by {:?}", source)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_amount() {
+        assert_eq!(BigDecimal::from_str("0.5").unwrap(), parse_amount(" 0.5 ").unwrap());
+        assert_eq!(BigDecimal::from(1000), parse_amount("1,000").unwrap());
+        assert_eq!("`half` is not a number", parse_amount("half").unwrap_err());
+    }
+
+    #[test]
+    fn test_find_alert_ticker() {
+        let tickers = vec![
+            ("BTC".to_string(), "usd".to_string()),
+            ("BTC".to_string(), "eur".to_string()),
+            ("ETH".to_string(), "usd".to_string()),
+        ];
+        assert_eq!(Ok(("ETH", "usd")), find_alert_ticker(&tickers, " eth ", ""));
+        assert_eq!(Ok(("BTC", "eur")), find_alert_ticker(&tickers, "btc", "EUR"));
+        assert_eq!(
+            Err("BTC is shown in USD, EUR, pick one with `currency`".to_string()),
+            find_alert_ticker(&tickers, "btc", "")
+        );
+        assert_eq!(
+            Err("ETH is not shown in EUR, only in USD".to_string()),
+            find_alert_ticker(&tickers, "eth", "eur")
+        );
+        assert_eq!(
+            Err("No ticker SOL, alerts can be set on BTC, ETH".to_string()),
+            find_alert_ticker(&tickers, "SOL", "")
+        );
+    }
+
+    #[test]
+    fn test_format_status() {
+        let mut report = FetchReport {
+            last_fetch_at: chrono::DateTime::from_timestamp(1_700_000_000, 0),
+            source: Some(QuoteSource::CoinGecko),
+            frequency: Some(60),
+            ..Default::default()
+        };
+        assert_eq!(
+            "**BTC**\nLast fetch: <t:1700000000:R> from CoinGecko\nFrequency: every 60 seconds\nErrors: none",
+            format_status("BTC", &report, &[], None)
+        );
+
+        report.paused = true;
+        report.error_streak = 3;
+        report.last_error = Some("rate limited".to_string());
+        let budget = BudgetReport {
+            calls: 4200,
+            budget: 10_000,
+            projected: 12_600,
+        };
+        assert_eq!(
+            "**BTC**\nLast fetch: <t:1700000000:R> from CoinGecko\nFrequency: paused\nErrors: 3 in a row, last: rate limited\n\
+             API budget: 4200 of 10000 calls made this month, 12600 projected\n\
+             Quarantined guilds: Traders (30 min left)",
+            format_status(
+                "BTC",
+                &report,
+                &[("Traders".to_string(), Duration::from_secs(30 * 60))],
+                Some(&budget)
+            )
+        );
+    }
+
+    #[test]
+    fn test_format_amount() {
+        assert_eq!("32,880.56", format_amount(&BigDecimal::from_str("32880.5612").unwrap()));
+        assert_eq!("0.001521", format_amount(&BigDecimal::from_str("0.00152065").unwrap()));
+        assert_eq!("0", format_amount(&BigDecimal::zero()));
+    }
+}
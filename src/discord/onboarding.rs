@@ -0,0 +1,69 @@
+use crate::alert::AlertSender;
+use serenity::all::{Context, EventHandler, Guild};
+use serenity::async_trait;
+use tracing::{info, warn};
+
+/// Notices the guilds the bot of a ticker joins while running, telling the operator and, with `welcome_message`,
+/// introducing the bot in the system channel of the guild
+pub struct GuildJoinHandler {
+    ticker: String,
+    welcome_message: bool,
+    alert_sender: AlertSender,
+}
+
+impl GuildJoinHandler {
+    pub fn new(ticker: String, welcome_message: bool, alert_sender: AlertSender) -> Self {
+        Self {
+            ticker,
+            welcome_message,
+            alert_sender,
+        }
+    }
+}
+
+#[async_trait]
+impl EventHandler for GuildJoinHandler {
+    async fn guild_create(&self, ctx: Context, guild: Guild, is_new: Option<bool>) {
+        // the guilds the bot is already in are sent on every connect too
+        if is_new != Some(true) {
+            return;
+        }
+
+        info!("The bot of {} joined guild {} ({})", self.ticker, guild.name, guild.id);
+        let message = format!("➕ {}: the bot joined guild {} ({})", self.ticker, guild.name, guild.id);
+        self.alert_sender.send_with_http(Some(&ctx.http), &message).await;
+
+        if !self.welcome_message {
+            return;
+        }
+        let Some(channel_id) = guild.system_channel_id else {
+            warn!("Guild {} has no system channel, skip the welcome message of {}", guild.id, self.ticker);
+            return;
+        };
+        if let Err(error) = channel_id.say(&ctx.http, welcome_message(&self.ticker)).await {
+            warn!("Error posting the welcome message of {} in guild {}: {}", self.ticker, guild.id, error);
+        }
+    }
+}
+
+fn welcome_message(ticker: &str) -> String {
+    format!(
+        "👋 Thanks for adding me! I show the price of {} as my nickname, with its change as my status. \
+         To update my nickname I need the **Change Nickname** permission in this server.",
+        ticker
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_welcome_message() {
+        assert_eq!(
+            "👋 Thanks for adding me! I show the price of BTC as my nickname, with its change as my status. \
+             To update my nickname I need the **Change Nickname** permission in this server.",
+            welcome_message("BTC")
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Nickname updates failing in a row in a guild, e.g. for a role hierarchy issue, after which it is quarantined
+pub const QUARANTINE_AFTER_FAILURES: u32 = 5;
+/// How long a quarantined guild is left alone, after which a single update is tried again
+pub const QUARANTINE_COOLDOWN: Duration = Duration::from_secs(30 * 60);
+
+/// Guilds consistently rejecting the nickname updates of a bot, skipped for `QUARANTINE_COOLDOWN` so that they
+/// do not cost API requests and log lines every tick. Shared with `/status`, which lists them.
+#[derive(Clone, Debug, Default)]
+pub struct GuildQuarantine {
+    guilds: Arc<Mutex<HashMap<u64, GuildFailures>>>,
+}
+
+#[derive(Debug)]
+struct GuildFailures {
+    name: String,
+    count: u32,
+    quarantined_until: Option<Instant>,
+}
+
+impl GuildQuarantine {
+    /// Whether updating the guild is to be skipped at `now`
+    pub fn is_quarantined(&self, guild_id: u64, now: Instant) -> bool {
+        self.guilds
+            .lock()
+            .unwrap()
+            .get(&guild_id)
+            .and_then(|failures| failures.quarantined_until)
+            .is_some_and(|until| now < until)
+    }
+
+    /// Records a failed update, returning true if the guild got quarantined by it. A guild failing again
+    /// after its cooldown is quarantined again right away.
+    pub fn record_failure(&self, guild_id: u64, name: &str, now: Instant) -> bool {
+        let mut guilds = self.guilds.lock().unwrap();
+        let failures = guilds.entry(guild_id).or_insert_with(|| GuildFailures {
+            name: name.to_string(),
+            count: 0,
+            quarantined_until: None,
+        });
+        failures.count += 1;
+        if failures.count < QUARANTINE_AFTER_FAILURES {
+            return false;
+        }
+
+        failures.quarantined_until = Some(now + QUARANTINE_COOLDOWN);
+        true
+    }
+
+    /// Records a successful update, returning true if the guild was quarantined
+    pub fn record_success(&self, guild_id: u64) -> bool {
+        self.guilds
+            .lock()
+            .unwrap()
+            .remove(&guild_id)
+            .is_some_and(|failures| failures.quarantined_until.is_some())
+    }
+
+    /// Names of the guilds quarantined at `now` with the time left, sorted by name
+    pub fn quarantined(&self, now: Instant) -> Vec<(String, Duration)> {
+        let mut quarantined: Vec<(String, Duration)> = self
+            .guilds
+            .lock()
+            .unwrap()
+            .values()
+            .filter_map(|failures| {
+                let until = failures.quarantined_until.filter(|until| now < *until)?;
+                Some((failures.name.clone(), until - now))
+            })
+            .collect();
+        quarantined.sort();
+        quarantined
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_quarantine() {
+        let quarantine = GuildQuarantine::default();
+        for _ in 1..QUARANTINE_AFTER_FAILURES {
+            assert!(!quarantine.record_failure(1, "Traders", Instant::now()));
+        }
+        assert!(!quarantine.is_quarantined(1, Instant::now()));
+        assert!(quarantine.record_failure(1, "Traders", Instant::now()));
+        assert!(quarantine.is_quarantined(1, Instant::now()));
+        assert_eq!(
+            vec![("Traders".to_string(), QUARANTINE_COOLDOWN)],
+            quarantine.quarantined(Instant::now())
+        );
+
+        tokio::time::advance(QUARANTINE_COOLDOWN).await;
+        assert!(!quarantine.is_quarantined(1, Instant::now()));
+        assert!(quarantine.quarantined(Instant::now()).is_empty());
+
+        // still failing after the cooldown
+        assert!(quarantine.record_failure(1, "Traders", Instant::now()));
+        assert!(quarantine.is_quarantined(1, Instant::now()));
+
+        assert!(quarantine.record_success(1));
+        assert!(!quarantine.is_quarantined(1, Instant::now()));
+        assert!(!quarantine.record_success(1));
+    }
+
+    #[test]
+    fn test_success_resets_failures() {
+        let now = Instant::now();
+        let quarantine = GuildQuarantine::default();
+        for _ in 1..QUARANTINE_AFTER_FAILURES {
+            quarantine.record_failure(1, "Traders", now);
+        }
+        assert!(!quarantine.record_success(1));
+        assert!(!quarantine.record_failure(1, "Traders", now));
+    }
+}
@@ -0,0 +1,66 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Spaces the nickname updates of every bot sharing it by at least `spacing`, so that tickers ticking together
+/// spread their guild updates over the tick rather than sending them from the same IP in one burst
+#[derive(Clone, Debug)]
+pub struct UpdateStagger {
+    spacing: Duration,
+    next_slot: Arc<Mutex<Instant>>,
+}
+
+impl UpdateStagger {
+    pub fn new(spacing: Duration) -> Self {
+        Self {
+            spacing,
+            next_slot: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Takes the next free slot and waits for it, right away if no update went out for `spacing`
+    pub async fn wait(&self) {
+        let slot = {
+            let mut next_slot = self.next_slot.lock().unwrap();
+            let slot = (*next_slot).max(Instant::now());
+            *next_slot = slot + self.spacing;
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_wait() {
+        let stagger = UpdateStagger::new(Duration::from_millis(500));
+        let started_at = Instant::now();
+
+        let (first, second, third) = tokio::join!(
+            async {
+                stagger.wait().await;
+                started_at.elapsed()
+            },
+            async {
+                stagger.wait().await;
+                started_at.elapsed()
+            },
+            async {
+                stagger.wait().await;
+                started_at.elapsed()
+            },
+        );
+        assert_eq!(Duration::ZERO, first);
+        assert_eq!(Duration::from_millis(500), second);
+        assert_eq!(Duration::from_millis(1000), third);
+
+        // idle for longer than the spacing
+        tokio::time::advance(Duration::from_secs(5)).await;
+        let idle_at = Instant::now();
+        stagger.wait().await;
+        assert_eq!(Duration::ZERO, idle_at.elapsed());
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Discord allows renaming a channel only twice per 10 minutes
+pub const CHANNEL_RENAME_LIMIT: usize = 2;
+pub const CHANNEL_RENAME_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// Sliding-window limiter keyed by channel id
+#[derive(Debug)]
+pub struct ChannelThrottle {
+    limit: usize,
+    window: Duration,
+    history: HashMap<u64, VecDeque<Instant>>,
+}
+
+impl ChannelThrottle {
+    pub fn new(limit: usize, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            history: HashMap::new(),
+        }
+    }
+
+    /// Records an action on the channel at `now` and returns true if it is within the limit,
+    /// returns false without recording anything otherwise
+    pub fn try_acquire(&mut self, channel_id: u64, now: Instant) -> bool {
+        let history = self.history.entry(channel_id).or_default();
+        while history
+            .front()
+            .is_some_and(|time| now.duration_since(*time) >= self.window)
+        {
+            history.pop_front();
+        }
+
+        if history.len() >= self.limit {
+            return false;
+        }
+
+        history.push_back(now);
+        true
+    }
+}
+
+impl Default for ChannelThrottle {
+    fn default() -> Self {
+        Self::new(CHANNEL_RENAME_LIMIT, CHANNEL_RENAME_WINDOW)
+    }
+}
+
+/// Lets the nickname of a bot change at most once per interval, while its status is updated on every tick
+#[derive(Debug, Default)]
+pub struct NicknameThrottle {
+    interval: Option<Duration>, // No limit if unset
+    last_update: Option<Instant>,
+}
+
+impl NicknameThrottle {
+    pub fn new(interval: Option<Duration>) -> Self {
+        Self {
+            interval,
+            last_update: None,
+        }
+    }
+
+    /// Records a nickname update at `now` and returns true if the interval passed since the last one,
+    /// returns false without recording anything otherwise
+    pub fn try_acquire(&mut self, now: Instant) -> bool {
+        if let (Some(interval), Some(last_update)) = (self.interval, self.last_update) {
+            if now.duration_since(last_update) < interval {
+                return false;
+            }
+        }
+
+        self.last_update = Some(now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_acquire() {
+        let mut throttle = ChannelThrottle::default();
+        let start = Instant::now();
+
+        assert!(throttle.try_acquire(1, start));
+        assert!(throttle.try_acquire(1, start + Duration::from_secs(60)));
+        assert!(!throttle.try_acquire(1, start + Duration::from_secs(120)));
+        assert!(throttle.try_acquire(2, start + Duration::from_secs(120)));
+        assert!(throttle.try_acquire(1, start + CHANNEL_RENAME_WINDOW));
+        assert!(!throttle.try_acquire(1, start + CHANNEL_RENAME_WINDOW + Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_nickname_throttle() {
+        let start = Instant::now();
+        let mut throttle = NicknameThrottle::new(Some(Duration::from_secs(120)));
+        assert!(throttle.try_acquire(start));
+        assert!(!throttle.try_acquire(start + Duration::from_secs(15)));
+        assert!(!throttle.try_acquire(start + Duration::from_secs(105)));
+        assert!(throttle.try_acquire(start + Duration::from_secs(120)));
+        assert!(!throttle.try_acquire(start + Duration::from_secs(135)));
+
+        let mut throttle = NicknameThrottle::default();
+        assert!(throttle.try_acquire(start));
+        assert!(throttle.try_acquire(start + Duration::from_secs(1)));
+    }
+}
@@ -0,0 +1,127 @@
+use crate::secret::SecretString;
+use reqwest::{header, StatusCode};
+use serde::Deserialize;
+use std::error::Error;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{debug, info};
+
+/// Keeps a single message posted through a Discord webhook up to date,
+/// for showing prices without creating a bot application per asset.
+#[derive(Clone, Debug)]
+pub struct DiscordWebhook {
+    http_client: reqwest::Client,
+    url: SecretString, // the URL holds the token of the webhook
+    message_id: Arc<Mutex<Option<String>>>,
+}
+
+#[derive(Deserialize)]
+struct WebhookMessage {
+    id: String,
+}
+
+impl DiscordWebhook {
+    /// Creates a webhook client editing the message `message_id` if given,
+    /// otherwise a new message is posted on the first update.
+    pub fn new(http_client: reqwest::Client, url: &str, message_id: Option<u64>) -> Self {
+        Self {
+            http_client,
+            url: SecretString::from(url.trim_end_matches('/')),
+            message_id: Arc::new(Mutex::new(message_id.map(|id| id.to_string()))),
+        }
+    }
+
+    pub async fn update_message(&self, content: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let body = serde_json::json!({ "content": content }).to_string();
+        let mut message_id = self.message_id.lock().await;
+
+        if let Some(id) = message_id.as_ref() {
+            let response = self
+                .http_client
+                .patch(format!("{}/messages/{}", self.url.expose(), id))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(body.clone())
+                .send()
+                .await
+                .map_err(reqwest::Error::without_url)?;
+
+            if response.status() != StatusCode::NOT_FOUND {
+                response.error_for_status().map_err(reqwest::Error::without_url)?;
+                debug!("Edited webhook message {} to {}", id, content);
+                return Ok(());
+            }
+
+            info!("Webhook message {} is gone, posting a new one", id);
+        }
+
+        let response = self
+            .http_client
+            .post(format!("{}?wait=true", self.url.expose()))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(reqwest::Error::without_url)?;
+
+        let text = response.text().await.map_err(reqwest::Error::without_url)?;
+        let message: WebhookMessage = serde_json::from_str(&text)?;
+        info!("Posted webhook message {}", message.id);
+        *message_id = Some(message.id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_json, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_update_message_posts_then_edits() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/webhooks/1/token"))
+            .and(query_param("wait", "true"))
+            .and(body_json(serde_json::json!({ "content": "first" })))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id":"42"}"#))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/api/webhooks/1/token/messages/42"))
+            .and(body_json(serde_json::json!({ "content": "second" })))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id":"42"}"#))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let url = format!("{}/api/webhooks/1/token", server.uri());
+        let webhook = DiscordWebhook::new(reqwest::Client::new(), &url, None);
+        webhook.update_message("first").await.unwrap();
+        webhook.update_message("second").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_update_message_reposts_deleted_message() {
+        let server = MockServer::start().await;
+        Mock::given(method("PATCH"))
+            .and(path("/api/webhooks/1/token/messages/7"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/webhooks/1/token"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id":"8"}"#))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let url = format!("{}/api/webhooks/1/token", server.uri());
+        let webhook = DiscordWebhook::new(reqwest::Client::new(), &url, Some(7));
+        webhook.update_message("price").await.unwrap();
+        assert_eq!(Some("8".to_string()), *webhook.message_id.lock().await);
+    }
+}
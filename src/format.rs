@@ -0,0 +1,731 @@
+use bigdecimal::num_bigint::Sign;
+use bigdecimal::{BigDecimal, RoundingMode, Zero};
+use serde::Deserialize;
+
+/// Options for rendering a price, on top of the number of decimals
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct PriceFormat {
+    #[serde(default)]
+    pub thousands_separator: bool, // Group the integer digits by thousands, e.g. `65,761.23`
+    #[serde(default)]
+    pub compact: bool, // Abbreviate large values (`1.92T`) and the leading zeros of tiny values (`0.0₅712`)
+    pub compact_decimals: Option<CompactDecimals>, // Decimals of abbreviated values per magnitude, instead of `decimals`
+    #[serde(default)]
+    pub lowercase_thousands: bool, // Abbreviate thousands as `65.7k` instead of `65.7K`
+    pub small_price_notation: Option<SmallPriceNotation>, // How to render tiny values, overrides what `compact` does to them
+    pub significant_digits: Option<u32>, // Keep this many significant digits instead of a fixed number of decimals
+    #[serde(default)]
+    pub pad_decimals: bool, // Pad with trailing zeros to always show all decimals, e.g. `123.50` instead of `123.5`
+    pub locale: Option<Locale>, // Separators of a locale like `de-DE` (`65.761,23`), defaults to `65,761.23`
+    #[serde(default)]
+    pub rounding: Rounding, // How the price is rounded to its decimals, defaults to `half_even`
+}
+
+/// Decimals of abbreviated values by magnitude, e.g. `{"thousands": 1, "millions": 2}` for `65.7K` and `1.23M`,
+/// a magnitude left out keeps the decimals of the ticker
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CompactDecimals {
+    pub thousands: Option<u8>,
+    pub millions: Option<u8>,
+    pub billions: Option<u8>,
+    pub trillions: Option<u8>,
+}
+
+impl CompactDecimals {
+    /// Decimals of values abbreviated by the power of ten `exponent`
+    fn for_exponent(&self, exponent: i64) -> Option<u8> {
+        match exponent {
+            3 => self.thousands,
+            6 => self.millions,
+            9 => self.billions,
+            12 => self.trillions,
+            _ => None,
+        }
+    }
+}
+
+/// How a price is rounded to the decimals shown
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Rounding {
+    HalfUp, // Halves away from zero, e.g. `0.125` to `0.13` and `0.135` to `0.14`
+    Floor, // Toward negative infinity, a price is never shown higher than it is
+    Ceiling, // Toward positive infinity
+    #[default]
+    HalfEven, // Halves to the even digit, e.g. `0.125` to `0.12` and `0.135` to `0.14`
+}
+
+impl From<Rounding> for RoundingMode {
+    fn from(rounding: Rounding) -> Self {
+        match rounding {
+            Rounding::HalfUp => RoundingMode::HalfUp,
+            Rounding::Floor => RoundingMode::Floor,
+            Rounding::Ceiling => RoundingMode::Ceiling,
+            Rounding::HalfEven => RoundingMode::HalfEven,
+        }
+    }
+}
+
+/// Thousands and decimal separators of the numbers of a locale, given by its tag like `de-DE` or `fr`
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(try_from = "String")]
+pub struct Locale {
+    pub thousands_separator: char,
+    pub decimal_separator: char,
+}
+
+impl TryFrom<String> for Locale {
+    type Error = String;
+
+    fn try_from(tag: String) -> Result<Self, Self::Error> {
+        let tag = tag.replace('_', "-").to_lowercase();
+        let language = tag.split('-').next().unwrap_or_default();
+
+        let (thousands_separator, decimal_separator) = match (language, tag.as_str()) {
+            // Switzerland groups with an apostrophe whatever the language
+            (_, "de-ch" | "fr-ch" | "it-ch") => ('\'', '.'),
+            ("en" | "ja" | "zh" | "ko" | "th" | "he", _) => (',', '.'),
+            ("de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el" | "ro" | "vi", _) => ('.', ','),
+            // narrow no-break space, so that Discord never wraps a price
+            ("fr" | "pl" | "ru" | "uk" | "cs" | "sk" | "sv" | "nb" | "fi" | "hu", _) => ('\u{202f}', ','),
+            _ => return Err(format!("unsupported locale `{}`", tag)),
+        };
+
+        Ok(Self {
+            thousands_separator,
+            decimal_separator,
+        })
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self {
+            thousands_separator: ',',
+            decimal_separator: '.',
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SmallPriceNotation {
+    Plain, // `0.00000712`
+    Subscript, // `0.0₅712`, the subscript is the number of zeros after the decimal point
+    Scientific, // `7.12e-6`
+}
+
+impl PriceFormat {
+    /// Decimals of a value abbreviated by the power of ten `exponent`
+    fn compact_decimals(&self, exponent: i64, decimals: u8) -> u8 {
+        self.compact_decimals
+            .as_ref()
+            .and_then(|compact_decimals| compact_decimals.for_exponent(exponent))
+            .unwrap_or(decimals)
+    }
+
+    fn compact_suffix(&self, suffix: &'static str) -> &'static str {
+        match suffix {
+            "K" if self.lowercase_thousands => "k",
+            suffix => suffix,
+        }
+    }
+
+    fn small_price_notation(&self) -> SmallPriceNotation {
+        match self.small_price_notation {
+            Some(notation) => notation,
+            None if self.compact => SmallPriceNotation::Subscript,
+            None => SmallPriceNotation::Plain,
+        }
+    }
+}
+
+/// Symbols the `{trend}` placeholder shows depending on the sign of the change over 24h, e.g. `🟢▲` and `🔴▼`
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TrendSymbols {
+    pub up: Option<String>, // Shown for a rise, defaults to `📈`
+    pub down: Option<String>, // Shown for a fall, defaults to `📉`
+    pub flat: Option<String>, // Shown for a change within `flat_threshold`, defaults to `➖`
+    pub flat_threshold: Option<BigDecimal>, // Changes of at most this many percent either way are flat, e.g. `0.1`; without it only a fall shows `down`
+}
+
+impl TrendSymbols {
+    pub const DEFAULT_UP: &'static str = "📈";
+    pub const DEFAULT_DOWN: &'static str = "📉";
+    pub const DEFAULT_FLAT: &'static str = "➖";
+
+    /// Symbol of a change over 24h in percent
+    pub fn symbol(&self, price_change: &BigDecimal) -> &str {
+        if let Some(flat_threshold) = &self.flat_threshold {
+            if &price_change.abs() <= flat_threshold {
+                return self.flat.as_deref().unwrap_or(Self::DEFAULT_FLAT);
+            }
+        }
+
+        if price_change.sign() == Sign::Minus {
+            self.down.as_deref().unwrap_or(Self::DEFAULT_DOWN)
+        } else {
+            self.up.as_deref().unwrap_or(Self::DEFAULT_UP)
+        }
+    }
+}
+
+/// Emoji `{change_emoji}` shows for a change over 24h within its bucket, e.g. `{"above": 5, "emoji": "🚀"}`
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChangeEmoji {
+    pub above: Option<BigDecimal>, // Changes must be greater than this many percent, no lower bound if omitted
+    pub below: Option<BigDecimal>, // Changes must be less than this many percent, no upper bound if omitted
+    pub emoji: String, // Shown for a change within the bucket
+}
+
+impl ChangeEmoji {
+    fn contains(&self, price_change: &BigDecimal) -> bool {
+        self.above.as_ref().is_none_or(|above| price_change > above)
+            && self.below.as_ref().is_none_or(|below| price_change < below)
+    }
+}
+
+/// Emoji of the first bucket of `change_emojis` a change over 24h in percent falls in, empty if there is none
+pub fn change_emoji<'a>(change_emojis: &'a [ChangeEmoji], price_change: &BigDecimal) -> &'a str {
+    change_emojis
+        .iter()
+        .find(|bucket| bucket.contains(price_change))
+        .map_or("", |bucket| bucket.emoji.as_str())
+}
+
+/// Suffixes for compact notation, with the power of ten they stand for, largest first
+const COMPACT_SUFFIXES: [(i64, &str); 4] = [(12, "T"), (9, "B"), (6, "M"), (3, "K")];
+
+/// Tiny values need at least this many zeros after the decimal point to get them collapsed
+const MIN_SUBSCRIPT_ZEROS: usize = 4;
+
+pub fn format_price(price: &BigDecimal, decimals: u8, format: &PriceFormat) -> String {
+    let compact = if format.compact {
+        format_compact(price, decimals, format)
+    } else {
+        None
+    };
+
+    let mut formatted = match compact {
+        Some(compact) => compact,
+        None => format_small_price(
+            &to_plain_string(&round_price(price, decimals, format)),
+            format.small_price_notation(),
+        ),
+    };
+
+    let locale = format.locale.unwrap_or_default();
+    formatted = localize_decimal_separator(&formatted, locale);
+    if format.thousands_separator {
+        formatted = group_thousands(&formatted, locale.thousands_separator);
+    }
+
+    formatted
+}
+
+/// Renders a change in percent rounded to `decimals` places, with its sign, e.g. `+1.50%`
+pub fn format_price_change(price_change: &BigDecimal, decimals: u8, locale: Option<Locale>) -> String {
+    let rounded = price_change.with_scale_round(decimals.into(), RoundingMode::HalfEven);
+
+    // if price change >= 0, add a plus sign, a change rounded to zero never shows as `-0.00%`
+    let sign = if rounded.sign() == Sign::Minus { "" } else { "+" };
+    let formatted = localize_decimal_separator(&to_plain_string(&rounded), locale.unwrap_or_default());
+    format!("{}{}%", sign, formatted)
+}
+
+/// Longest nickname Discord accepts, longer ones are refused with a 400
+pub const MAX_NICKNAME_LEN: usize = 32;
+/// Longest custom activity Discord accepts as the status of a bot
+pub const MAX_STATUS_LEN: usize = 128;
+
+/// Length of a text in UTF-16 code units, where an emoji outside the basic plane counts twice,
+/// so that it never falls short of the length Discord checks
+fn discord_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+pub fn fits_nickname(name: &str) -> bool {
+    discord_len(name) <= MAX_NICKNAME_LEN
+}
+
+/// Cuts the name to `MAX_NICKNAME_LEN`, ending it with `…` if anything was cut
+pub fn truncate_nickname(name: &str) -> String {
+    truncate_with_ellipsis(name, MAX_NICKNAME_LEN)
+}
+
+pub fn fits_status(status: &str) -> bool {
+    discord_len(status) <= MAX_STATUS_LEN
+}
+
+/// Cuts the status to `MAX_STATUS_LEN`, ending it with `…` if anything was cut
+pub fn truncate_status(status: &str) -> String {
+    truncate_with_ellipsis(status, MAX_STATUS_LEN)
+}
+
+fn truncate_with_ellipsis(text: &str, max_len: usize) -> String {
+    if discord_len(text) <= max_len {
+        return text.to_string();
+    }
+
+    let mut truncated = String::new();
+    let mut len = discord_len("…");
+    for c in text.chars() {
+        len += c.len_utf16();
+        if len > max_len {
+            break;
+        }
+        truncated.push(c);
+    }
+
+    // an emoji cut in the middle of its sequence would leave a joiner or a variation selector dangling
+    let truncated = truncated.trim_end_matches(|c: char| c == '\u{200d}' || c == '\u{fe0f}' || c.is_whitespace());
+    format!("{}…", truncated)
+}
+
+/// Replaces the decimal point of a formatted number with the separator of the locale
+fn localize_decimal_separator(formatted: &str, locale: Locale) -> String {
+    if locale.decimal_separator == '.' {
+        return formatted.to_string();
+    }
+    formatted.replacen('.', &locale.decimal_separator.to_string(), 1)
+}
+
+/// Rounds the price to `decimals` places, or to `significant_digits` significant digits if set,
+/// prices with fewer places are left as they are unless `pad_decimals` is set
+fn round_price(price: &BigDecimal, decimals: u8, format: &PriceFormat) -> BigDecimal {
+    let rounded = match format.significant_digits {
+        Some(significant_digits) => {
+            let rounded = round_to_decimals(price, significant_decimals(price, significant_digits), format.rounding);
+            // rounding may carry into a new leading digit, e.g. 9.996 becomes 10.00 with 3 significant digits
+            round_to_decimals(&rounded, significant_decimals(&rounded, significant_digits), format.rounding)
+        }
+        None => round_to_decimals(price, decimals.into(), format.rounding),
+    };
+
+    if !format.pad_decimals {
+        return rounded;
+    }
+
+    let decimals = match format.significant_digits {
+        Some(significant_digits) => significant_decimals(&rounded, significant_digits),
+        None => decimals.into(),
+    };
+
+    if rounded.fractional_digit_count() < decimals {
+        rounded.with_scale(decimals)
+    } else {
+        rounded
+    }
+}
+
+fn round_to_decimals(price: &BigDecimal, decimals: i64, rounding: Rounding) -> BigDecimal {
+    if price.fractional_digit_count() > decimals {
+        price.with_scale_round(decimals, rounding.into())
+    } else {
+        price.clone()
+    }
+}
+
+/// Number of decimals needed to show `significant_digits` significant digits of the price,
+/// digits of the integer part are never rounded away, so `65761.23` keeps `65761` even with 3
+fn significant_decimals(price: &BigDecimal, significant_digits: u32) -> i64 {
+    if price.is_zero() {
+        return 0;
+    }
+
+    // power of ten of the leading digit, e.g. 4 for 65761.23 and -6 for 0.000004123
+    let leading_exponent = price.digits() as i64 - price.fractional_digit_count() - 1;
+    (significant_digits as i64 - 1 - leading_exponent).max(0)
+}
+
+/// Formats values of at least a thousand as `65.76K`, `1.92T` and so on,
+/// returns `None` for smaller values
+fn format_compact(price: &BigDecimal, decimals: u8, format: &PriceFormat) -> Option<String> {
+    let abs_price = price.abs();
+    let index = COMPACT_SUFFIXES
+        .iter()
+        .position(|(exponent, _)| abs_price >= BigDecimal::new(1.into(), -exponent))?;
+
+    let abbreviate = |(exponent, suffix): (i64, &'static str)| {
+        let mantissa = round_price(
+            &(price * BigDecimal::new(1.into(), exponent)).normalized(),
+            format.compact_decimals(exponent, decimals),
+            format,
+        );
+        (mantissa, format.compact_suffix(suffix))
+    };
+    let (mut mantissa, mut suffix) = abbreviate(COMPACT_SUFFIXES[index]);
+
+    // rounding may carry over to the next suffix, e.g. 999,999.999 would become `1000.00K`
+    if index > 0 && mantissa.abs() >= BigDecimal::from(1000) {
+        (mantissa, suffix) = abbreviate(COMPACT_SUFFIXES[index - 1]);
+    }
+
+    Some(format!("{}{}", to_plain_string(&mantissa), suffix))
+}
+
+/// Rewrites tiny values in the given notation, e.g. `0.00000712` becomes `0.0₅712` or `7.12e-6`,
+/// values with only a few zeros after the decimal point are left as they are
+fn format_small_price(formatted: &str, notation: SmallPriceNotation) -> String {
+    let (sign, digits) = split_sign(formatted);
+    let fraction = match digits.strip_prefix("0.") {
+        Some(fraction) => fraction,
+        None => return formatted.to_string(),
+    };
+
+    let zeros = fraction.chars().take_while(|c| *c == '0').count();
+    if zeros < MIN_SUBSCRIPT_ZEROS || zeros == fraction.len() {
+        return formatted.to_string();
+    }
+
+    let significand = &fraction[zeros..];
+    match notation {
+        SmallPriceNotation::Plain => formatted.to_string(),
+        SmallPriceNotation::Subscript => {
+            format!("{}0.0{}{}", sign, to_subscript(zeros), significand)
+        }
+        SmallPriceNotation::Scientific => {
+            let (first, rest) = significand.split_at(1);
+            let rest = rest.trim_end_matches('0');
+            let point = if rest.is_empty() { "" } else { "." };
+            format!("{}{}{}{}e-{}", sign, first, point, rest, zeros + 1)
+        }
+    }
+}
+
+fn to_subscript(number: usize) -> String {
+    number
+        .to_string()
+        .chars()
+        .map(|c| char::from_u32('₀' as u32 + c.to_digit(10).unwrap_or(0)).unwrap_or(c))
+        .collect()
+}
+
+/// Inserts `separator` between every three digits of the integer part
+fn group_thousands(formatted: &str, separator: char) -> String {
+    let (sign, digits) = split_sign(formatted);
+    let (integer, rest) = match digits.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => digits.split_at(index),
+        None => (digits, ""),
+    };
+
+    let mut grouped = String::new();
+    for (i, c) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push(separator);
+        }
+        grouped.push(c);
+    }
+
+    format!("{}{}{}", sign, grouped, rest)
+}
+
+fn split_sign(formatted: &str) -> (&str, &str) {
+    match formatted.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", formatted),
+    }
+}
+
+/// Renders the value without exponent notation, which `BigDecimal::to_string` uses for tiny values
+fn to_plain_string(value: &BigDecimal) -> String {
+    let (digits, scale) = value.as_bigint_and_exponent();
+    let sign = if digits.sign() == Sign::Minus { "-" } else { "" };
+    let mut digits = digits.magnitude().to_string();
+
+    if scale <= 0 {
+        digits.push_str(&"0".repeat(scale.unsigned_abs() as usize));
+    } else {
+        let scale = scale as usize;
+        if digits.len() <= scale {
+            digits.insert_str(0, &"0".repeat(scale - digits.len() + 1));
+        }
+        digits.insert(digits.len() - scale, '.');
+    }
+
+    format!("{}{}", sign, digits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn price(value: &str) -> BigDecimal {
+        BigDecimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn test_format_price() {
+        let price = price("123456789.123456789");
+        let format = PriceFormat::default();
+        assert_eq!("123456789", format_price(&price, 0, &format));
+        assert_eq!("123456789.12", format_price(&price, 2, &format));
+        assert_eq!("123456789.123456789", format_price(&price, 9, &format));
+        assert_eq!("123456789.123456789", format_price(&price, 10, &format));
+    }
+
+    #[test]
+    fn test_format_price_tiny_value_without_exponent() {
+        let format = PriceFormat::default();
+        assert_eq!("0.0000000000712", format_price(&price("7.12E-11"), 13, &format));
+        assert_eq!("100000", format_price(&price("1E+5"), 2, &format));
+    }
+
+    #[test]
+    fn test_format_price_thousands_separator() {
+        let format = PriceFormat {
+            thousands_separator: true,
+            ..Default::default()
+        };
+        assert_eq!("65,761.23", format_price(&price("65761.2345"), 2, &format));
+        assert_eq!("-1,234,567", format_price(&price("-1234567"), 2, &format));
+        assert_eq!("123.45", format_price(&price("123.45"), 2, &format));
+        assert_eq!("0.00001234", format_price(&price("0.00001234"), 8, &format));
+    }
+
+    #[test]
+    fn test_format_price_compact() {
+        let format = PriceFormat {
+            compact: true,
+            ..Default::default()
+        };
+        assert_eq!("1.92T", format_price(&price("1920000000000"), 2, &format));
+        assert_eq!("65.76K", format_price(&price("65761.23"), 2, &format));
+        assert_eq!("-3.5M", format_price(&price("-3500000"), 2, &format));
+        assert_eq!("1.00M", format_price(&price("999999.999"), 2, &format));
+        assert_eq!("999.99", format_price(&price("999.99"), 2, &format));
+        assert_eq!("0.0₅712", format_price(&price("0.00000712"), 8, &format));
+        assert_eq!("0.0₁₁712", format_price(&price("7.12E-12"), 14, &format));
+        assert_eq!("0.000712", format_price(&price("0.000712"), 6, &format));
+        assert_eq!("0.00", format_price(&price("0.00000712"), 2, &format));
+    }
+
+    #[test]
+    fn test_format_price_compact_decimals() {
+        let format = PriceFormat {
+            compact: true,
+            compact_decimals: Some(CompactDecimals {
+                thousands: Some(1),
+                millions: Some(2),
+                ..Default::default()
+            }),
+            lowercase_thousands: true,
+            ..Default::default()
+        };
+        assert_eq!("65.8k", format_price(&price("65761.23"), 4, &format));
+        assert_eq!("1.23M", format_price(&price("1234567"), 4, &format));
+        assert_eq!("1.9235B", format_price(&price("1923456789"), 4, &format));
+        assert_eq!("999.9876", format_price(&price("999.9876"), 4, &format));
+        // carried over to millions, with their decimals
+        assert_eq!("1.00M", format_price(&price("999960"), 4, &format));
+    }
+
+    #[test]
+    fn test_format_price_small_price_notation() {
+        let format = |notation| PriceFormat {
+            small_price_notation: Some(notation),
+            ..Default::default()
+        };
+
+        let subscript = format(SmallPriceNotation::Subscript);
+        assert_eq!("0.0₅712", format_price(&price("0.00000712"), 8, &subscript));
+        assert_eq!("-0.0₅712", format_price(&price("-0.00000712"), 8, &subscript));
+        assert_eq!("65761.23", format_price(&price("65761.23"), 2, &subscript));
+
+        let scientific = format(SmallPriceNotation::Scientific);
+        assert_eq!("7.12e-6", format_price(&price("0.00000712"), 8, &scientific));
+        assert_eq!("7e-6", format_price(&price("0.00000700"), 8, &scientific));
+        assert_eq!("0.0712", format_price(&price("0.0712"), 8, &scientific));
+
+        let plain_compact = PriceFormat {
+            compact: true,
+            ..format(SmallPriceNotation::Plain)
+        };
+        assert_eq!("0.00000712", format_price(&price("0.00000712"), 8, &plain_compact));
+        assert_eq!("1.92T", format_price(&price("1920000000000"), 2, &plain_compact));
+    }
+
+    #[test]
+    fn test_format_price_significant_digits() {
+        let format = PriceFormat {
+            significant_digits: Some(3),
+            ..Default::default()
+        };
+        assert_eq!("0.00000412", format_price(&price("0.000004123456"), 2, &format));
+        assert_eq!("1.23", format_price(&price("1.23456"), 0, &format));
+        assert_eq!("0.5", format_price(&price("0.5"), 0, &format));
+        assert_eq!("65761", format_price(&price("65761.23"), 2, &format));
+        assert_eq!("10.0", format_price(&price("9.996"), 2, &format));
+        assert_eq!("0", format_price(&price("0"), 2, &format));
+
+        let compact = PriceFormat {
+            compact: true,
+            ..format
+        };
+        assert_eq!("65.8K", format_price(&price("65761.23"), 2, &compact));
+        assert_eq!("0.0₅412", format_price(&price("0.000004123456"), 2, &compact));
+    }
+
+    #[test]
+    fn test_format_price_rounding() {
+        let format = |rounding| PriceFormat {
+            rounding,
+            ..Default::default()
+        };
+        let formatted = |value: &str, rounding| format_price(&price(value), 2, &format(rounding));
+
+        assert_eq!("0.12", formatted("0.125", Rounding::HalfEven));
+        assert_eq!("0.13", formatted("0.125", Rounding::HalfUp));
+        assert_eq!("-0.13", formatted("-0.125", Rounding::HalfUp));
+        assert_eq!("65761.23", formatted("65761.239", Rounding::Floor));
+        assert_eq!("-65761.24", formatted("-65761.231", Rounding::Floor));
+        assert_eq!("65761.24", formatted("65761.231", Rounding::Ceiling));
+        assert_eq!("65761.2", formatted("65761.2", Rounding::Ceiling));
+
+        let compact = PriceFormat {
+            compact: true,
+            ..format(Rounding::Floor)
+        };
+        assert_eq!("65.76K", format_price(&price("65769.99"), 2, &compact));
+        let format: PriceFormat = serde_json::from_str(r#"{"rounding": "half_up"}"#).unwrap();
+        assert_eq!(Rounding::HalfUp, format.rounding);
+    }
+
+    #[test]
+    fn test_format_price_pad_decimals() {
+        let format = PriceFormat {
+            pad_decimals: true,
+            ..Default::default()
+        };
+        assert_eq!("123.50", format_price(&price("123.5"), 2, &format));
+        assert_eq!("123.00", format_price(&price("123"), 2, &format));
+        assert_eq!("123.46", format_price(&price("123.456"), 2, &format));
+        assert_eq!("123", format_price(&price("123.456"), 0, &format));
+        assert_eq!("100000.00", format_price(&price("1E+5"), 2, &format));
+
+        let compact = PriceFormat {
+            compact: true,
+            ..format.clone()
+        };
+        assert_eq!("3.50M", format_price(&price("3500000"), 2, &compact));
+
+        let significant = PriceFormat {
+            significant_digits: Some(3),
+            ..format
+        };
+        assert_eq!("0.500", format_price(&price("0.5"), 2, &significant));
+        assert_eq!("65761", format_price(&price("65761.23"), 2, &significant));
+    }
+
+    #[test]
+    fn test_format_price_change() {
+        assert_eq!("+12.34%", format_price_change(&price("12.34"), 2, None));
+        assert_eq!("-0.12%", format_price_change(&price("-0.12"), 2, None));
+        assert_eq!("+0.00%", format_price_change(&price("0"), 2, None));
+        assert_eq!("+0.00%", format_price_change(&price("-0.001"), 2, None));
+        assert_eq!("+1.50%", format_price_change(&price("1.5"), 2, None));
+    }
+
+    #[test]
+    fn test_format_price_locale() {
+        let format = PriceFormat {
+            thousands_separator: true,
+            locale: Some(Locale::try_from("de-DE".to_string()).unwrap()),
+            ..Default::default()
+        };
+        assert_eq!("65.761,23", format_price(&price("65761.2345"), 2, &format));
+        assert_eq!("-1.234.567", format_price(&price("-1234567"), 2, &format));
+        assert_eq!("0,0000712", format_price(&price("0.0000712"), 7, &format));
+
+        let compact = PriceFormat {
+            compact: true,
+            ..format.clone()
+        };
+        assert_eq!("65,76K", format_price(&price("65761.2345"), 2, &compact));
+        assert_eq!("0,0₅712", format_price(&price("0.00000712"), 8, &compact));
+
+        let format: PriceFormat =
+            serde_json::from_value(serde_json::json!({"thousands_separator": true, "locale": "fr_FR"})).unwrap();
+        assert_eq!("65\u{202f}761,23", format_price(&price("65761.2345"), 2, &format));
+
+        let format: PriceFormat =
+            serde_json::from_value(serde_json::json!({"thousands_separator": true, "locale": "de-CH"})).unwrap();
+        assert_eq!("65'761.23", format_price(&price("65761.2345"), 2, &format));
+
+        let locale = Locale::try_from("it".to_string()).unwrap();
+        assert_eq!("-0,12%", format_price_change(&price("-0.12"), 2, Some(locale)));
+        assert!(serde_json::from_value::<PriceFormat>(serde_json::json!({"locale": "xx-YY"})).is_err());
+    }
+
+    #[test]
+    fn test_truncate_nickname() {
+        assert_eq!("$65,761.23 USD", truncate_nickname("$65,761.23 USD"));
+        assert_eq!("a".repeat(32), truncate_nickname(&"a".repeat(32)));
+        assert_eq!(format!("{}…", "a".repeat(31)), truncate_nickname(&"a".repeat(33)));
+
+        // every emoji counts twice
+        let name = truncate_nickname(&"🟢".repeat(20));
+        assert_eq!(format!("{}…", "🟢".repeat(15)), name);
+        assert!(fits_nickname(&name));
+
+        let name = truncate_nickname(&format!("{}👩\u{200d}💻 x", "a".repeat(28)));
+        assert_eq!(format!("{}👩…", "a".repeat(28)), name);
+    }
+
+    #[test]
+    fn test_truncate_status() {
+        let status = "+1.50% | BTC";
+        assert!(fits_status(status));
+        assert_eq!(status, truncate_status(status));
+
+        let status = truncate_status(&"📈 ETH $3,412 ".repeat(12));
+        assert!(fits_status(&status));
+        assert!(status.starts_with("📈 ETH $3,412 📈"));
+        assert!(status.ends_with("$3,412…"), "{}", status);
+    }
+
+    #[test]
+    fn test_trend_symbols() {
+        let symbols = TrendSymbols::default();
+        assert_eq!("📈", symbols.symbol(&price("0")));
+        assert_eq!("📉", symbols.symbol(&price("-0.01")));
+
+        let symbols: TrendSymbols = serde_json::from_value(serde_json::json!({
+            "up": "🟢▲", "down": "🔴▼", "flat": "⏸", "flat_threshold": 0.1
+        }))
+        .unwrap();
+        assert_eq!("🟢▲", symbols.symbol(&price("1.5")));
+        assert_eq!("🔴▼", symbols.symbol(&price("-0.11")));
+        assert_eq!("⏸", symbols.symbol(&price("-0.1")));
+        assert_eq!("⏸", symbols.symbol(&price("0.05")));
+    }
+
+    #[test]
+    fn test_change_emoji() {
+        let change_emojis: Vec<ChangeEmoji> = serde_json::from_value(serde_json::json!([
+            {"above": 5, "emoji": "🚀"},
+            {"below": -5, "emoji": "🩸"},
+            {"above": 1, "emoji": "🟢"}
+        ]))
+        .unwrap();
+        assert_eq!("🚀", change_emoji(&change_emojis, &price("12.5")));
+        assert_eq!("🟢", change_emoji(&change_emojis, &price("5")));
+        assert_eq!("🩸", change_emoji(&change_emojis, &price("-5.01")));
+        assert_eq!("", change_emoji(&change_emojis, &price("-5")));
+        assert_eq!("", change_emoji(&change_emojis, &price("0.5")));
+        assert_eq!("", change_emoji(&[], &price("12.5")));
+    }
+
+    #[test]
+    fn test_format_price_change_decimals() {
+        let change = price("1.8841205093585678");
+        assert_eq!("+2%", format_price_change(&change, 0, None));
+        assert_eq!("+1.8841%", format_price_change(&change, 4, None));
+        assert_eq!("+1.8841205093585678%", format_price_change(&change, 16, None));
+        assert_eq!("-0.000012%", format_price_change(&price("-0.0000123"), 6, None));
+    }
+}
@@ -0,0 +1,160 @@
+use crate::config::QuoteSource;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Silent ticks after which a ticker counts as stalled
+pub const STALLED_AFTER_TICKS: u32 = 3;
+/// Added to the silence allowed to every ticker, covering slow fetches with retries and the restart backoff of
+/// a ticker that panicked
+pub const STALL_GRACE: Duration = Duration::from_secs(300);
+
+/// Tickers of which fetching the price or updating the bot failed, collected in the run-once mode
+pub type FailedTickers = Arc<Mutex<BTreeSet<String>>>;
+
+/// When every running ticker last went through its loop, to tell whether they all still make progress
+#[derive(Clone, Default)]
+pub struct Liveness {
+    tickers: Arc<Mutex<HashMap<String, TickerBeat>>>,
+}
+
+struct TickerBeat {
+    last_beat: Instant,
+    max_silence: Duration,
+}
+
+impl Liveness {
+    /// Records that `ticker` went through its loop, which ticks every `tick_duration`
+    pub fn beat(&self, ticker: &str, tick_duration: Duration) {
+        self.tickers.lock().unwrap().insert(
+            ticker.to_string(),
+            TickerBeat {
+                last_beat: Instant::now(),
+                max_silence: tick_duration * STALLED_AFTER_TICKS + STALL_GRACE,
+            },
+        );
+    }
+
+    /// Forgets `ticker`, e.g. after it was disabled on purpose
+    pub fn remove(&self, ticker: &str) {
+        self.tickers.lock().unwrap().remove(ticker);
+    }
+
+    /// Tickers which did not go through their loop for longer than allowed, sorted by name
+    pub fn stalled(&self) -> Vec<String> {
+        let now = Instant::now();
+        let mut stalled: Vec<String> = self
+            .tickers
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, beat)| now.duration_since(beat.last_beat) > beat.max_silence)
+            .map(|(ticker, _)| ticker.clone())
+            .collect();
+        stalled.sort();
+        stalled
+    }
+}
+
+/// What every running ticker last reported of fetching its price, shown by `/status`
+#[derive(Clone, Default)]
+pub struct TickerHealth {
+    tickers: Arc<Mutex<HashMap<String, FetchReport>>>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FetchReport {
+    pub last_fetch_at: Option<DateTime<Utc>>, // Last successful fetch
+    pub source: Option<QuoteSource>, // Provider of the last successful fetch
+    pub frequency: Option<u64>, // Seconds between fetches, `None` on a cron `schedule`
+    pub paused: bool,
+    pub error_streak: u32, // Failed fetches since the last successful one
+    pub last_error: Option<String>,
+}
+
+impl TickerHealth {
+    /// Records how often `ticker` fetches, including changes made through `/ticker`
+    pub fn record_frequency(&self, ticker: &str, frequency: Option<u64>, paused: bool) {
+        let mut tickers = self.tickers.lock().unwrap();
+        let report = tickers.entry(ticker.to_string()).or_default();
+        report.frequency = frequency;
+        report.paused = paused;
+    }
+
+    pub fn record_success(&self, ticker: &str, source: QuoteSource, fetched_at: DateTime<Utc>) {
+        let mut tickers = self.tickers.lock().unwrap();
+        let report = tickers.entry(ticker.to_string()).or_default();
+        report.last_fetch_at = Some(fetched_at);
+        report.source = Some(source);
+        report.error_streak = 0;
+        report.last_error = None;
+    }
+
+    pub fn record_failure(&self, ticker: &str, error: String) {
+        let mut tickers = self.tickers.lock().unwrap();
+        let report = tickers.entry(ticker.to_string()).or_default();
+        report.error_streak += 1;
+        report.last_error = Some(error);
+    }
+
+    /// Last report of `ticker`, `None` until it started
+    pub fn report(&self, ticker: &str) -> Option<FetchReport> {
+        self.tickers.lock().unwrap().get(ticker).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stalled() {
+        let liveness = Liveness::default();
+        liveness.beat("BTC", Duration::from_secs(60));
+        liveness.beat("ETH", Duration::from_secs(10));
+
+        tokio::time::advance(Duration::from_secs(3 * 10 + 300)).await;
+        assert!(liveness.stalled().is_empty());
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert_eq!(vec!["ETH".to_string()], liveness.stalled());
+
+        liveness.beat("ETH", Duration::from_secs(10));
+        assert!(liveness.stalled().is_empty());
+
+        tokio::time::advance(Duration::from_secs(3 * 60)).await;
+        assert_eq!(vec!["BTC".to_string()], liveness.stalled());
+
+        liveness.remove("BTC");
+        assert!(liveness.stalled().is_empty());
+    }
+
+    #[test]
+    fn test_ticker_health() {
+        let health = TickerHealth::default();
+        assert_eq!(None, health.report("BTC"));
+
+        health.record_frequency("BTC", Some(60), false);
+        health.record_failure("BTC", "timed out".to_string());
+        health.record_failure("BTC", "rate limited".to_string());
+        let report = health.report("BTC").unwrap();
+        assert_eq!(2, report.error_streak);
+        assert_eq!(Some("rate limited".to_string()), report.last_error);
+
+        let fetched_at = Utc::now();
+        health.record_success("BTC", QuoteSource::Binance, fetched_at);
+        assert_eq!(
+            FetchReport {
+                last_fetch_at: Some(fetched_at),
+                source: Some(QuoteSource::Binance),
+                frequency: Some(60),
+                paused: false,
+                error_streak: 0,
+                last_error: None,
+            },
+            health.report("BTC").unwrap()
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use bigdecimal::BigDecimal;
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Window of the `{high_24h}` and `{low_24h}` placeholders
+pub const HIGH_LOW_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Prices of a ticker over the last `max_age`, kept in memory only
+pub struct PriceHistory {
+    samples: VecDeque<(Instant, BigDecimal)>,
+    max_age: Duration,
+}
+
+impl PriceHistory {
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            samples: VecDeque::new(),
+            max_age,
+        }
+    }
+
+    pub fn record(&mut self, at: Instant, price: BigDecimal) {
+        self.samples.push_back((at, price));
+
+        while let Some((oldest, _)) = self.samples.front() {
+            if at.duration_since(*oldest) <= self.max_age {
+                break;
+            }
+            self.samples.pop_front();
+        }
+    }
+
+    /// Simple moving average of the prices recorded within `window` before `now`,
+    /// over the prices recorded so far if the history is shorter than `window`
+    pub fn sma(&self, window: Duration, now: Instant) -> Option<BigDecimal> {
+        let prices: Vec<&BigDecimal> = self
+            .samples
+            .iter()
+            .filter(|(at, _)| now.duration_since(*at) <= window)
+            .map(|(_, price)| price)
+            .collect();
+
+        if prices.is_empty() {
+            return None;
+        }
+
+        let count = BigDecimal::from(prices.len() as u64);
+        Some(prices.into_iter().sum::<BigDecimal>() / count)
+    }
+
+    /// Highest and lowest price recorded within `window` before `now`
+    pub fn high_low(&self, window: Duration, now: Instant) -> Option<(BigDecimal, BigDecimal)> {
+        let mut prices = self
+            .samples
+            .iter()
+            .filter(|(at, _)| now.duration_since(*at) <= window)
+            .map(|(_, price)| price);
+
+        let first = prices.next()?;
+        let (high, low) = prices.fold((first, first), |(high, low), price| (high.max(price), low.min(price)));
+        Some((high.clone(), low.clone()))
+    }
+}
+
+/// Parses a window like `30m`, `1h` or `7d`
+pub fn parse_window(window: &str) -> Option<Duration> {
+    let unit_secs = match window.chars().last()? {
+        's' => 1,
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        _ => return None,
+    };
+    let amount: u64 = window[..window.len() - 1].parse().ok()?;
+
+    if amount == 0 {
+        return None;
+    }
+
+    Some(Duration::from_secs(amount * unit_secs))
+}
+
+/// Windows of the `{sma_<window>}` and `{vs_sma_<window>}` placeholders in `template`, e.g. `24h`
+pub fn sma_windows(template: &str) -> Vec<(String, Duration)> {
+    let mut windows: Vec<(String, Duration)> = Vec::new();
+
+    for placeholder in template.split('{').skip(1).filter_map(|part| part.split_once('}')).map(|(name, _)| name) {
+        let Some(window) = placeholder.strip_prefix("vs_sma_").or_else(|| placeholder.strip_prefix("sma_")) else {
+            continue;
+        };
+
+        if let Some(duration) = parse_window(window) {
+            if !windows.iter().any(|(existing, _)| existing == window) {
+                windows.push((window.to_string(), duration));
+            }
+        }
+    }
+
+    windows
+}
+
+/// Whether `template` has a `{high_24h}` or `{low_24h}` placeholder
+pub fn uses_high_low(template: &str) -> bool {
+    template.contains("{high_24h}") || template.contains("{low_24h}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn price(value: &str) -> BigDecimal {
+        BigDecimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn test_parse_window() {
+        assert_eq!(Some(Duration::from_secs(30 * 60)), parse_window("30m"));
+        assert_eq!(Some(Duration::from_secs(60 * 60)), parse_window("1h"));
+        assert_eq!(Some(Duration::from_secs(7 * 24 * 60 * 60)), parse_window("7d"));
+        assert_eq!(None, parse_window("0h"));
+        assert_eq!(None, parse_window("h"));
+        assert_eq!(None, parse_window("1w"));
+        assert_eq!(None, parse_window(""));
+    }
+
+    #[test]
+    fn test_sma_windows() {
+        assert_eq!(
+            vec![
+                ("1h".to_string(), Duration::from_secs(60 * 60)),
+                ("24h".to_string(), Duration::from_secs(24 * 60 * 60)),
+            ],
+            sma_windows("{change} | {sma_1h} {vs_sma_24h} 24h MA | {sma_24h} {sma_x}")
+        );
+        assert!(sma_windows("{change} | {ticker}").is_empty());
+        assert!(uses_high_low("H {high_24h} L {low_24h} | {ticker}"));
+        assert!(!uses_high_low("{change} | {sma_24h}"));
+    }
+
+    #[test]
+    fn test_sma() {
+        let start = Instant::now();
+        let mut history = PriceHistory::new(Duration::from_secs(3600));
+        assert_eq!(None, history.sma(Duration::from_secs(60), start));
+
+        history.record(start, price("100"));
+        history.record(start + Duration::from_secs(1800), price("200"));
+        history.record(start + Duration::from_secs(3600), price("300"));
+
+        let now = start + Duration::from_secs(3600);
+        assert_eq!(price("200"), history.sma(Duration::from_secs(3600), now).unwrap());
+        assert_eq!(price("250"), history.sma(Duration::from_secs(1800), now).unwrap());
+
+        // prices older than `max_age` are dropped
+        history.record(start + Duration::from_secs(5400), price("400"));
+        assert_eq!(price("300"), history.sma(Duration::from_secs(7200), start + Duration::from_secs(5400)).unwrap());
+    }
+
+    #[test]
+    fn test_high_low() {
+        let start = Instant::now();
+        let mut history = PriceHistory::new(HIGH_LOW_WINDOW);
+        assert_eq!(None, history.high_low(HIGH_LOW_WINDOW, start));
+
+        history.record(start, price("63800"));
+        history.record(start + Duration::from_secs(60), price("66100"));
+        history.record(start + Duration::from_secs(120), price("65761"));
+        let now = start + Duration::from_secs(120);
+        assert_eq!(Some((price("66100"), price("63800"))), history.high_low(HIGH_LOW_WINDOW, now));
+        assert_eq!(Some((price("66100"), price("65761"))), history.high_low(Duration::from_secs(60), now));
+    }
+}
@@ -0,0 +1,28 @@
+pub mod alert;
+pub mod bot;
+pub mod bot_update;
+pub mod config;
+pub mod countdown;
+pub mod discord;
+pub mod format;
+pub mod health;
+pub mod history;
+pub mod http;
+pub mod market_hours;
+pub mod portfolio;
+pub mod price_alert;
+pub mod quote;
+pub mod ratio;
+pub mod reload;
+pub mod scheduler;
+pub mod secret;
+pub mod shutdown;
+pub mod sink;
+mod state_file;
+pub mod supervisor;
+#[cfg(feature = "systemd")]
+pub mod systemd;
+#[cfg(feature = "otel")]
+pub mod telemetry;
+mod ticker;
+pub mod ticker_control;
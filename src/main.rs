@@ -1,350 +1,152 @@
-use bigdecimal;
-use serde_json;
-use tracing_subscriber::fmt::format;
-use std::io::Result;
-use std::time;
-use tokio::fs;
+use discord_price_ticker::bot::{TickerBot, TickerBotError};
+use discord_price_ticker::config::{read_config, Config, ConfigFormat};
 use tokio::signal;
-use tokio::sync::mpsc;
-use tokio::sync::oneshot;
-use tokio::time::timeout;
-use tracing::debug;
-use tracing::trace;
-use tracing::warn;
-use bigdecimal::RoundingMode;
-use tracing::info;
 use tracing::error;
-use tracing::instrument;
-use tracing::Level;
-use tracing_subscriber;
-
-mod quote;
-mod discord;
-mod bot_update;
-mod config;
-
-use crate::quote::req_consumer::consume_crypto_price_requests;
-use crate::quote::request::AssetQuoteRequest;
-use crate::bot_update::BotUpdateInfo;
-use crate::discord::client::DiscordClient;
-use crate::config::{Config, TickerConfig};
-
-
-async fn read_config(file_path: &str) -> Result<Config> {
-    let config_string = fs::read_to_string(file_path).await?;
-    let config: Config = serde_json::from_str(config_string.as_str())?;
-    Ok(config)
-}
-
-#[instrument(skip_all, fields(ticker = ticker_config.ticker))]
-async fn run_periodic_crypto_fetch_job_loop(
-    ticker_config: TickerConfig,
-    mut stop_signal_recv: oneshot::Receiver<()>,
-    job_sender: mpsc::UnboundedSender<AssetQuoteRequest>,
-    bot_update_sender: mpsc::UnboundedSender<BotUpdateInfo>,
-    discord_client: DiscordClient,
-) {
-    const VS_CURRENCY: &str = "usd";
-    const VS_CURRENCY_SYMBOL_PREFIX: &str = "$";
-    const VS_CURRENCY_SYMBOL_SUFFIX: &str = "";
-
-    macro_rules! break_if_signaled {
-        ($stop_signal_recv:expr) => {
-            match $stop_signal_recv.try_recv() {
-                Ok(_) => {
-                    info!("Stop signal received for {}", ticker_config.ticker);
-                    let _ = stop_signal_recv.await;
-                    break;
-                }
-                Err(e) => {
-                    if e != oneshot::error::TryRecvError::Empty {
-                        warn!(
-                            "Error receiving stop signal for {}: {}",
-                            ticker_config.ticker, e
-                        );
-                        break;
-                    }
-                }
-            }
-        };
-    }
-
-    let tick_duration = time::Duration::from_secs(ticker_config.frequency);
-    let (get_price_chan_sender, mut get_price_chan_receiver) = mpsc::unbounded_channel();
-
-    loop {
-        break_if_signaled!(&mut stop_signal_recv);
-
-        debug!(
-            "Timer ticked for {}, fetching price...",
-            ticker_config.ticker
-        );
-
-        let id = &ticker_config.name;
-
-        let crypto_price_request = AssetQuoteRequest {
-            name: id.to_string(),
-            vs_currency: VS_CURRENCY.to_string(),
-            resp_sender: get_price_chan_sender.clone(),
-        };
-
-        if let Err(e) = job_sender.send(crypto_price_request) {
-            tracing::error!(
-                "cannot send crypto price request to channel for {}, stopping: {}",
-                id, e
-            );
-            break;
-        }
-
-        let get_price_chan_response = match get_price_chan_receiver.recv().await {
-            Some(r) => r,
-            None => {
-                warn!(
-                    "get crypto price response channel of '{}' is closed, will retry if possible",
-                    ticker_config.ticker
-                );
-                continue;
-            }
-        };
-
-        let get_price_response = match get_price_chan_response {
-            Ok(r) => r,
-            Err(error) => {
-                warn!(
-                    "Error getting price for {}: {}",
-                    ticker_config.ticker, error
-                );
-                tokio::time::sleep(tick_duration).await;
-                continue;
-            }
-        };
-
-        let price_usd = get_price_response.price_usd;
-        let price_change_24h = get_price_response.price_change_24h;
-
-        let formatted_price_usd = format_price(&price_usd, ticker_config.decimals);
-        let formatted_price_change_24h = format_price_change(price_change_24h);
-
-        debug!(
-            "Price for {} is {} USD (original value: {}), change in 24h is {}%",
-            ticker_config.ticker, formatted_price_usd, price_usd, formatted_price_change_24h
-        );
+use tracing::info;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::reload;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
 
-        break_if_signaled!(&mut stop_signal_recv);
+/// Exit code when some tasks had to be aborted because they did not stop within the shutdown timeout
+const EXIT_CODE_SHUTDOWN_TIMEOUT: i32 = 2;
+/// Exit code of `--once` when fetching the price or updating the bot failed for some tickers
+const EXIT_CODE_FAILED_TICKERS: i32 = 3;
 
-        let discord_bot_name = generate_discord_bot_name(formatted_price_usd.as_str(), VS_CURRENCY_SYMBOL_PREFIX, VS_CURRENCY_SYMBOL_SUFFIX);
-        let discord_bot_status = generate_discord_bot_status(formatted_price_change_24h.as_str(), ticker_config.ticker.as_str());
+#[tokio::main]
+async fn main() {
+    // debug until the config tells its `log_level`, which only filters the log: the OTLP export has its own level
+    let (level_filter, level_handle) = reload::Layer::new(LevelFilter::DEBUG);
+    let registry = tracing_subscriber::registry().with(tracing_subscriber::fmt::layer().with_filter(level_filter));
+
+    #[cfg(feature = "otel")]
+    let telemetry = discord_price_ticker::telemetry::Telemetry::from_env();
+    #[cfg(feature = "otel")]
+    let registry = registry.with(
+        telemetry
+            .as_ref()
+            .ok()
+            .and_then(Option::as_ref)
+            .map(|telemetry| telemetry.layer()),
+    );
+
+    registry.init();
 
-        debug!(
-            "Update Discord bot name for {}, set to {} ({})...",
-            ticker_config.ticker, discord_bot_name, discord_bot_status
-        );
+    info!("Hello, world!");
 
-        if let Err(e) = bot_update_sender.send(BotUpdateInfo {
-            name: discord_bot_name,
-            status: discord_bot_status,
-            discord_client: discord_client.clone(),
-        }) {
-            warn!("Failed to send bot update: {}", e);
+    #[cfg(feature = "otel")]
+    let telemetry = match telemetry {
+        Ok(Some(telemetry)) => {
+            info!("Exporting traces and metrics over OTLP");
+            Some(telemetry)
         }
-
-        break_if_signaled!(&mut stop_signal_recv);
-
-        if let Ok(_) = timeout(tick_duration, &mut stop_signal_recv).await {
-            info!(
-                "Received stop signal for {}, quit loop",
-                ticker_config.ticker
+        Ok(None) => None,
+        Err(error) => {
+            error!(
+                "Error setting up the OTLP export, continuing without it: {}",
+                error
             );
-            break;
+            None
         }
-    }
-}
-
-fn format_price(price: &bigdecimal::BigDecimal, decimals: u8) -> String {
-    if price.fractional_digit_count() > decimals as i64 {
-        return price
-           .with_scale_round(decimals.into(), RoundingMode::HalfEven)
-           .to_string();
-    } else {
-        return price.to_string();
-    }
-}
-
-fn format_price_change(price_change: f64) -> String {
-    // if price change > 0, add a plus sign
-    if price_change >= 0.0 {
-        return format!("+{:.*}%", 2, price_change)
-    }
-
-    format!("{:.*}%", 2, price_change)
-}
-
-fn generate_discord_bot_name(
-    formatted_price: &str,
-    vs_currency_symbol_prefix: &str,
-    vs_currency_symbol_suffix: &str,
-) -> String {
-    if vs_currency_symbol_suffix.is_empty() {
-        return format!("{}{}", vs_currency_symbol_prefix, formatted_price);
-    }
-
-    format!(
-        "{}{} {}",
-        vs_currency_symbol_prefix, formatted_price, vs_currency_symbol_suffix
-    )
-}
-
-fn generate_discord_bot_status(formatted_price_change: &str, ticker: &str) -> String {
-    format!("{}% | {}", formatted_price_change, ticker)
-}
+    };
 
-#[tokio::main]
-async fn main() {
-    tracing_subscriber::fmt()
-        .with_max_level(Level::DEBUG)
-        .init();
+    let args: Vec<String> = std::env::args().collect();
+    let validate_config_only = args.iter().any(|arg| arg == "--validate-config");
+    let once = args.iter().any(|arg| arg == "--once");
 
-    info!("Hello, world!");
+    // `--config <path>` names a config file or a directory of them, otherwise the first default file found wins,
+    // JSON if there is none so that the error names it
+    let config_path = match args.iter().position(|arg| arg == "--config") {
+        Some(index) => match args.get(index + 1) {
+            Some(path) => path.as_str(),
+            None => {
+                error!("Missing path after --config");
+                std::process::exit(1);
+            }
+        },
+        None => ConfigFormat::DEFAULT_FILE_PATHS
+            .into_iter()
+            .find(|path| std::path::Path::new(path).exists())
+            .unwrap_or(ConfigFormat::DEFAULT_FILE_PATHS[0]),
+    };
+    info!("Reading config from {}", config_path);
 
-    let config = match read_config("app_config.json").await {
+    let config = match read_config(config_path).await {
         Ok(config) => config,
         Err(error) => {
-            tracing::error!("Error reading config file: {}", error);
+            error!("Error reading config file: {}", error);
             return;
         }
     };
 
-    let (crypto_price_req_sender, crypto_price_req_receiver) = mpsc::unbounded_channel();
-    // let (stock_price_req_sender, stock_price_req_receiver) = mpsc::unbounded_channel();
-    let (bot_update_sender, mut bot_update_receiver) = mpsc::unbounded_channel();
-
-    let mut tasks = Vec::new();
-    let mut stop_signal_channels = Vec::new();
-
-    for ticker_config in config.tickers {
-        debug!(
-            "Loaded config for ticker: {}, is crypto? {}",
-            ticker_config.ticker, ticker_config.crypto
-        );
-
-        if !is_bot_token_valid(&ticker_config.discord_bot_token) {
-            error!(
-                "Invalid Discord bot token for ticker {}, skipping",
-                ticker_config.ticker
-            );
-            continue;
+    let set_log_level = move |config: &Config| {
+        let log_level = config.log_level();
+        if level_handle.clone_current() != Some(log_level) {
+            info!("Log level set to {}", log_level);
+            if let Err(error) = level_handle.reload(log_level) {
+                error!("Error setting the log level: {}", error);
+            }
         }
-
-        let (stop_signal_send, stop_signal_recv) = oneshot::channel();
-        let discord_client = DiscordClient::new(&ticker_config.discord_bot_token).await;
-        let ticker = ticker_config.ticker.to_string();
-
-        if ticker_config.crypto {
-            let crypto_price_req_sender_clone = crypto_price_req_sender.clone();
-            let bot_update_sender_clone = bot_update_sender.clone();
-
-            trace!("Spawning task for crypto ticker: {}", ticker);
-            tasks.push(tokio::spawn(async move {
-                run_periodic_crypto_fetch_job_loop(
-                    ticker_config,
-                    stop_signal_recv,
-                    crypto_price_req_sender_clone,
-                    bot_update_sender_clone,
-                    discord_client,
-                )
-                .await;
-            }));
-
-            trace!("Creating stop signal channel for ticker: {}", ticker);
-        } else {
-            // TODO: implement stock price fetching
-            // spawn_job(ticker_config, Arc::clone(&stop_flag), stock_price_req_sender.clone());
-            warn!(
-                "TBD: ticker {} is not an US stock, not implemented yet, skip",
-                ticker_config.ticker
-            )
+    };
+    set_log_level(&config);
+
+    let (reload_sender, reload_receiver) = tokio::sync::mpsc::unbounded_channel();
+    #[cfg(unix)]
+    tokio::spawn(discord_price_ticker::reload::reload_on_sighup(
+        config_path.to_string(),
+        reload_sender,
+        set_log_level,
+    ));
+    #[cfg(not(unix))]
+    drop((reload_sender, set_log_level));
+
+    let bot = match TickerBot::builder().config(config).reloads(reload_receiver).build() {
+        Ok(bot) => bot,
+        Err(error) => {
+            error!("{}", error);
+            std::process::exit(1);
         }
+    };
 
-        stop_signal_channels.push((ticker, stop_signal_send));
-    }
-
-    let coingecko_api_key = config.coingecko_api_key.to_string();
-    trace!("Starting crypto price request consumer...");
-    tokio::spawn(async move {
-        consume_crypto_price_requests(crypto_price_req_receiver, coingecko_api_key).await;
-    });
-
-    tokio::spawn(async move {
-        while let Some(update) = bot_update_receiver.recv().await {
-            update.discord_client.update_bot(update.name, update.status).await;
+    let ctrl_c = async {
+        if let Err(error) = signal::ctrl_c().await {
+            error!("Error listening for Ctrl+C: {}", error);
+            std::future::pending::<()>().await;
         }
-    });
-
-    // consume_stock_price_requests(stock_price_req_receiver, ...);
-
-    trace!("Starting signal handler...");
-    tokio::spawn(async move {
-        trace!("Waiting for Ctrl+C signal...");
-        signal::ctrl_c().await.expect("failed to listen for event");
+        info!("Ctrl+C pressed");
+    };
+    let result = if validate_config_only {
+        bot.validate().await
+    } else if once {
+        bot.run_once(ctrl_c).await
+    } else {
+        bot.run(ctrl_c).await
+    };
 
-        info!("Ctrl+C pressed. Stopping...");
-        for (ticker, stop_signal) in stop_signal_channels {
-            info!("Sending stop signal to receiver for ticker: {}", ticker);
-            if let Err(_) = stop_signal.send(()) {
-                warn!("Stop signal receiver for ticker {} is already dropped", ticker);
-            } else {
-                info!("Stop signal sent to receiver for ticker: {}", ticker);
+    let exit_code = match result {
+        Ok(()) if validate_config_only => {
+            info!("Config is valid");
+            0
+        }
+        Ok(()) => 0,
+        Err(error) => {
+            error!("{}", error);
+            match error {
+                TickerBotError::ShutdownTimeout(_) => EXIT_CODE_SHUTDOWN_TIMEOUT,
+                TickerBotError::FailedTickers(_) => EXIT_CODE_FAILED_TICKERS,
+                _ => 1,
             }
         }
-    });
-
-    info!("Waiting for all tasks to finish...");
-    for task in tasks {
-        let _ = task.await;
-    }
-
-    info!("All tasks finished.");
-}
-
-fn is_bot_token_valid(bot_token: &str) -> bool {
-    // just check if the token is empty
-    !bot_token.trim().is_empty() && bot_token.is_ascii()
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::str::FromStr;
-
-    #[test]
-    fn test_format_price() {
-        let price = bigdecimal::BigDecimal::from_str("123456789.123456789").unwrap();
-        assert_eq!("123456789", format_price(&price, 0));
-        assert_eq!("123456789.12", format_price(&price, 2));
-        assert_eq!("123456789.123456789", format_price(&price, 9));
-        assert_eq!("123456789.123456789", format_price(&price, 10));
-    }
-
-    #[test]
-    fn test_format_price_change() {
-        assert_eq!("+12.34%", format_price_change(12.34));
-        assert_eq!("-0.12%", format_price_change(-0.12));
-        assert_eq!("+0.00%", format_price_change(0.0));
-    }
+    };
 
-    #[test]
-    fn test_generate_discord_bot_name() {
-        assert_eq!("$1234.56", generate_discord_bot_name("1234.56", "$", ""));
-        assert_eq!("$1234.56   space  ", generate_discord_bot_name("1234.56", "$", "  space  "));
-        assert_eq!("$1234.56 USD", generate_discord_bot_name("1234.56", "$", "USD"));
-        assert_eq!("1234.56 USD", generate_discord_bot_name("1234.56", "", "USD"));
-        assert_eq!("1234.56", generate_discord_bot_name("1234.56", "", ""));
+    // `exit` skips destructors, the spans of the shutdown would be lost without flushing them first
+    #[cfg(feature = "otel")]
+    if let Some(telemetry) = telemetry {
+        telemetry.shutdown();
     }
 
-    #[test]
-    fn test_generate_discord_bot_status() {
-        assert_eq!("+12.34% | TICKER", generate_discord_bot_status("+12.34", "TICKER"));
-        assert_eq!("-0.12% | TICKER", generate_discord_bot_status("-0.12", "TICKER"));
-        assert_eq!("+0.00% | TICKER", generate_discord_bot_status("+0.00", "TICKER"));
+    if exit_code != 0 {
+        std::process::exit(exit_code);
     }
 }
@@ -0,0 +1,76 @@
+use chrono::{DateTime, Datelike, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::Deserialize;
+
+/// Trading hours of an exchange, in the local time of the exchange.
+/// Holidays are not taken into account.
+#[derive(Clone, Debug, Deserialize)]
+pub struct MarketHours {
+    pub timezone: Tz, // IANA timezone of the exchange, e.g. `America/New_York`
+    pub open: NaiveTime, // Local time the market opens, e.g. `09:30:00`
+    pub close: NaiveTime, // Local time the market closes, e.g. `16:00:00`
+    #[serde(default = "default_trading_days")]
+    pub days: Vec<Weekday>, // Days the market is open, defaults to Monday to Friday
+}
+
+fn default_trading_days() -> Vec<Weekday> {
+    vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri]
+}
+
+impl MarketHours {
+    pub fn is_open(&self, now: DateTime<Utc>) -> bool {
+        let local = now.with_timezone(&self.timezone);
+        if !self.days.contains(&local.weekday()) {
+            return false;
+        }
+
+        let time = local.time();
+        time >= self.open && time < self.close
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nyse() -> MarketHours {
+        serde_json::from_value(serde_json::json!({
+            "timezone": "America/New_York",
+            "open": "09:30:00",
+            "close": "16:00:00"
+        }))
+        .unwrap()
+    }
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_is_open() {
+        let market_hours = nyse();
+        // Wednesday, during daylight saving time (UTC-4)
+        assert!(!market_hours.is_open(utc("2024-07-10T13:29:00Z")));
+        assert!(market_hours.is_open(utc("2024-07-10T13:30:00Z")));
+        assert!(market_hours.is_open(utc("2024-07-10T19:59:00Z")));
+        assert!(!market_hours.is_open(utc("2024-07-10T20:00:00Z")));
+        // Wednesday, standard time (UTC-5)
+        assert!(!market_hours.is_open(utc("2024-01-10T14:29:00Z")));
+        assert!(market_hours.is_open(utc("2024-01-10T14:30:00Z")));
+        // Saturday
+        assert!(!market_hours.is_open(utc("2024-07-13T15:00:00Z")));
+    }
+
+    #[test]
+    fn test_days() {
+        let market_hours: MarketHours = serde_json::from_value(serde_json::json!({
+            "timezone": "Asia/Taipei",
+            "open": "09:00:00",
+            "close": "13:30:00",
+            "days": ["Sat"]
+        }))
+        .unwrap();
+        assert!(market_hours.is_open(utc("2024-07-13T02:00:00Z")));
+        assert!(!market_hours.is_open(utc("2024-07-10T02:00:00Z")));
+    }
+}
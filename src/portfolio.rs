@@ -0,0 +1,69 @@
+use crate::quote::response::AssetQuoteResponse;
+use bigdecimal::{BigDecimal, Zero};
+
+/// Value of the holdings and its change over 24h in percent, from the quote and quantity of every holding.
+/// The change is `None` if a holding lost its whole value, since its value 24h ago cannot be told from its quote.
+pub fn portfolio_value(holdings: &[(BigDecimal, AssetQuoteResponse)]) -> (BigDecimal, Option<BigDecimal>) {
+    let mut value = BigDecimal::zero();
+    let mut value_24h_ago = Some(BigDecimal::zero());
+
+    for (quantity, quote) in holdings {
+        let holding_value = quantity * &quote.price;
+
+        // the price 24h ago is derived from the change, it cannot be if the price dropped to zero
+        let change_factor = BigDecimal::from(100) + &quote.price_change_24h;
+        value_24h_ago = match value_24h_ago {
+            Some(value_24h_ago) if change_factor > BigDecimal::zero() => {
+                Some(value_24h_ago + &holding_value * BigDecimal::from(100) / change_factor)
+            }
+            _ => None,
+        };
+
+        value += holding_value;
+    }
+
+    let change = match value_24h_ago {
+        Some(value_24h_ago) if value_24h_ago.is_zero() => Some(BigDecimal::zero()),
+        Some(value_24h_ago) => Some((&value - &value_24h_ago) * BigDecimal::from(100) / value_24h_ago),
+        None => None,
+    };
+    (value, change)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn holding(quantity: &str, price: &str, price_change_24h: &str) -> (BigDecimal, AssetQuoteResponse) {
+        (
+            BigDecimal::from_str(quantity).unwrap(),
+            AssetQuoteResponse {
+                name: "coin".to_string(),
+                price: BigDecimal::from_str(price).unwrap(),
+                price_change_24h: BigDecimal::from_str(price_change_24h).unwrap(),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_portfolio_value() {
+        let (value, change) = portfolio_value(&[
+            holding("0.5", "60000", "20"),
+            holding("4", "2500", "0"),
+        ]);
+        assert_eq!(BigDecimal::from(40000), value);
+        // 24h ago: 0.5 * 50000 + 4 * 2500 = 35000
+        assert_eq!(BigDecimal::from_str("14.285714").unwrap(), change.unwrap().round(6));
+
+        // the holding that went to zero is not left out, which would show the others as the whole portfolio
+        let (value, change) = portfolio_value(&[holding("2", "0", "-100"), holding("4", "2500", "0")]);
+        assert_eq!(BigDecimal::from(10000), value);
+        assert_eq!(None, change);
+
+        let (value, change) = portfolio_value(&[]);
+        assert_eq!(BigDecimal::zero(), value);
+        assert_eq!(Some(BigDecimal::zero()), change);
+    }
+}
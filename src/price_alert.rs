@@ -0,0 +1,227 @@
+use crate::state_file::{load_json_state, save_json_state};
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tokio::sync::Mutex;
+
+/// A price a guild member asked to be notified of through `/alert`, removed once triggered
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PriceAlert {
+    pub id: u64,
+    pub user_id: u64,
+    pub channel_id: u64, // Channel the alert was created in, where the user is mentioned
+    pub ticker: String,
+    #[serde(default)]
+    pub vs_currency: String, // Currency of the ticker the price is in, empty for alerts saved before it was kept
+    pub direction: PriceDirection,
+    pub price: BigDecimal,
+    pub delivery: AlertDelivery,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceDirection {
+    Above, // Triggered once the price reaches the alert price or goes above it
+    Below, // Triggered once the price reaches the alert price or goes below it
+}
+
+impl PriceDirection {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PriceDirection::Above => "above",
+            PriceDirection::Below => "below",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertDelivery {
+    #[default]
+    Dm, // A direct message, falling back to a mention if the user does not accept them
+    Mention, // A message mentioning the user in the channel the alert was created in
+}
+
+impl PriceAlert {
+    pub fn is_triggered(&self, price: &BigDecimal) -> bool {
+        match self.direction {
+            PriceDirection::Above => price >= &self.price,
+            PriceDirection::Below => price <= &self.price,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct PriceAlertState {
+    next_id: u64,
+    alerts: Vec<PriceAlert>,
+}
+
+/// The price alerts of every user, saved to a JSON file on every change so that they survive restarts
+#[derive(Debug)]
+pub struct PriceAlertStore {
+    state_file: PathBuf,
+    max_alerts_per_user: usize,
+    state: Mutex<PriceAlertState>,
+}
+
+impl PriceAlertStore {
+    pub async fn load(state_file: PathBuf, max_alerts_per_user: usize) -> Self {
+        let state = load_json_state(&state_file, "price alerts").await;
+
+        Self {
+            state_file,
+            max_alerts_per_user,
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Adds an alert of `user_id`, failing if the user already has `max_alerts_per_user` of them
+    #[allow(clippy::too_many_arguments)]
+    pub async fn add(
+        &self,
+        user_id: u64,
+        channel_id: u64,
+        ticker: &str,
+        vs_currency: &str,
+        direction: PriceDirection,
+        price: BigDecimal,
+        delivery: AlertDelivery,
+    ) -> Result<PriceAlert, String> {
+        let mut state = self.state.lock().await;
+        let user_alerts = state.alerts.iter().filter(|alert| alert.user_id == user_id).count();
+        if user_alerts >= self.max_alerts_per_user {
+            return Err(format!(
+                "You already have {} alerts, remove one with `/alert remove` first",
+                user_alerts
+            ));
+        }
+
+        state.next_id += 1;
+        let alert = PriceAlert {
+            id: state.next_id,
+            user_id,
+            channel_id,
+            ticker: ticker.to_string(),
+            vs_currency: vs_currency.to_string(),
+            direction,
+            price,
+            delivery,
+        };
+        state.alerts.push(alert.clone());
+        self.save(&state).await;
+        Ok(alert)
+    }
+
+    /// Alerts of `user_id`, oldest first
+    pub async fn list(&self, user_id: u64) -> Vec<PriceAlert> {
+        let state = self.state.lock().await;
+        state.alerts.iter().filter(|alert| alert.user_id == user_id).cloned().collect()
+    }
+
+    /// Removes the alert `id` of `user_id`, returning whether there was one
+    pub async fn remove(&self, user_id: u64, id: u64) -> bool {
+        let mut state = self.state.lock().await;
+        let count = state.alerts.len();
+        state.alerts.retain(|alert| !(alert.user_id == user_id && alert.id == id));
+        if state.alerts.len() == count {
+            return false;
+        }
+
+        self.save(&state).await;
+        true
+    }
+
+    /// Removes and returns the alerts of `ticker` quoted in `vs_currency` triggered by `price`
+    pub async fn take_triggered(&self, ticker: &str, vs_currency: &str, price: &BigDecimal) -> Vec<PriceAlert> {
+        let mut state = self.state.lock().await;
+        let (triggered, remaining): (Vec<PriceAlert>, Vec<PriceAlert>) = std::mem::take(&mut state.alerts)
+            .into_iter()
+            .partition(|alert| {
+                alert.ticker == ticker
+                    && (alert.vs_currency.is_empty() || alert.vs_currency == vs_currency)
+                    && alert.is_triggered(price)
+            });
+        state.alerts = remaining;
+
+        if !triggered.is_empty() {
+            self.save(&state).await;
+        }
+        triggered
+    }
+
+    /// Puts back alerts taken by `take_triggered` whose notification failed, so that they are not lost but
+    /// triggered again by the next price reaching them
+    pub async fn restore(&self, alerts: Vec<PriceAlert>) {
+        if alerts.is_empty() {
+            return;
+        }
+
+        let mut state = self.state.lock().await;
+        state.alerts.extend(alerts);
+        state.alerts.sort_by_key(|alert| alert.id);
+        self.save(&state).await;
+    }
+
+    async fn save(&self, state: &PriceAlertState) {
+        save_json_state(&self.state_file, "price alerts", state).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_file::temp_state_file;
+    use std::str::FromStr;
+
+    fn price(value: &str) -> BigDecimal {
+        BigDecimal::from_str(value).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_price_alert_store() {
+        let state_file = temp_state_file("alerts");
+        let store = PriceAlertStore::load(state_file.clone(), 2).await;
+
+        let above = store
+            .add(1, 10, "BTC", "usd", PriceDirection::Above, price("70000"), AlertDelivery::Dm)
+            .await
+            .unwrap();
+        store
+            .add(1, 10, "BTC", "usd", PriceDirection::Below, price("60000"), AlertDelivery::Mention)
+            .await
+            .unwrap();
+        assert!(store
+            .add(1, 10, "ETH", "usd", PriceDirection::Above, price("4000"), AlertDelivery::Dm)
+            .await
+            .is_err());
+        store
+            .add(2, 10, "ETH", "usd", PriceDirection::Above, price("4000"), AlertDelivery::Dm)
+            .await
+            .unwrap();
+
+        assert!(store.take_triggered("BTC", "usd", &price("65000")).await.is_empty());
+        // the same ticker in another currency does not trigger it
+        assert!(store.take_triggered("BTC", "eur", &price("70000")).await.is_empty());
+        assert_eq!(vec![above], store.take_triggered("BTC", "usd", &price("70000")).await);
+        assert!(store.take_triggered("BTC", "usd", &price("70001")).await.is_empty());
+
+        // an alert which could not be delivered is triggered again
+        let below = store.take_triggered("BTC", "usd", &price("59000")).await;
+        assert_eq!(1, below.len());
+        store.restore(below.clone()).await;
+        assert_eq!(below, store.list(1).await);
+
+        // the alerts are kept across restarts
+        let store = PriceAlertStore::load(state_file.clone(), 2).await;
+        let alerts = store.list(1).await;
+        assert_eq!(1, alerts.len());
+        assert_eq!(PriceDirection::Below, alerts[0].direction);
+        assert!(!store.remove(2, alerts[0].id).await);
+        assert!(store.remove(1, alerts[0].id).await);
+        assert!(store.list(1).await.is_empty());
+        assert_eq!(1, store.list(2).await.len());
+
+        tokio::fs::remove_file(&state_file).await.unwrap();
+    }
+}
@@ -1,4 +1,31 @@
+pub mod alternative_me;
+pub mod api_keys;
+pub mod beaconchain;
+pub mod binance;
+pub mod binance_futures;
+pub mod chainlink;
+pub mod budget;
+pub mod cache;
+pub mod circuit_breaker;
+pub mod coin_list;
+pub mod coincap;
+pub mod coingecko;
+pub mod custom_http;
+pub mod defillama;
 pub mod error;
+pub mod etag;
+pub mod etherscan;
+pub mod exec;
+pub mod geckoterminal;
+#[cfg(test)]
+pub mod mock;
+pub mod opensea;
+pub mod pacing;
+pub mod provider;
+pub mod rate_limit;
 pub mod req_consumer;
 pub mod request;
+pub mod request_queue;
 pub mod response;
+pub mod twse;
+pub mod yahoo;
@@ -0,0 +1,146 @@
+use crate::quote::error::QuoteRequestError;
+use crate::quote::provider::QuoteProvider;
+use crate::quote::response::AssetQuoteResponse;
+use async_trait::async_trait;
+use bigdecimal::{BigDecimal, Zero};
+use reqwest::header;
+use serde::Deserialize;
+use std::str::FromStr;
+
+pub const DEFAULT_BASE_URL: &str = "https://api.alternative.me";
+
+/// Minimum interval between two updates of a ticker, the index is only updated once a day
+pub const MIN_UPDATE_INTERVAL_SECS: u64 = 60;
+
+pub fn fear_and_greed_url(base_url: &str) -> String {
+    format!("{}/fng/?limit=2", base_url)
+}
+
+/// The crypto Fear & Greed index of alternative.me, the id of the asset is ignored.
+/// The price of the quote is the index value, its change is the one since the previous day.
+pub struct FearAndGreedProvider {
+    http_client: reqwest::Client,
+    base_url: String,
+}
+
+impl FearAndGreedProvider {
+    pub fn new(http_client: reqwest::Client, base_url: String) -> Self {
+        Self {
+            http_client,
+            base_url,
+        }
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for FearAndGreedProvider {
+    fn name(&self) -> &str {
+        "alternative.me"
+    }
+
+    async fn fetch_quote(
+        &self,
+        id: &str,
+        _vs_currency: &str,
+    ) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        let body = self
+            .http_client
+            .get(fear_and_greed_url(&self.base_url))
+            .header(header::ACCEPT, "application/json")
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        parse_fear_and_greed_response(&body, id)
+    }
+}
+
+/// Response of the `/fng/` endpoint, latest value first.
+///
+/// Example: `{"data":[{"value":"72","value_classification":"Greed","timestamp":"1721347200"}]}`
+#[derive(Debug, Deserialize)]
+struct FearAndGreedResponse {
+    #[serde(default)]
+    data: Vec<FearAndGreedEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FearAndGreedEntry {
+    value: String,
+    value_classification: String,
+}
+
+pub fn parse_fear_and_greed_response(body: &str, id: &str) -> Result<AssetQuoteResponse, QuoteRequestError> {
+    let response: FearAndGreedResponse = serde_json::from_str(body)?;
+    let mut entries = response.data.into_iter();
+
+    let latest = entries
+        .next()
+        .ok_or_else(|| QuoteRequestError::MissingAsset(id.to_string()))?;
+    let value = BigDecimal::from_str(&latest.value)?;
+
+    let price_change_24h = match entries.next() {
+        Some(previous) => {
+            let previous = BigDecimal::from_str(&previous.value)?;
+            if previous.is_zero() {
+                BigDecimal::zero()
+            } else {
+                (&value - &previous) * BigDecimal::from(100) / previous
+            }
+        }
+        None => BigDecimal::zero(),
+    };
+
+    Ok(AssetQuoteResponse {
+        name: id.to_string(),
+        price: value,
+        price_change_24h,
+        label: Some(latest.value_classification),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fear_and_greed_url() {
+        assert_eq!("https://api.alternative.me/fng/?limit=2", fear_and_greed_url(DEFAULT_BASE_URL));
+    }
+
+    #[test]
+    fn test_parse_fear_and_greed_response() {
+        let body = r#"{"name":"Fear and Greed Index","data":[
+            {"value":"80","value_classification":"Extreme Greed","timestamp":"1721347200","time_until_update":"3600"},
+            {"value":"64","value_classification":"Greed","timestamp":"1721260800"}
+        ],"metadata":{"error":null}}"#;
+        let response = parse_fear_and_greed_response(body, "fear-and-greed").unwrap();
+        assert_eq!("80", response.price.to_string());
+        assert_eq!(BigDecimal::from(25), response.price_change_24h);
+        assert_eq!(Some("Extreme Greed".to_string()), response.label);
+
+        let body = r#"{"data":[{"value":"20","value_classification":"Extreme Fear"}]}"#;
+        let response = parse_fear_and_greed_response(body, "fear-and-greed").unwrap();
+        assert!(response.price_change_24h.is_zero());
+    }
+
+    #[test]
+    fn test_parse_fear_and_greed_response_malformed() {
+        let cases = [
+            ("", "JSON Parse Error"),
+            (r#"{"data":[],"metadata":{"error":"limit"}}"#, "Missing Asset Error"),
+            (r#"{"data":[{"value":"x","value_classification":"Greed"}]}"#, "BigDecimal Parse Error"),
+        ];
+
+        for (body, expected) in cases {
+            let err = parse_fear_and_greed_response(body, "fear-and-greed").unwrap_err();
+            assert!(
+                err.to_string().starts_with(expected),
+                "body {:?}: expected {:?}, got {:?}",
+                body, expected, err.to_string()
+            );
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use crate::config::ApiKeyRotation;
+use crate::quote::error::QuoteRequestError;
+use crate::secret::SecretString;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+/// How long a rate limited key is left out of the rotation
+pub const RATE_LIMITED_BENCH: Duration = Duration::from_secs(60);
+/// How long a rejected key is left out of the rotation, e.g. a revoked one or one out of credits
+pub const REJECTED_BENCH: Duration = Duration::from_secs(60 * 60);
+
+/// How a key of the pool fared so far
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KeyHealth {
+    pub uses: u64,
+    pub failures: u32, // Rate limited or rejected responses in a row
+    pub benched_until: Option<Instant>, // Left out of the rotation until then
+}
+
+struct KeysState {
+    next: usize, // Where the round-robin goes on
+    health: Vec<KeyHealth>,
+}
+
+/// The API keys of a provider, e.g. several CoinGecko demo keys, picked for every request by `rotation`. A key
+/// answered with 429 or 401/403 is benched for `RATE_LIMITED_BENCH` or `REJECTED_BENCH`, the requests going to the
+/// other keys meanwhile. Once every key is benched, the one back the soonest is used anyway.
+pub struct ApiKeys {
+    provider: String,
+    keys: Vec<SecretString>,
+    rotation: ApiKeyRotation,
+    state: Mutex<KeysState>,
+}
+
+impl ApiKeys {
+    /// Pool of the non-empty `keys`, requests are sent without a key if there is none
+    pub fn new(provider: &str, keys: Vec<SecretString>, rotation: ApiKeyRotation) -> Self {
+        let keys: Vec<SecretString> = keys.into_iter().filter(|key| !key.is_empty()).collect();
+        Self {
+            provider: provider.to_string(),
+            state: Mutex::new(KeysState {
+                next: 0,
+                health: vec![KeyHealth::default(); keys.len()],
+            }),
+            keys,
+            rotation,
+        }
+    }
+
+    /// Pool of a single key, or of none if it is empty
+    pub fn single(provider: &str, key: String) -> Self {
+        Self::new(provider, vec![SecretString::from(key)], ApiKeyRotation::default())
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn health(&self) -> Vec<KeyHealth> {
+        self.state.lock().unwrap().health.clone()
+    }
+
+    /// Sends `request` with the key picked for it, `None` without keys, and records how the key fared
+    pub async fn with_key<T, F, Fut>(&self, request: F) -> Result<T, QuoteRequestError>
+    where
+        F: FnOnce(Option<SecretString>) -> Fut,
+        Fut: Future<Output = Result<T, QuoteRequestError>>,
+    {
+        let picked = self.pick(Instant::now());
+        let result = request(picked.map(|index| self.keys[index].clone())).await;
+        if let Some(index) = picked {
+            self.record(index, &result, Instant::now());
+        }
+        result
+    }
+
+    fn pick(&self, now: Instant) -> Option<usize> {
+        if self.keys.is_empty() {
+            return None;
+        }
+        let mut state = self.state.lock().unwrap();
+        let count = self.keys.len();
+        let start = match self.rotation {
+            ApiKeyRotation::RoundRobin => state.next,
+            ApiKeyRotation::Failover => 0,
+        };
+
+        let available = (0..count)
+            .map(|offset| (start + offset) % count)
+            .find(|index| state.health[*index].benched_until.is_none_or(|until| until <= now));
+        let index = available.unwrap_or_else(|| {
+            (0..count)
+                .min_by_key(|index| state.health[*index].benched_until)
+                .unwrap_or_default()
+        });
+
+        state.next = (index + 1) % count;
+        state.health[index].uses += 1;
+        Some(index)
+    }
+
+    fn record<T>(&self, index: usize, result: &Result<T, QuoteRequestError>, now: Instant) {
+        let mut state = self.state.lock().unwrap();
+        let health = &mut state.health[index];
+        let bench = match result {
+            Err(QuoteRequestError::RateLimited(_)) => RATE_LIMITED_BENCH,
+            Err(QuoteRequestError::Unauthorized(_)) => REJECTED_BENCH,
+            Ok(_) => {
+                if health.failures > 0 {
+                    info!("{} API key #{} works again", self.provider, index + 1);
+                }
+                health.failures = 0;
+                health.benched_until = None;
+                return;
+            }
+            Err(_) => return,
+        };
+
+        health.failures += 1;
+        health.benched_until = Some(now + bench);
+        warn!(
+            "{} API key #{} of {} failed {} times in a row ({}), leaving it out for {:?}",
+            self.provider,
+            index + 1,
+            self.keys.len(),
+            health.failures,
+            result.as_ref().err().map(|e| e.to_string()).unwrap_or_default(),
+            bench
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(rotation: ApiKeyRotation) -> ApiKeys {
+        ApiKeys::new("Test", vec!["a".into(), "".into(), "b".into(), "c".into()], rotation)
+    }
+
+    fn rate_limited() -> Result<(), QuoteRequestError> {
+        Err(QuoteRequestError::RateLimited("429".to_string()))
+    }
+
+    #[test]
+    fn test_round_robin() {
+        let now = Instant::now();
+        let keys = keys(ApiKeyRotation::RoundRobin);
+        assert_eq!(vec![Some(0), Some(1), Some(2), Some(0)], (0..4).map(|_| keys.pick(now)).collect::<Vec<_>>());
+
+        keys.record(1, &rate_limited(), now);
+        assert_eq!(vec![Some(2), Some(0), Some(2)], (0..3).map(|_| keys.pick(now)).collect::<Vec<_>>());
+        // back after the bench
+        let later = now + RATE_LIMITED_BENCH;
+        assert_eq!(vec![Some(0), Some(1)], (0..2).map(|_| keys.pick(later)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_failover() {
+        let now = Instant::now();
+        let keys = keys(ApiKeyRotation::Failover);
+        assert_eq!(Some(0), keys.pick(now));
+        assert_eq!(Some(0), keys.pick(now));
+
+        keys.record(0, &Err::<(), _>(QuoteRequestError::Unauthorized("401".to_string())), now);
+        assert_eq!(Some(1), keys.pick(now));
+        keys.record(1, &rate_limited(), now);
+        keys.record(2, &rate_limited(), now + Duration::from_secs(1));
+        // all benched, the one back the soonest
+        assert_eq!(Some(1), keys.pick(now));
+
+        keys.record(1, &Ok(()), now);
+        assert_eq!(Some(1), keys.pick(now));
+        let health = keys.health();
+        assert_eq!(1, health[0].failures);
+        assert_eq!(0, health[1].failures);
+        assert_eq!(3, health[1].uses);
+    }
+
+    #[tokio::test]
+    async fn test_with_key() {
+        let keys = ApiKeys::single("Test", String::new());
+        assert_eq!(None, keys.with_key(|key| async { Ok::<_, QuoteRequestError>(key) }).await.unwrap());
+
+        let keys = ApiKeys::single("Test", "key".to_string());
+        let key = keys.with_key(|key| async { Ok::<_, QuoteRequestError>(key) }).await.unwrap();
+        assert_eq!(Some(SecretString::from("key")), key);
+    }
+}
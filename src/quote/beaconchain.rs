@@ -0,0 +1,133 @@
+use crate::quote::error::QuoteRequestError;
+use crate::quote::provider::QuoteProvider;
+use crate::quote::response::AssetQuoteResponse;
+use async_trait::async_trait;
+use bigdecimal::{BigDecimal, Zero};
+use reqwest::header;
+use serde::Deserialize;
+
+pub const DEFAULT_BASE_URL: &str = "https://beaconcha.in";
+
+/// Minimum interval between two updates of a ticker, ETH.STORE is computed once a day
+pub const MIN_UPDATE_INTERVAL_SECS: u64 = 60 * 60;
+
+pub fn ethstore_url(base_url: &str, day: Option<u64>) -> String {
+    match day {
+        Some(day) => format!("{}/api/v1/ethstore/{}", base_url, day),
+        None => format!("{}/api/v1/ethstore/latest", base_url),
+    }
+}
+
+/// Staking APR of Ethereum from beaconcha.in, the ETH.STORE rate, the reward a validator earned over the last
+/// day on its consensus and execution layers, the id of the asset is ignored. The price of the quote is the APR
+/// in percent, its change the relative one since the day before.
+pub struct BeaconChainProvider {
+    http_client: reqwest::Client,
+    base_url: String,
+}
+
+impl BeaconChainProvider {
+    pub fn new(http_client: reqwest::Client, base_url: String) -> Self {
+        Self {
+            http_client,
+            base_url,
+        }
+    }
+
+    async fn fetch_ethstore(&self, day: Option<u64>) -> Result<EthStoreDay, QuoteRequestError> {
+        let body = self
+            .http_client
+            .get(ethstore_url(&self.base_url, day))
+            .header(header::ACCEPT, "application/json")
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        parse_ethstore_response(&body)
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for BeaconChainProvider {
+    fn name(&self) -> &str {
+        "beaconcha.in"
+    }
+
+    async fn fetch_quote(
+        &self,
+        id: &str,
+        _vs_currency: &str,
+    ) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        let latest = self.fetch_ethstore(None).await?;
+        let previous = match latest.day.checked_sub(1) {
+            Some(day) => Some(self.fetch_ethstore(Some(day)).await?),
+            None => None,
+        };
+        Ok(apr_quote(&latest, previous.as_ref(), id))
+    }
+}
+
+/// Response of the `/api/v1/ethstore/{day}` endpoint, only the fields in use are listed. The APR is a fraction.
+///
+/// Example: `{"status":"OK","data":{"day":1012,"apr":0.0331,"cl_apr":0.0287,"el_apr":0.0044}}`
+#[derive(Debug, Deserialize)]
+struct EthStoreResponse {
+    data: Option<EthStoreDay>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EthStoreDay {
+    day: u64,
+    apr: BigDecimal,
+}
+
+pub fn parse_ethstore_response(body: &str) -> Result<EthStoreDay, QuoteRequestError> {
+    let response: EthStoreResponse = serde_json::from_str(body)?;
+    response
+        .data
+        .ok_or_else(|| QuoteRequestError::MissingField("data".to_string()))
+}
+
+/// The APR of `latest` in percent, and its change since `previous`
+pub fn apr_quote(latest: &EthStoreDay, previous: Option<&EthStoreDay>, id: &str) -> AssetQuoteResponse {
+    let hundred = BigDecimal::from(100);
+    let price_change_24h = match previous {
+        Some(previous) if !previous.apr.is_zero() => (&latest.apr - &previous.apr) * &hundred / &previous.apr,
+        _ => BigDecimal::zero(),
+    };
+
+    AssetQuoteResponse {
+        name: id.to_string(),
+        price: &latest.apr * hundred,
+        price_change_24h,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ethstore_url() {
+        assert_eq!("https://beaconcha.in/api/v1/ethstore/latest", ethstore_url(DEFAULT_BASE_URL, None));
+        assert_eq!("https://beaconcha.in/api/v1/ethstore/1011", ethstore_url(DEFAULT_BASE_URL, Some(1011)));
+    }
+
+    #[test]
+    fn test_apr_quote() {
+        let latest = parse_ethstore_response(r#"{"status":"OK","data":{"day":1012,"apr":0.0330,"cl_apr":0.0287,"el_apr":0.0043}}"#).unwrap();
+        let previous = parse_ethstore_response(r#"{"status":"OK","data":{"day":1011,"apr":0.0300}}"#).unwrap();
+
+        let response = apr_quote(&latest, Some(&previous), "ETH APR");
+        assert_eq!(BigDecimal::from(33) / BigDecimal::from(10), response.price);
+        assert_eq!(BigDecimal::from(10), response.price_change_24h);
+        assert!(apr_quote(&latest, None, "ETH APR").price_change_24h.is_zero());
+
+        assert!(matches!(
+            parse_ethstore_response(r#"{"status":"ERROR: invalid day","data":null}"#),
+            Err(QuoteRequestError::MissingField(_))
+        ));
+    }
+}
@@ -0,0 +1,166 @@
+use crate::quote::error::QuoteRequestError;
+use crate::quote::provider::QuoteProvider;
+use crate::quote::response::{AssetQuoteResponse, DayRange};
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use reqwest::header;
+use serde::Deserialize;
+use std::str::FromStr;
+
+pub const DEFAULT_BASE_URL: &str = "https://api.binance.com";
+
+/// Minimum interval between two updates of a ticker, the request weight limit of Binance is generous
+pub const MIN_UPDATE_INTERVAL_SECS: u64 = 2;
+
+/// Trading pair of `symbol` quoted in `vs_currency`, USD prices come from the USDT pairs
+pub fn trading_pair(symbol: &str, vs_currency: &str) -> String {
+    let quote_asset = match vs_currency.to_lowercase().as_str() {
+        "usd" => "USDT".to_string(),
+        other => other.to_uppercase(),
+    };
+    format!("{}{}", symbol.to_uppercase(), quote_asset)
+}
+
+pub fn ticker_24hr_url(base_url: &str, symbol: &str, vs_currency: &str) -> String {
+    format!(
+        "{}/api/v3/ticker/24hr?symbol={}",
+        base_url,
+        trading_pair(symbol, vs_currency)
+    )
+}
+
+/// Quotes from the spot market of Binance, assets are identified by their symbol (e.g. `BTC`)
+pub struct BinanceProvider {
+    http_client: reqwest::Client,
+    base_url: String,
+}
+
+impl BinanceProvider {
+    pub fn new(http_client: reqwest::Client, base_url: String) -> Self {
+        Self {
+            http_client,
+            base_url,
+        }
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for BinanceProvider {
+    fn name(&self) -> &str {
+        "Binance"
+    }
+
+    async fn fetch_quote(
+        &self,
+        id: &str,
+        vs_currency: &str,
+    ) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        let body = self
+            .http_client
+            .get(ticker_24hr_url(&self.base_url, id, vs_currency))
+            .header(header::ACCEPT, "application/json")
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        parse_ticker_24hr_response(&body, id)
+    }
+}
+
+/// Response of the `/api/v3/ticker/24hr` endpoint, only the fields in use are listed.
+///
+/// Example: `{"symbol":"BTCUSDT","priceChangePercent":"1.884","lastPrice":"65761.12000000","highPrice":"66100.00000000","lowPrice":"63800.00000000"}`
+///
+/// The price is deserialized straight into `BigDecimal`, from a string or a number, so that no precision is lost.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Ticker24hrResponse {
+    last_price: Option<BigDecimal>,
+    price_change_percent: Option<String>,
+    high_price: Option<BigDecimal>,
+    low_price: Option<BigDecimal>,
+}
+
+pub fn parse_ticker_24hr_response(body: &str, symbol: &str) -> Result<AssetQuoteResponse, QuoteRequestError> {
+    let response: Ticker24hrResponse = serde_json::from_str(body)?;
+
+    let price = response
+        .last_price
+        .ok_or_else(|| QuoteRequestError::MissingField("lastPrice".to_string()))?;
+    let price_change_24h = response
+        .price_change_percent
+        .as_deref()
+        .ok_or_else(|| QuoteRequestError::MissingField("priceChangePercent".to_string()))
+        .and_then(|value| BigDecimal::from_str(value).map_err(|_| QuoteRequestError::InvalidField("priceChangePercent".to_string())))?;
+
+    Ok(AssetQuoteResponse {
+        name: symbol.to_string(),
+        price,
+        price_change_24h,
+        day_range: response
+            .high_price
+            .zip(response.low_price)
+            .map(|(high, low)| DayRange { high, low }),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ticker_24hr_url() {
+        assert_eq!(
+            "https://api.binance.com/api/v3/ticker/24hr?symbol=BTCUSDT",
+            ticker_24hr_url(DEFAULT_BASE_URL, "btc", "usd")
+        );
+        assert_eq!(
+            "http://localhost:8080/api/v3/ticker/24hr?symbol=ETHEUR",
+            ticker_24hr_url("http://localhost:8080", "ETH", "eur")
+        );
+    }
+
+    #[test]
+    fn test_parse_ticker_24hr_response() {
+        let body = r#"{"symbol":"BTCUSDT","priceChange":"1215.10","priceChangePercent":"1.884","lastPrice":"65761.12000000"}"#;
+        let response = parse_ticker_24hr_response(body, "BTC").unwrap();
+        assert_eq!("BTC", response.name);
+        assert_eq!("65761.12000000", response.price.to_string());
+        assert_eq!("1.884", response.price_change_24h.to_string());
+        assert!(response.day_range.is_none());
+
+        let body = r#"{"priceChangePercent":"1.884","lastPrice":"65761.12","highPrice":"66100.00","lowPrice":"63800.00"}"#;
+        let day_range = parse_ticker_24hr_response(body, "BTC").unwrap().day_range.unwrap();
+        assert_eq!("66100.00", day_range.high.to_string());
+        assert_eq!("63800.00", day_range.low.to_string());
+    }
+
+    #[test]
+    fn test_parse_ticker_24hr_response_number_price() {
+        let body = r#"{"priceChangePercent":"1.884","lastPrice":0.000012345678901234567890}"#;
+        let response = parse_ticker_24hr_response(body, "SHIB").unwrap();
+        assert_eq!("0.000012345678901234567890", response.price.to_string());
+    }
+
+    #[test]
+    fn test_parse_ticker_24hr_response_malformed() {
+        let cases = [
+            ("", "JSON Parse Error"),
+            (r#"{"code":-1121,"msg":"Invalid symbol."}"#, "Missing Field Error: `lastPrice`"),
+            (r#"{"lastPrice":"abc","priceChangePercent":"1.0"}"#, "JSON Parse Error"),
+            (r#"{"lastPrice":"1.0"}"#, "Missing Field Error: `priceChangePercent`"),
+            (r#"{"lastPrice":"1.0","priceChangePercent":"x"}"#, "Invalid Field Error: `priceChangePercent`"),
+        ];
+
+        for (body, expected) in cases {
+            let err = parse_ticker_24hr_response(body, "BTC").unwrap_err();
+            assert!(
+                err.to_string().starts_with(expected),
+                "body {:?}: expected {:?}, got {:?}",
+                body, expected, err.to_string()
+            );
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use crate::quote::binance::trading_pair;
+use crate::quote::error::QuoteRequestError;
+use crate::quote::provider::QuoteProvider;
+use crate::quote::response::AssetQuoteResponse;
+use async_trait::async_trait;
+use bigdecimal::{BigDecimal, Zero};
+use reqwest::header;
+use serde::Deserialize;
+use std::str::FromStr;
+
+pub const DEFAULT_BASE_URL: &str = "https://fapi.binance.com";
+
+/// Minimum interval between two updates of a ticker, funding rates change every few hours
+pub const MIN_UPDATE_INTERVAL_SECS: u64 = 60;
+
+pub fn premium_index_url(base_url: &str, symbol: &str) -> String {
+    format!("{}/fapi/v1/premiumIndex?symbol={}", base_url, trading_pair(symbol, "usd"))
+}
+
+pub fn open_interest_url(base_url: &str, symbol: &str) -> String {
+    format!("{}/fapi/v1/openInterest?symbol={}", base_url, trading_pair(symbol, "usd"))
+}
+
+/// Whether `template` has a `{funding}` or `{open_interest}` placeholder
+pub fn uses_derivatives(template: &str) -> bool {
+    template.contains("{funding}") || template.contains("{open_interest}")
+}
+
+/// Funding rate and open interest of the USDT-margined perpetual contract of an asset
+#[derive(Clone, Debug)]
+pub struct DerivativesInfo {
+    pub funding_rate_percent: f64, // Funding rate of the current period, in percent
+    pub open_interest: BigDecimal, // Open contracts, in units of the asset
+}
+
+/// Public market data of the USDT-margined futures of Binance, assets are identified by symbol (e.g. `BTC`).
+/// The price of the quote is the mark price of the perpetual contract in USD, without a change, and the funding
+/// rate and open interest are in `derivatives`.
+pub struct BinanceFuturesProvider {
+    http_client: reqwest::Client,
+    base_url: String,
+}
+
+impl BinanceFuturesProvider {
+    pub fn new(http_client: reqwest::Client, base_url: String) -> Self {
+        Self {
+            http_client,
+            base_url,
+        }
+    }
+
+    async fn get(&self, url: String) -> Result<String, QuoteRequestError> {
+        Ok(self
+            .http_client
+            .get(url)
+            .header(header::ACCEPT, "application/json")
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?)
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for BinanceFuturesProvider {
+    fn name(&self) -> &str {
+        "Binance Futures"
+    }
+
+    async fn fetch_quote(
+        &self,
+        id: &str,
+        _vs_currency: &str,
+    ) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        let premium_index = self.get(premium_index_url(&self.base_url, id)).await?;
+        let open_interest = self.get(open_interest_url(&self.base_url, id)).await?;
+        parse_derivatives_quote(&premium_index, &open_interest, id)
+    }
+}
+
+/// Example: `{"symbol":"BTCUSDT","markPrice":"65761.10","lastFundingRate":"0.00010000"}`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PremiumIndexResponse {
+    mark_price: BigDecimal,
+    last_funding_rate: String,
+}
+
+/// Example: `{"symbol":"BTCUSDT","openInterest":"10659.509","time":1721332800000}`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenInterestResponse {
+    open_interest: String,
+}
+
+pub fn parse_derivatives_quote(
+    premium_index_body: &str,
+    open_interest_body: &str,
+    id: &str,
+) -> Result<AssetQuoteResponse, QuoteRequestError> {
+    let premium_index: PremiumIndexResponse = serde_json::from_str(premium_index_body)?;
+    let open_interest: OpenInterestResponse = serde_json::from_str(open_interest_body)?;
+
+    let funding_rate_percent = premium_index
+        .last_funding_rate
+        .parse::<f64>()
+        .map_err(|_| QuoteRequestError::InvalidField("lastFundingRate".to_string()))?
+        * 100.0;
+
+    Ok(AssetQuoteResponse {
+        name: id.to_string(),
+        price: premium_index.mark_price,
+        price_change_24h: BigDecimal::zero(),
+        derivatives: Some(DerivativesInfo {
+            funding_rate_percent,
+            open_interest: BigDecimal::from_str(&open_interest.open_interest)?,
+        }),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_urls() {
+        assert_eq!(
+            "https://fapi.binance.com/fapi/v1/premiumIndex?symbol=BTCUSDT",
+            premium_index_url(DEFAULT_BASE_URL, "BTC")
+        );
+        assert_eq!(
+            "https://fapi.binance.com/fapi/v1/openInterest?symbol=ETHUSDT",
+            open_interest_url(DEFAULT_BASE_URL, "eth")
+        );
+    }
+
+    #[test]
+    fn test_parse_derivatives_quote() {
+        let quote = parse_derivatives_quote(
+            r#"{"symbol":"BTCUSDT","markPrice":"65761.10","lastFundingRate":"0.00010000","nextFundingTime":1721347200000}"#,
+            r#"{"symbol":"BTCUSDT","openInterest":"10659.509","time":1721332800000}"#,
+            "BTC",
+        )
+        .unwrap();
+        assert_eq!("65761.10", quote.price.to_string());
+        let info = quote.derivatives.unwrap();
+        assert!((info.funding_rate_percent - 0.01).abs() < 1e-12);
+        assert_eq!("10659.509", info.open_interest.to_string());
+
+        assert!(parse_derivatives_quote(r#"{"code":-1121,"msg":"Invalid symbol."}"#, "{}", "BTC").is_err());
+        assert!(parse_derivatives_quote(
+            r#"{"markPrice":"1","lastFundingRate":"x"}"#,
+            r#"{"openInterest":"1"}"#,
+            "BTC"
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,332 @@
+use crate::config::QuoteSource;
+use crate::quote::error::QuoteRequestError;
+use crate::quote::pacing::{Pacing, MAX_STRETCH};
+use crate::quote::provider::QuoteProvider;
+use crate::quote::rate_limit::RateLimitQuota;
+use crate::quote::response::AssetQuoteResponse;
+use crate::state_file::{load_json_state, save_json_state};
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+/// How often the usage of a provider is projected to the end of the month
+pub const BUDGET_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// How often the counts are saved, a restart loses the calls made since
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Calls made to a provider in a calendar month, in UTC
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct MonthlyUsage {
+    pub month: String, // e.g. `2026-10`
+    pub calls: u64,
+    pub since: DateTime<Utc>, // When counting started, the first call of the month
+}
+
+impl MonthlyUsage {
+    fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            month: month_of(now),
+            calls: 0,
+            since: now,
+        }
+    }
+
+    /// Calls by the end of the month at the rate since `since`, over an hour at least so that the first calls of
+    /// the month do not project a burst
+    pub fn projected(&self, now: DateTime<Utc>) -> u64 {
+        let elapsed = (now - self.since).num_seconds().max(BUDGET_CHECK_INTERVAL.as_secs() as i64);
+        let remaining = (end_of_month(now) - now).num_seconds().max(0);
+        self.calls + (self.calls as i128 * remaining as i128 / elapsed as i128) as u64
+    }
+}
+
+/// Usage of a provider against its monthly budget, for `/status`
+#[derive(Clone, Debug, PartialEq)]
+pub struct BudgetReport {
+    pub calls: u64,
+    pub budget: u64,
+    pub projected: u64, // Calls by the end of the month at the rate so far
+}
+
+/// Calls made to every provider with a budget this month, saved to a JSON file so that they survive restarts
+pub struct ApiUsage {
+    state_file: PathBuf,
+    budgets: BTreeMap<QuoteSource, u64>,
+    usage: Mutex<BTreeMap<QuoteSource, MonthlyUsage>>,
+    saved_at: Mutex<Option<Instant>>,
+}
+
+impl ApiUsage {
+    pub async fn load(state_file: PathBuf, budgets: BTreeMap<QuoteSource, u64>) -> Self {
+        let usage = load_json_state(&state_file, "API usage").await;
+
+        Self {
+            state_file,
+            budgets,
+            usage: Mutex::new(usage),
+            saved_at: Mutex::new(None),
+        }
+    }
+
+    pub fn budget(&self, source: QuoteSource) -> Option<u64> {
+        self.budgets.get(&source).copied()
+    }
+
+    /// Counts a call to `source`, starting over in a new month, and returns its usage this month
+    pub async fn record_call(&self, source: QuoteSource, now: DateTime<Utc>) -> MonthlyUsage {
+        let usage = {
+            let mut usage = self.usage.lock().unwrap();
+            let monthly_usage = usage.entry(source).or_insert_with(|| MonthlyUsage::new(now));
+            if monthly_usage.month != month_of(now) {
+                *monthly_usage = MonthlyUsage::new(now);
+            }
+            monthly_usage.calls += 1;
+            monthly_usage.clone()
+        };
+
+        let save_due = {
+            let mut saved_at = self.saved_at.lock().unwrap();
+            let due = saved_at.is_none_or(|saved_at| saved_at.elapsed() >= SAVE_INTERVAL);
+            if due {
+                *saved_at = Some(Instant::now());
+            }
+            due
+        };
+        if save_due {
+            self.save().await;
+        }
+        usage
+    }
+
+    /// Usage of `source` this month, `None` without a budget
+    pub fn report(&self, source: QuoteSource, now: DateTime<Utc>) -> Option<BudgetReport> {
+        let budget = self.budget(source)?;
+        let usage = self.usage.lock().unwrap();
+        let monthly_usage = usage.get(&source).filter(|usage| usage.month == month_of(now));
+        Some(BudgetReport {
+            calls: monthly_usage.map_or(0, |usage| usage.calls),
+            budget,
+            projected: monthly_usage.map_or(0, |usage| usage.projected(now)),
+        })
+    }
+
+    /// Saves the counts, e.g. before exiting
+    pub async fn save(&self) {
+        let usage = self.usage.lock().unwrap().clone();
+        save_json_state(&self.state_file, "API usage", &usage).await;
+    }
+}
+
+struct BudgetCheck {
+    checked_at: Instant,
+    calls: Option<u64>, // Calls of the month at `checked_at`, from the first call on
+    warned_month: Option<String>,
+}
+
+/// Counts the calls to `provider` against its monthly budget in `ApiUsage`. Every `BUDGET_CHECK_INTERVAL` the
+/// usage is projected to the end of the month, with a warning if it exceeds the budget. With `pacing`, the
+/// intervals of the tickers of the provider are also stretched so that the rest of the budget lasts the month.
+pub struct BudgetedProvider {
+    provider: Arc<dyn QuoteProvider>,
+    source: QuoteSource,
+    budget: u64,
+    usage: Arc<ApiUsage>,
+    pacing: Option<Pacing>,
+    check: Mutex<BudgetCheck>,
+}
+
+impl BudgetedProvider {
+    pub fn new(
+        provider: Arc<dyn QuoteProvider>,
+        source: QuoteSource,
+        budget: u64,
+        usage: Arc<ApiUsage>,
+        pacing: Option<Pacing>,
+    ) -> Self {
+        Self {
+            provider,
+            source,
+            budget,
+            usage,
+            pacing,
+            check: Mutex::new(BudgetCheck {
+                checked_at: Instant::now(),
+                calls: None,
+                warned_month: None,
+            }),
+        }
+    }
+
+    fn check_budget(&self, usage: &MonthlyUsage, now: DateTime<Utc>) {
+        let mut check = self.check.lock().unwrap();
+        // the calls of the month before a restart are not part of the first window
+        let checked_calls = *check.calls.get_or_insert(usage.calls.saturating_sub(1));
+        let elapsed = check.checked_at.elapsed();
+        if elapsed < BUDGET_CHECK_INTERVAL {
+            return;
+        }
+        // fewer calls than at the last check after a new month started
+        let window_calls = usage.calls.saturating_sub(checked_calls);
+        check.checked_at = Instant::now();
+        check.calls = Some(usage.calls);
+
+        let remaining = self.budget.saturating_sub(usage.calls);
+        let projected = usage.projected(now);
+        info!(
+            gauge.api_budget_remaining = remaining,
+            gauge.api_budget_projected = projected,
+            provider = self.provider.name(),
+            "{} API: {} of {} calls made this month, {} projected",
+            self.provider.name(),
+            usage.calls,
+            self.budget,
+            projected
+        );
+        if projected > self.budget && check.warned_month.as_ref() != Some(&usage.month) {
+            warn!(
+                "{} API is projected to make {} calls this month, over its budget of {}",
+                self.provider.name(),
+                projected,
+                self.budget
+            );
+            check.warned_month = Some(usage.month.clone());
+        }
+
+        let Some(pacing) = &self.pacing else {
+            return;
+        };
+        // the calls were made at the current stretch, the tickers would make this many without it
+        let demand = window_calls as f64 / elapsed.as_secs_f64() * pacing.stretch();
+        let remaining_secs = (end_of_month(now) - now).num_seconds().max(0) as u64;
+        let stretch = budget_stretch(demand, remaining, remaining_secs);
+        if pacing.set_budget_stretch(stretch) {
+            info!(
+                histogram.interval_stretch = stretch,
+                "Stretching the interval of the tickers of {} API {:.2}x to stay within its budget",
+                self.provider.name(),
+                stretch
+            );
+        }
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for BudgetedProvider {
+    fn name(&self) -> &str {
+        self.provider.name()
+    }
+
+    fn take_rate_limit_quota(&self) -> Option<RateLimitQuota> {
+        self.provider.take_rate_limit_quota()
+    }
+
+    async fn fetch_quote(
+        &self,
+        id: &str,
+        vs_currency: &str,
+    ) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        let result = self.provider.fetch_quote(id, vs_currency).await;
+        let now = Utc::now();
+        let usage = self.usage.record_call(self.source, now).await;
+        self.check_budget(&usage, now);
+        result
+    }
+}
+
+/// Stretch of the intervals for `demand` calls per second to spend no more than `remaining` calls in
+/// `remaining_secs`, from 1 up to `MAX_STRETCH`
+fn budget_stretch(demand: f64, remaining: u64, remaining_secs: u64) -> f64 {
+    if remaining_secs == 0 {
+        return 1.0;
+    }
+    let allowed = remaining as f64 / remaining_secs as f64;
+    if allowed <= 0.0 {
+        return MAX_STRETCH;
+    }
+    (demand / allowed).clamp(1.0, MAX_STRETCH)
+}
+
+fn month_of(now: DateTime<Utc>) -> String {
+    now.format("%Y-%m").to_string()
+}
+
+fn end_of_month(now: DateTime<Utc>) -> DateTime<Utc> {
+    let (year, month) = match now.month() {
+        12 => (now.year() + 1, 1),
+        month => (now.year(), month + 1),
+    };
+    Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_file::temp_state_file;
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        timestamp.parse().unwrap()
+    }
+
+    #[test]
+    fn test_projected() {
+        let usage = MonthlyUsage {
+            month: "2026-04".to_string(),
+            calls: 1000,
+            since: at("2026-04-01T00:00:00Z"),
+        };
+        // 1000 calls in 3 days, 27 days to go
+        assert_eq!(10_000, usage.projected(at("2026-04-04T00:00:00Z")));
+        assert_eq!(1000, usage.projected(at("2026-04-30T23:59:59Z")));
+
+        // counted over an hour at least
+        let usage = MonthlyUsage {
+            since: at("2026-04-30T23:00:00Z"),
+            ..usage
+        };
+        assert_eq!(1500, usage.projected(at("2026-04-30T23:30:00Z")));
+    }
+
+    #[test]
+    fn test_budget_stretch() {
+        // 1 call per second allowed
+        assert_eq!(1.0, budget_stretch(0.5, 3600, 3600));
+        assert_eq!(2.0, budget_stretch(2.0, 3600, 3600));
+        assert_eq!(MAX_STRETCH, budget_stretch(100.0, 3600, 3600));
+        assert_eq!(MAX_STRETCH, budget_stretch(0.5, 0, 3600));
+        assert_eq!(1.0, budget_stretch(2.0, 0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_api_usage() {
+        let state_file = temp_state_file("api-usage");
+        let budgets = BTreeMap::from([(QuoteSource::CoinGecko, 10_000)]);
+        let usage = ApiUsage::load(state_file.clone(), budgets.clone()).await;
+
+        usage.record_call(QuoteSource::CoinGecko, at("2026-03-31T23:00:00Z")).await;
+        let monthly_usage = usage.record_call(QuoteSource::CoinGecko, at("2026-04-01T00:00:00Z")).await;
+        assert_eq!(1, monthly_usage.calls, "starts over in a new month");
+        usage.record_call(QuoteSource::CoinGecko, at("2026-04-01T00:00:00Z")).await;
+        usage.save().await;
+
+        // the counts are kept across restarts
+        let usage = ApiUsage::load(state_file.clone(), budgets).await;
+        assert_eq!(
+            Some(BudgetReport {
+                calls: 2,
+                budget: 10_000,
+                projected: 2 + 2 * 29,
+            }),
+            usage.report(QuoteSource::CoinGecko, at("2026-04-02T00:00:00Z"))
+        );
+        assert_eq!(0, usage.report(QuoteSource::CoinGecko, at("2026-05-01T00:00:00Z")).unwrap().calls);
+        assert_eq!(None, usage.report(QuoteSource::Binance, at("2026-04-02T00:00:00Z")));
+
+        tokio::fs::remove_file(&state_file).await.unwrap();
+    }
+}
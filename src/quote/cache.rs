@@ -0,0 +1,113 @@
+use crate::quote::error::QuoteRequestError;
+use crate::quote::provider::QuoteProvider;
+use crate::quote::rate_limit::RateLimitQuota;
+use crate::quote::response::AssetQuoteResponse;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+type CacheEntry = Arc<tokio::sync::Mutex<Option<(Instant, AssetQuoteResponse)>>>;
+
+/// Serves repeated requests for the same asset and currency from memory for `ttl`,
+/// e.g. when several tickers show the same coin. Concurrent requests for the same
+/// asset wait for the first one instead of hitting `provider` as well.
+pub struct CachedProvider {
+    provider: Arc<dyn QuoteProvider>,
+    ttl: Duration,
+    entries: Mutex<HashMap<(String, String), CacheEntry>>,
+}
+
+impl CachedProvider {
+    pub fn new(provider: Arc<dyn QuoteProvider>, ttl: Duration) -> Self {
+        Self {
+            provider,
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn entry(&self, id: &str, vs_currency: &str) -> CacheEntry {
+        self.entries
+            .lock()
+            .unwrap()
+            .entry((id.to_string(), vs_currency.to_lowercase()))
+            .or_default()
+            .clone()
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for CachedProvider {
+    fn name(&self) -> &str {
+        self.provider.name()
+    }
+
+    fn take_rate_limit_quota(&self) -> Option<RateLimitQuota> {
+        self.provider.take_rate_limit_quota()
+    }
+
+    async fn fetch_quote(
+        &self,
+        id: &str,
+        vs_currency: &str,
+    ) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        let entry = self.entry(id, vs_currency);
+        let mut entry = entry.lock().await;
+
+        if let Some((fetched_at, response)) = entry.as_ref() {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(response.clone());
+            }
+        }
+
+        let response = self.provider.fetch_quote(id, vs_currency).await?;
+        *entry = Some((Instant::now(), response.clone()));
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quote::mock::MockProvider;
+    use bigdecimal::BigDecimal;
+    use std::str::FromStr;
+
+    fn quote(price: &str) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        Ok(AssetQuoteResponse {
+            name: "bitcoin".to_string(),
+            price: BigDecimal::from_str(price).unwrap(),
+            price_change_24h: BigDecimal::from_str("1.5").unwrap(),
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cached_within_ttl() {
+        let mock = Arc::new(MockProvider::new(vec![quote("1"), quote("2"), quote("3")]));
+        let provider = CachedProvider::new(mock.clone(), Duration::from_secs(5));
+
+        assert_eq!("1", provider.fetch_quote("bitcoin", "usd").await.unwrap().price.to_string());
+        assert_eq!("1", provider.fetch_quote("bitcoin", "USD").await.unwrap().price.to_string());
+        assert_eq!(1, mock.calls());
+
+        // a different currency is a different quote
+        assert_eq!("2", provider.fetch_quote("bitcoin", "eur").await.unwrap().price.to_string());
+
+        tokio::time::advance(Duration::from_secs(5)).await;
+        assert_eq!("3", provider.fetch_quote("bitcoin", "usd").await.unwrap().price.to_string());
+        assert_eq!(3, mock.calls());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_errors_not_cached() {
+        let mock = Arc::new(MockProvider::new(vec![Err("first".into()), quote("1")]));
+        let provider = CachedProvider::new(mock.clone(), Duration::from_secs(5));
+
+        assert!(provider.fetch_quote("bitcoin", "usd").await.is_err());
+        assert_eq!("1", provider.fetch_quote("bitcoin", "usd").await.unwrap().price.to_string());
+        assert_eq!(2, mock.calls());
+    }
+}
@@ -0,0 +1,230 @@
+use crate::quote::error::QuoteRequestError;
+use crate::quote::provider::QuoteProvider;
+use crate::quote::response::AssetQuoteResponse;
+use crate::secret::SecretString;
+use async_trait::async_trait;
+use bigdecimal::num_bigint::BigInt;
+use bigdecimal::{BigDecimal, Zero};
+use reqwest::header;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+pub const DEFAULT_RPC_URL: &str = "https://ethereum-rpc.publicnode.com";
+
+/// Minimum interval between two updates of a ticker, about the block time of Ethereum
+pub const MIN_UPDATE_INTERVAL_SECS: u64 = 12;
+
+/// Selector of `latestRoundData()` of a Chainlink aggregator
+const LATEST_ROUND_DATA: &str = "0xfeaf968c";
+/// Selector of `decimals()` of a Chainlink aggregator
+const DECIMALS: &str = "0x313ce567";
+/// Length of an ABI-encoded word
+const WORD_LEN: usize = 32;
+
+/// Oracle prices of Chainlink aggregators, e.g. ETH / USD at `0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419` on
+/// Ethereum, read from the chain over JSON-RPC. The id of the asset is the address of the aggregator. The feeds
+/// have no change over 24h, it is always `0`.
+pub struct ChainlinkProvider {
+    http_client: reqwest::Client,
+    rpc_url: SecretString,
+    decimals: Mutex<HashMap<String, i64>>, // Decimals of the answer of every aggregator asked so far, they never change
+}
+
+impl ChainlinkProvider {
+    pub fn new(http_client: reqwest::Client, rpc_url: SecretString) -> Self {
+        Self {
+            http_client,
+            rpc_url,
+            decimals: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Calls the read-only function `data` of the contract at `to` in the latest block
+    async fn eth_call(&self, to: &str, data: &str) -> Result<Vec<u8>, QuoteRequestError> {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_call",
+            "params": [{"to": to, "data": data}, "latest"],
+        });
+        let body = self
+            .http_client
+            .post(self.rpc_url.expose())
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(request.to_string())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            // the URL of Infura or Alchemy holds the API key
+            .map_err(reqwest::Error::without_url)?
+            .text()
+            .await
+            .map_err(reqwest::Error::without_url)?;
+        parse_rpc_response(&body)
+    }
+
+    async fn feed_decimals(&self, address: &str) -> Result<i64, QuoteRequestError> {
+        if let Some(decimals) = self.decimals.lock().unwrap().get(address) {
+            return Ok(*decimals);
+        }
+
+        let output = self.eth_call(address, DECIMALS).await?;
+        let decimals = word(&output, 0)
+            .and_then(|word| i64::try_from(BigInt::from_signed_bytes_be(word)).ok())
+            .ok_or_else(|| QuoteRequestError::InvalidField("decimals".to_string()))?;
+        self.decimals.lock().unwrap().insert(address.to_string(), decimals);
+        Ok(decimals)
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for ChainlinkProvider {
+    fn name(&self) -> &str {
+        "Chainlink"
+    }
+
+    async fn fetch_quote(
+        &self,
+        id: &str,
+        _vs_currency: &str,
+    ) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        if !is_address(id) {
+            return Err(QuoteRequestError::NotFound(format!("{} is not the address of an aggregator", id)));
+        }
+        let address = id.to_lowercase();
+
+        let decimals = self.feed_decimals(&address).await?;
+        let output = self.eth_call(&address, LATEST_ROUND_DATA).await?;
+        Ok(AssetQuoteResponse {
+            name: id.to_string(),
+            price: parse_latest_answer(&output, decimals)?,
+            price_change_24h: BigDecimal::zero(),
+            ..Default::default()
+        })
+    }
+}
+
+/// Whether `id` is an EVM address, `0x` and 40 hex digits
+pub fn is_address(id: &str) -> bool {
+    id.strip_prefix("0x")
+        .is_some_and(|hex| hex.len() == 40 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Response of `eth_call`, the output of the function in hex or an error, e.g. for a reverted call.
+///
+/// Example: `{"jsonrpc":"2.0","id":1,"result":"0x0000000000000000000000000000000000000000000000000000000000000008"}`
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    result: Option<String>,
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+pub fn parse_rpc_response(body: &str) -> Result<Vec<u8>, QuoteRequestError> {
+    let response: RpcResponse = serde_json::from_str(body)?;
+    if let Some(error) = response.error {
+        return Err(QuoteRequestError::Other(format!("JSON-RPC error {}: {}", error.code, error.message)));
+    }
+
+    let result = response
+        .result
+        .ok_or_else(|| QuoteRequestError::MissingField("result".to_string()))?;
+    decode_hex(&result).ok_or_else(|| QuoteRequestError::InvalidField("result".to_string()))
+}
+
+/// The `answer` of the output of `latestRoundData()`, the second of its five words, scaled by the `decimals` of
+/// the feed
+pub fn parse_latest_answer(output: &[u8], decimals: i64) -> Result<BigDecimal, QuoteRequestError> {
+    // a contract without this function, or an address without a contract, returns nothing
+    let answer = word(output, 1).ok_or_else(|| QuoteRequestError::MissingField("answer".to_string()))?;
+    Ok(BigDecimal::new(BigInt::from_signed_bytes_be(answer), decimals))
+}
+
+fn word(output: &[u8], index: usize) -> Option<&[u8]> {
+    output.get(index * WORD_LEN..(index + 1) * WORD_LEN)
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Output of `latestRoundData()` of the ETH / USD feed, answering 3,412.56789 with 8 decimals
+    const LATEST_ROUND_DATA_OUTPUT: &str = concat!(
+        "0x",
+        "0000000000000000000000000000000000000000000000060000000000001234",
+        "0000000000000000000000000000000000000000000000000000004f747d5c08",
+        "0000000000000000000000000000000000000000000000000000000066a0e4a0",
+        "0000000000000000000000000000000000000000000000000000000066a0e4a0",
+        "0000000000000000000000000000000000000000000000060000000000001234"
+    );
+
+    #[test]
+    fn test_is_address() {
+        assert!(is_address("0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419"));
+        assert!(!is_address("5f4eC3Df9cbd43714FE2740f5E3616155c5b8419"));
+        assert!(!is_address("0x5f4eC3Df9cbd43714FE2740f5E3616155c5b841"));
+        assert!(!is_address("0x5f4eC3Df9cbd43714FE2740f5E3616155c5b841g"));
+        assert!(!is_address("ethereum"));
+    }
+
+    #[test]
+    fn test_parse_latest_answer() {
+        let body = format!(r#"{{"jsonrpc":"2.0","id":1,"result":"{}"}}"#, LATEST_ROUND_DATA_OUTPUT);
+        let output = parse_rpc_response(&body).unwrap();
+        assert_eq!("3412.56789", parse_latest_answer(&output, 8).unwrap().normalized().to_string());
+
+        // a negative answer, e.g. of a feed of a rate
+        let mut negative = vec![0; WORD_LEN];
+        negative.extend([0xff; WORD_LEN - 1]);
+        negative.push(0x9c);
+        assert_eq!("-1.00", parse_latest_answer(&negative, 2).unwrap().to_string());
+
+        let no_contract = parse_rpc_response(r#"{"jsonrpc":"2.0","id":1,"result":"0x"}"#).unwrap();
+        assert!(matches!(parse_latest_answer(&no_contract, 8), Err(QuoteRequestError::MissingField(_))));
+    }
+
+    #[tokio::test]
+    async fn test_rpc_url_is_not_in_errors() {
+        // nothing listens on port 1, so the request fails to connect
+        let provider = ChainlinkProvider::new(
+            reqwest::Client::new(),
+            SecretString::from("http://127.0.0.1:1/v3/0123456789abcdef"),
+        );
+        let error = provider
+            .fetch_quote("0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419", "usd")
+            .await
+            .unwrap_err();
+        let message = crate::alert::error_chain(&error);
+        assert!(!message.contains("0123456789abcdef"), "{}", message);
+    }
+
+    #[test]
+    fn test_parse_rpc_response() {
+        assert_eq!(vec![0x12, 0xab], parse_rpc_response(r#"{"jsonrpc":"2.0","id":1,"result":"0x12ab"}"#).unwrap());
+        assert!(matches!(
+            parse_rpc_response(r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"execution reverted"}}"#),
+            Err(QuoteRequestError::Other(message)) if message == "JSON-RPC error -32000: execution reverted"
+        ));
+        assert!(matches!(
+            parse_rpc_response(r#"{"jsonrpc":"2.0","id":1,"result":"0x12a"}"#),
+            Err(QuoteRequestError::InvalidField(_))
+        ));
+    }
+}
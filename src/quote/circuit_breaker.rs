@@ -0,0 +1,190 @@
+use crate::quote::error::QuoteRequestError;
+use crate::quote::provider::QuoteProvider;
+use crate::quote::rate_limit::RateLimitQuota;
+use crate::quote::response::AssetQuoteResponse;
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+/// Longest the circuit stays open, however many trials failed in a row
+pub const MAX_COOLDOWN: Duration = Duration::from_secs(30 * 60);
+
+/// Stops asking `provider` during an outage: after `failure_threshold` failed requests in a row the circuit
+/// opens and every request fails at once for `cooldown`. The first request after that is a trial; if it fails
+/// as well the circuit opens again for twice as long, up to `MAX_COOLDOWN`, if it succeeds the circuit closes.
+///
+/// Only errors telling that the provider is unreachable or overloaded count, i.e. failed HTTP requests,
+/// timeouts, rate limiting and server errors, not an asset missing from a response.
+pub struct CircuitBreakerProvider {
+    provider: Arc<dyn QuoteProvider>,
+    failure_threshold: u32,
+    cooldown: Duration,
+    state: Mutex<CircuitState>,
+}
+
+#[derive(Default)]
+struct CircuitState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    cooldown: Option<Duration>, // Cooldown of the last opening, doubled if the trial fails
+}
+
+impl CircuitBreakerProvider {
+    pub fn new(provider: Arc<dyn QuoteProvider>, failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            provider,
+            failure_threshold,
+            cooldown,
+            state: Mutex::new(CircuitState::default()),
+        }
+    }
+
+    fn record_success(&self) {
+        let mut state = self.state.lock().unwrap();
+        if state.cooldown.is_some() {
+            info!("{} API is back, closing its circuit", self.provider.name());
+        }
+        *state = CircuitState::default();
+    }
+
+    fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.consecutive_failures += 1;
+
+        let cooldown = match state.cooldown {
+            // the trial after a cooldown failed
+            Some(cooldown) => (cooldown * 2).min(MAX_COOLDOWN),
+            None if state.consecutive_failures >= self.failure_threshold => self.cooldown,
+            None => return,
+        };
+
+        warn!(
+            "{} API failed {} times in a row, not asking it for {:?}",
+            self.provider.name(),
+            state.consecutive_failures,
+            cooldown
+        );
+        state.open_until = Some(Instant::now() + cooldown);
+        state.cooldown = Some(cooldown);
+    }
+}
+
+fn is_outage(error: &QuoteRequestError) -> bool {
+    matches!(
+        error,
+        QuoteRequestError::HttpRequest(_)
+            | QuoteRequestError::Timeout(_)
+            | QuoteRequestError::RateLimited(_)
+            | QuoteRequestError::ServerError(_)
+    )
+}
+
+#[async_trait]
+impl QuoteProvider for CircuitBreakerProvider {
+    fn name(&self) -> &str {
+        self.provider.name()
+    }
+
+    fn take_rate_limit_quota(&self) -> Option<RateLimitQuota> {
+        self.provider.take_rate_limit_quota()
+    }
+
+    async fn fetch_quote(
+        &self,
+        id: &str,
+        vs_currency: &str,
+    ) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        if let Some(open_until) = self.state.lock().unwrap().open_until {
+            let now = Instant::now();
+            if now < open_until {
+                return Err(QuoteRequestError::CircuitOpen(open_until - now));
+            }
+        }
+
+        match self.provider.fetch_quote(id, vs_currency).await {
+            Ok(response) => {
+                self.record_success();
+                Ok(response)
+            }
+            Err(e) => {
+                if is_outage(&e) {
+                    self.record_failure();
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quote::mock::MockProvider;
+    use bigdecimal::BigDecimal;
+    use std::str::FromStr;
+
+    fn quote(price: &str) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        Ok(AssetQuoteResponse {
+            name: "bitcoin".to_string(),
+            price: BigDecimal::from_str(price).unwrap(),
+            price_change_24h: BigDecimal::from_str("1.5").unwrap(),
+            ..Default::default()
+        })
+    }
+
+    fn timeout() -> Result<AssetQuoteResponse, QuoteRequestError> {
+        Err(QuoteRequestError::Timeout("no response".to_string()))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_opens_after_threshold() {
+        let mock = Arc::new(MockProvider::new(vec![timeout(), timeout(), quote("1")]));
+        let provider = CircuitBreakerProvider::new(mock.clone(), 2, Duration::from_secs(60));
+
+        assert!(provider.fetch_quote("bitcoin", "usd").await.is_err());
+        assert!(provider.fetch_quote("bitcoin", "usd").await.is_err());
+        let err = provider.fetch_quote("bitcoin", "usd").await.unwrap_err();
+        assert!(matches!(err, QuoteRequestError::CircuitOpen(_)), "{}", err);
+        assert_eq!(2, mock.calls());
+
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert_eq!("1", provider.fetch_quote("bitcoin", "usd").await.unwrap().price.to_string());
+        assert_eq!(3, mock.calls());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failed_trial_doubles_cooldown() {
+        let mock = Arc::new(MockProvider::new(vec![timeout(), timeout(), quote("1")]));
+        let provider = CircuitBreakerProvider::new(mock.clone(), 1, Duration::from_secs(60));
+
+        assert!(provider.fetch_quote("bitcoin", "usd").await.is_err());
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert!(matches!(
+            provider.fetch_quote("bitcoin", "usd").await,
+            Err(QuoteRequestError::Timeout(_))
+        ));
+
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert!(matches!(
+            provider.fetch_quote("bitcoin", "usd").await,
+            Err(QuoteRequestError::CircuitOpen(_))
+        ));
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert_eq!("1", provider.fetch_quote("bitcoin", "usd").await.unwrap().price.to_string());
+        assert_eq!(3, mock.calls());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_missing_asset_not_counted() {
+        let mock = Arc::new(MockProvider::new(vec![
+            Err(QuoteRequestError::MissingAsset("bitcoin".to_string())),
+            quote("1"),
+        ]));
+        let provider = CircuitBreakerProvider::new(mock.clone(), 1, Duration::from_secs(60));
+
+        assert!(provider.fetch_quote("bitcoin", "usd").await.is_err());
+        assert_eq!("1", provider.fetch_quote("bitcoin", "usd").await.unwrap().price.to_string());
+    }
+}
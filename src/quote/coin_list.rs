@@ -0,0 +1,154 @@
+use crate::quote::error::QuoteRequestError;
+use serde::Deserialize;
+use std::collections::HashSet;
+
+/// Every coin known to CoinGecko, as returned by `/coins/list`
+pub struct CoinList {
+    coins: Vec<CoinListEntry>,
+    ids: HashSet<String>,
+}
+
+/// Example: `{"id":"bitcoin","symbol":"btc","name":"Bitcoin"}`
+#[derive(Debug, Deserialize)]
+pub struct CoinListEntry {
+    pub id: String,
+    pub symbol: String,
+    pub name: String,
+}
+
+impl CoinList {
+    pub fn parse(body: &str) -> Result<Self, QuoteRequestError> {
+        let coins: Vec<CoinListEntry> = serde_json::from_str(body)?;
+        let ids = coins.iter().map(|coin| coin.id.clone()).collect();
+        Ok(Self { coins, ids })
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.ids.contains(id)
+    }
+
+    /// Symbol of the coin `id`, e.g. `btc` for `bitcoin`
+    pub fn symbol_of(&self, id: &str) -> Option<&str> {
+        self.coins.iter().find(|coin| coin.id == id).map(|coin| coin.symbol.as_str())
+    }
+
+    /// Ids of the coins whose symbol is `symbol`, e.g. `bitcoin` and the bridged versions of it for `BTC`
+    pub fn ids_of_symbol(&self, symbol: &str) -> Vec<&str> {
+        self.coins
+            .iter()
+            .filter(|coin| coin.symbol.eq_ignore_ascii_case(symbol))
+            .map(|coin| coin.id.as_str())
+            .collect()
+    }
+
+    /// The id the user most likely meant by `id`: a coin whose symbol or name is `id`,
+    /// otherwise the id closest to it if it differs by only a few characters.
+    pub fn suggest(&self, id: &str) -> Option<&str> {
+        let id = id.trim().to_lowercase();
+
+        if let Some(coin) = self
+            .coins
+            .iter()
+            .find(|coin| coin.symbol.eq_ignore_ascii_case(&id) || coin.name.eq_ignore_ascii_case(&id))
+        {
+            return Some(&coin.id);
+        }
+
+        let max_distance = (id.chars().count() / 3).max(2);
+        self.coins
+            .iter()
+            .map(|coin| (edit_distance(&id, &coin.id), coin))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, coin)| coin.id.as_str())
+    }
+}
+
+/// Levenshtein distance between `a` and `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coin_list() -> CoinList {
+        CoinList::parse(
+            r#"[
+                {"id":"bitcoin","symbol":"btc","name":"Bitcoin"},
+                {"id":"bitcoin-cash","symbol":"bch","name":"Bitcoin Cash"},
+                {"id":"cardano","symbol":"ada","name":"Cardano"},
+                {"id":"ethereum","symbol":"eth","name":"Ethereum"}
+            ]"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(0, edit_distance("bitcoin", "bitcoin"));
+        assert_eq!(2, edit_distance("bitcoin", "bitcion"));
+        assert_eq!(1, edit_distance("etherum", "ethereum"));
+        assert_eq!(3, edit_distance("", "abc"));
+    }
+
+    #[test]
+    fn test_contains() {
+        let coins = coin_list();
+        assert!(coins.contains("bitcoin"));
+        assert!(!coins.contains("Bitcoin"));
+        assert!(!coins.contains("btc"));
+    }
+
+    #[test]
+    fn test_symbol_of() {
+        let coins = coin_list();
+        assert_eq!(Some("eth"), coins.symbol_of("ethereum"));
+        assert_eq!(None, coins.symbol_of("eth"));
+    }
+
+    #[test]
+    fn test_ids_of_symbol() {
+        let coins = CoinList::parse(
+            r#"[
+                {"id":"bitcoin","symbol":"btc","name":"Bitcoin"},
+                {"id":"bitcoin-cash","symbol":"bch","name":"Bitcoin Cash"},
+                {"id":"osmosis-allbtc","symbol":"btc","name":"Osmosis allBTC"}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(vec!["bitcoin", "osmosis-allbtc"], coins.ids_of_symbol("BTC"));
+        assert_eq!(vec!["bitcoin-cash"], coins.ids_of_symbol("bch"));
+        assert!(coins.ids_of_symbol("XYZ").is_empty());
+    }
+
+    #[test]
+    fn test_suggest() {
+        let coins = coin_list();
+        assert_eq!(Some("ethereum"), coins.suggest("etherum"));
+        assert_eq!(Some("cardano"), coins.suggest("cardona"));
+        assert_eq!(Some("bitcoin"), coins.suggest("BTC"));
+        assert_eq!(Some("bitcoin-cash"), coins.suggest("bitcoin cash"));
+        assert_eq!(None, coins.suggest("dogecoin"));
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        assert!(CoinList::parse("").is_err());
+        assert!(CoinList::parse(r#"{"id":"bitcoin"}"#).is_err());
+        assert!(CoinList::parse(r#"[{"id":"bitcoin"}]"#).is_err());
+    }
+}
@@ -0,0 +1,162 @@
+use crate::quote::api_keys::ApiKeys;
+use crate::quote::error::QuoteRequestError;
+use crate::quote::provider::QuoteProvider;
+use crate::quote::response::AssetQuoteResponse;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use reqwest::header;
+use serde::Deserialize;
+use std::str::FromStr;
+use std::sync::Arc;
+
+pub const DEFAULT_BASE_URL: &str = "https://rest.coincap.io/v3";
+
+/// Minimum interval between two updates of a ticker, every request costs credits of the API key
+pub const MIN_UPDATE_INTERVAL_SECS: u64 = 10;
+
+pub fn asset_url(base_url: &str, id: &str) -> String {
+    format!("{}/assets/{}", base_url, id)
+}
+
+/// Quotes from CoinCap, assets are identified by ids mostly matching the ones of CoinGecko.
+/// CoinCap only quotes in USD.
+pub struct CoinCapProvider {
+    http_client: reqwest::Client,
+    base_url: String,
+    api_keys: Arc<ApiKeys>,
+}
+
+impl CoinCapProvider {
+    pub fn new(http_client: reqwest::Client, base_url: String, api_key: String) -> Self {
+        Self::with_api_keys(http_client, base_url, Arc::new(ApiKeys::single("CoinCap", api_key)))
+    }
+
+    pub fn with_api_keys(http_client: reqwest::Client, base_url: String, api_keys: Arc<ApiKeys>) -> Self {
+        Self {
+            http_client,
+            base_url,
+            api_keys,
+        }
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for CoinCapProvider {
+    fn name(&self) -> &str {
+        "CoinCap"
+    }
+
+    async fn fetch_quote(
+        &self,
+        id: &str,
+        vs_currency: &str,
+    ) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        if !vs_currency.eq_ignore_ascii_case("usd") {
+            return Err(QuoteRequestError::Other(format!(
+                "CoinCap only quotes in USD, not in {}",
+                vs_currency
+            )));
+        }
+
+        let body = self
+            .api_keys
+            .with_key(|api_key| async move {
+                let mut http_req_build = self
+                    .http_client
+                    .get(asset_url(&self.base_url, id))
+                    .header(header::ACCEPT, "application/json");
+
+                if let Some(api_key) = api_key {
+                    http_req_build = http_req_build.bearer_auth(api_key.expose());
+                }
+
+                Ok(http_req_build.send().await?.error_for_status()?.text().await?)
+            })
+            .await?;
+        parse_asset_response(&body, id)
+    }
+}
+
+/// Response of the `/assets/{id}` endpoint, only the fields in use are listed.
+///
+/// Example: `{"data":{"id":"bitcoin","symbol":"BTC","priceUsd":"65761.12","changePercent24Hr":"1.884"}}`
+#[derive(Debug, Deserialize)]
+struct AssetResponse {
+    data: Option<Asset>,
+}
+
+/// The price is deserialized straight into `BigDecimal`, from a string or a number, so that no precision is lost.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Asset {
+    price_usd: Option<BigDecimal>,
+    change_percent_24_hr: Option<String>,
+}
+
+pub fn parse_asset_response(body: &str, id: &str) -> Result<AssetQuoteResponse, QuoteRequestError> {
+    let response: AssetResponse = serde_json::from_str(body)?;
+    let asset = response
+        .data
+        .ok_or_else(|| QuoteRequestError::MissingAsset(id.to_string()))?;
+
+    let price = asset
+        .price_usd
+        .ok_or_else(|| QuoteRequestError::MissingField("priceUsd".to_string()))?;
+    let price_change_24h = asset
+        .change_percent_24_hr
+        .as_deref()
+        .ok_or_else(|| QuoteRequestError::MissingField("changePercent24Hr".to_string()))
+        .and_then(|value| BigDecimal::from_str(value).map_err(|_| QuoteRequestError::InvalidField("changePercent24Hr".to_string())))?;
+
+    Ok(AssetQuoteResponse {
+        name: id.to_string(),
+        price,
+        price_change_24h,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_asset_url() {
+        assert_eq!("https://rest.coincap.io/v3/assets/bitcoin", asset_url(DEFAULT_BASE_URL, "bitcoin"));
+    }
+
+    #[test]
+    fn test_parse_asset_response() {
+        let body = r#"{"data":{"id":"bitcoin","symbol":"BTC","priceUsd":"65761.1234567890","changePercent24Hr":"-1.25"},"timestamp":1721332800000}"#;
+        let response = parse_asset_response(body, "bitcoin").unwrap();
+        assert_eq!("bitcoin", response.name);
+        assert_eq!("65761.1234567890", response.price.to_string());
+        assert_eq!("-1.25", response.price_change_24h.to_string());
+    }
+
+    #[test]
+    fn test_parse_asset_response_number_price() {
+        let body = r#"{"data":{"priceUsd":65761.123456789012345678,"changePercent24Hr":"-1.25"}}"#;
+        let response = parse_asset_response(body, "bitcoin").unwrap();
+        assert_eq!("65761.123456789012345678", response.price.to_string());
+    }
+
+    #[test]
+    fn test_parse_asset_response_malformed() {
+        let cases = [
+            ("", "JSON Parse Error"),
+            (r#"{"error":"bitcoinz not found"}"#, "Missing Asset Error"),
+            (r#"{"data":{"changePercent24Hr":"1.0"}}"#, "Missing Field Error: `priceUsd`"),
+            (r#"{"data":{"priceUsd":"1.0"}}"#, "Missing Field Error: `changePercent24Hr`"),
+        ];
+
+        for (body, expected) in cases {
+            let err = parse_asset_response(body, "bitcoin").unwrap_err();
+            assert!(
+                err.to_string().starts_with(expected),
+                "body {:?}: expected {:?}, got {:?}",
+                body, expected, err.to_string()
+            );
+        }
+    }
+}
@@ -0,0 +1,423 @@
+use crate::quote::api_keys::ApiKeys;
+use crate::quote::coin_list::CoinList;
+use crate::quote::error::QuoteRequestError;
+use crate::quote::etag::EtagCache;
+use crate::quote::provider::QuoteProvider;
+use crate::quote::rate_limit::{LastQuota, RateLimitQuota};
+use crate::quote::response::AssetQuoteResponse;
+use crate::secret::SecretString;
+use async_trait::async_trait;
+use bigdecimal::{BigDecimal, Zero};
+use reqwest::header;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub const DEFAULT_BASE_URL: &str = "https://api.coingecko.com/api/v3";
+
+/// Minimum interval between two updates of a ticker, keeps the demo plan's rate limit in reach
+pub const MIN_UPDATE_INTERVAL_SECS: u64 = 10;
+
+pub fn simple_price_url(base_url: &str, id: &str, vs_currency: &str) -> String {
+    format!(
+        "{}/simple/price?ids={}&vs_currencies={}&include_24hr_change=true",
+        base_url, id, vs_currency
+    )
+}
+
+pub fn coins_list_url(base_url: &str) -> String {
+    format!("{}/coins/list", base_url)
+}
+
+pub fn coins_markets_url(base_url: &str, ids: &[&str], vs_currency: &str) -> String {
+    format!(
+        "{}/coins/markets?vs_currency={}&ids={}",
+        base_url,
+        vs_currency,
+        ids.join(",")
+    )
+}
+
+pub struct CoinGeckoProvider {
+    http_client: reqwest::Client,
+    base_url: String,
+    api_keys: Arc<ApiKeys>, // Shared with the provider of the global figures
+    simple_prices: EtagCache<AssetQuoteResponse>, // Answered with 304 while the price did not change
+    quota: LastQuota, // From the rate limit headers of the last response
+}
+
+impl CoinGeckoProvider {
+    pub fn new(http_client: reqwest::Client, base_url: String, api_key: String) -> Self {
+        Self::with_api_keys(http_client, base_url, Arc::new(ApiKeys::single("CoinGecko", api_key)))
+    }
+
+    pub fn with_api_keys(http_client: reqwest::Client, base_url: String, api_keys: Arc<ApiKeys>) -> Self {
+        Self {
+            http_client,
+            base_url,
+            api_keys,
+            simple_prices: EtagCache::default(),
+            quota: LastQuota::default(),
+        }
+    }
+
+    /// Fetches the id, symbol and name of every coin, to check the configured ids against
+    pub async fn fetch_coin_list(&self) -> Result<CoinList, QuoteRequestError> {
+        let body = self.get(coins_list_url(&self.base_url)).await?;
+        CoinList::parse(&body)
+    }
+
+    /// Resolves a symbol like `BTC` to the id of the coin with the highest market cap among
+    /// the coins sharing that symbol. Returns `None` if no coin has that symbol.
+    pub async fn resolve_symbol(
+        &self,
+        coin_list: &CoinList,
+        symbol: &str,
+    ) -> Result<Option<String>, QuoteRequestError> {
+        let ids = coin_list.ids_of_symbol(symbol);
+        if ids.len() <= 1 {
+            return Ok(ids.first().map(|id| id.to_string()));
+        }
+
+        let body = self.get(coins_markets_url(&self.base_url, &ids, "usd")).await?;
+        let markets = parse_coins_markets_response(&body)?;
+        Ok(highest_market_cap(&markets).or_else(|| ids.first().map(|id| id.to_string())))
+    }
+
+    pub fn api_keys(&self) -> Arc<ApiKeys> {
+        self.api_keys.clone()
+    }
+
+    async fn get(&self, url: String) -> Result<String, QuoteRequestError> {
+        self.api_keys
+            .with_key(|api_key| async move {
+                let response = self.request(&url, api_key.as_ref()).send().await?;
+                self.record_quota(response.headers());
+                Ok(response.error_for_status()?.text().await?)
+            })
+            .await
+    }
+
+    /// The quota of one key of a pool says little about the others, which take over once it runs out
+    fn record_quota(&self, headers: &header::HeaderMap) {
+        if self.api_keys.len() <= 1 {
+            self.quota.record(headers);
+        }
+    }
+
+    fn request(&self, url: &str, api_key: Option<&SecretString>) -> reqwest::RequestBuilder {
+        let http_req_build = self
+            .http_client
+            .get(url)
+            .header(header::ACCEPT, "application/json");
+
+        match api_key {
+            Some(api_key) => http_req_build.header("x-cg-demo-api-key", api_key.expose()),
+            None => http_req_build,
+        }
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for CoinGeckoProvider {
+    fn name(&self) -> &str {
+        "CoinGecko"
+    }
+
+    async fn fetch_quote(
+        &self,
+        id: &str,
+        vs_currency: &str,
+    ) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        let url = simple_price_url(&self.base_url, id, vs_currency);
+        let (mut response, not_modified) = self
+            .api_keys
+            .with_key(|api_key| {
+                self.simple_prices.fetch(
+                    &url,
+                    self.request(&url, api_key.as_ref()),
+                    |headers| self.record_quota(headers),
+                    |body| parse_simple_price_response(body, id, vs_currency),
+                )
+            })
+            .await?;
+        response.not_modified = not_modified;
+        Ok(response)
+    }
+
+    fn take_rate_limit_quota(&self) -> Option<RateLimitQuota> {
+        self.quota.take()
+    }
+}
+
+pub fn global_url(base_url: &str) -> String {
+    format!("{}/global", base_url)
+}
+
+/// Figures of the whole crypto market from the `/global` endpoint. The id of the asset picks the figure:
+/// `total_market_cap` in the requested currency, or `<symbol>_dominance` (e.g. `btc_dominance`) in percent.
+pub struct CoinGeckoGlobalProvider {
+    provider: CoinGeckoProvider,
+}
+
+impl CoinGeckoGlobalProvider {
+    pub fn new(http_client: reqwest::Client, base_url: String, api_key: String) -> Self {
+        Self {
+            provider: CoinGeckoProvider::new(http_client, base_url, api_key),
+        }
+    }
+
+    pub fn with_api_keys(http_client: reqwest::Client, base_url: String, api_keys: Arc<ApiKeys>) -> Self {
+        Self {
+            provider: CoinGeckoProvider::with_api_keys(http_client, base_url, api_keys),
+        }
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for CoinGeckoGlobalProvider {
+    fn name(&self) -> &str {
+        "CoinGecko global"
+    }
+
+    async fn fetch_quote(
+        &self,
+        id: &str,
+        vs_currency: &str,
+    ) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        let body = self.provider.get(global_url(&self.provider.base_url)).await?;
+        parse_global_response(&body, id, vs_currency)
+    }
+
+    fn take_rate_limit_quota(&self) -> Option<RateLimitQuota> {
+        self.provider.take_rate_limit_quota()
+    }
+}
+
+/// Response of the `/global` endpoint, only the fields in use are listed.
+///
+/// Example: `{"data":{"total_market_cap":{"usd":2.3e12},"market_cap_percentage":{"btc":52.1},"market_cap_change_percentage_24h_usd":1.2}}`
+#[derive(Debug, Deserialize)]
+struct GlobalResponse {
+    data: GlobalData,
+}
+
+#[derive(Debug, Deserialize)]
+struct GlobalData {
+    #[serde(default)]
+    total_market_cap: HashMap<String, BigDecimal>,
+    #[serde(default)]
+    market_cap_percentage: HashMap<String, BigDecimal>,
+    market_cap_change_percentage_24h_usd: Option<BigDecimal>,
+}
+
+pub fn parse_global_response(
+    body: &str,
+    id: &str,
+    vs_currency: &str,
+) -> Result<AssetQuoteResponse, QuoteRequestError> {
+    let response: GlobalResponse = serde_json::from_str(body)?;
+    let data = response.data;
+
+    let (price, price_change_24h, label) = if id == "total_market_cap" {
+        let total_market_cap = data
+            .total_market_cap
+            .get(vs_currency)
+            .ok_or_else(|| QuoteRequestError::MissingField(format!("total_market_cap.{}", vs_currency)))?;
+        (total_market_cap.clone(), data.market_cap_change_percentage_24h_usd.unwrap_or_else(BigDecimal::zero), None)
+    } else if let Some(symbol) = id.strip_suffix("_dominance") {
+        let dominance = data
+            .market_cap_percentage
+            .get(symbol)
+            .ok_or_else(|| QuoteRequestError::MissingField(format!("market_cap_percentage.{}", symbol)))?;
+        (dominance.clone(), BigDecimal::zero(), Some(format!("{} dominance", symbol.to_uppercase())))
+    } else {
+        return Err(QuoteRequestError::MissingAsset(id.to_string()));
+    };
+
+    Ok(AssetQuoteResponse {
+        name: id.to_string(),
+        price,
+        price_change_24h,
+        label,
+        ..Default::default()
+    })
+}
+
+/// Response of the `/simple/price` endpoint, keyed by coin id.
+///
+/// Example: `{"bitcoin":{"usd":65761,"usd_24h_change":1.8841205093585678}}`
+pub type SimplePriceResponse = HashMap<String, SimplePriceEntry>;
+
+/// Quote fields of a single coin, keyed by field name (e.g. `usd`, `usd_24h_change`).
+/// Values are deserialized straight into `BigDecimal` so that no precision is lost.
+#[derive(Debug, Deserialize)]
+#[serde(transparent)]
+pub struct SimplePriceEntry(HashMap<String, Option<BigDecimal>>);
+
+impl SimplePriceEntry {
+    fn field(&self, name: &str) -> Result<&BigDecimal, QuoteRequestError> {
+        match self.0.get(name) {
+            Some(Some(value)) => Ok(value),
+            _ => Err(QuoteRequestError::MissingField(name.to_string())),
+        }
+    }
+
+    pub fn price(&self, vs_currency: &str) -> Result<&BigDecimal, QuoteRequestError> {
+        self.field(vs_currency)
+    }
+
+    pub fn price_change_24h(&self, vs_currency: &str) -> Result<&BigDecimal, QuoteRequestError> {
+        self.field(&format!("{}_24h_change", vs_currency))
+    }
+}
+
+/// Entry of the `/coins/markets` endpoint, only the fields in use are listed.
+///
+/// Example: `[{"id":"bitcoin","symbol":"btc","current_price":65761,"market_cap":1296010000000}]`
+#[derive(Debug, Deserialize)]
+pub struct CoinMarket {
+    pub id: String,
+    pub market_cap: Option<BigDecimal>,
+}
+
+pub fn parse_coins_markets_response(body: &str) -> Result<Vec<CoinMarket>, QuoteRequestError> {
+    Ok(serde_json::from_str(body)?)
+}
+
+/// Id of the coin with the highest market cap, coins without one are ignored
+fn highest_market_cap(markets: &[CoinMarket]) -> Option<String> {
+    markets
+        .iter()
+        .filter_map(|market| market.market_cap.as_ref().map(|market_cap| (market_cap, &market.id)))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, id)| id.clone())
+}
+
+pub fn parse_simple_price_response(
+    body: &str,
+    id: &str,
+    vs_currency: &str,
+) -> Result<AssetQuoteResponse, QuoteRequestError> {
+    let response: SimplePriceResponse = serde_json::from_str(body)?;
+
+    let entry = response
+        .get(id)
+        .ok_or_else(|| QuoteRequestError::MissingAsset(id.to_string()))?;
+
+    let price = entry.price(vs_currency)?.clone();
+    let price_change_24h = entry.price_change_24h(vs_currency)?.clone();
+
+    Ok(AssetQuoteResponse {
+        name: id.to_string(),
+        price,
+        price_change_24h,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simple_price_url() {
+        assert_eq!(
+            "https://api.coingecko.com/api/v3/simple/price?ids=bitcoin&vs_currencies=usd&include_24hr_change=true",
+            simple_price_url(DEFAULT_BASE_URL, "bitcoin", "usd")
+        );
+        assert_eq!(
+            "http://localhost:8080/simple/price?ids=cardano&vs_currencies=eur&include_24hr_change=true",
+            simple_price_url("http://localhost:8080", "cardano", "eur")
+        );
+    }
+
+    #[test]
+    fn test_coins_list_url() {
+        assert_eq!("https://api.coingecko.com/api/v3/coins/list", coins_list_url(DEFAULT_BASE_URL));
+    }
+
+    #[test]
+    fn test_parse_global_response() {
+        let body = r#"{"data":{"active_cryptocurrencies":15000,
+            "total_market_cap":{"usd":2345678901234.5,"eur":2100000000000},
+            "market_cap_percentage":{"btc":52.123,"eth":16.9},
+            "market_cap_change_percentage_24h_usd":-1.25}}"#;
+
+        let response = parse_global_response(body, "total_market_cap", "eur").unwrap();
+        assert_eq!("2100000000000", response.price.to_string());
+        assert_eq!("-1.25", response.price_change_24h.to_string());
+        assert_eq!(None, response.label);
+
+        let response = parse_global_response(body, "btc_dominance", "usd").unwrap();
+        assert_eq!("52.123", response.price.to_string());
+        assert_eq!(Some("BTC dominance".to_string()), response.label);
+
+        for (id, vs_currency, expected) in [
+            ("total_market_cap", "xyz", "Missing Field Error: `total_market_cap.xyz`"),
+            ("doge_dominance", "usd", "Missing Field Error: `market_cap_percentage.doge`"),
+            ("bitcoin", "usd", "Missing Asset Error"),
+        ] {
+            let err = parse_global_response(body, id, vs_currency).unwrap_err();
+            assert!(err.to_string().starts_with(expected), "{}: got {}", id, err);
+        }
+    }
+
+    #[test]
+    fn test_highest_market_cap() {
+        let body = r#"[
+            {"id":"osmosis-allbtc","symbol":"btc","market_cap":12345678.9},
+            {"id":"bitcoin","symbol":"btc","market_cap":1296010000000},
+            {"id":"some-btc","symbol":"btc","market_cap":null}
+        ]"#;
+        let markets = parse_coins_markets_response(body).unwrap();
+        assert_eq!(Some("bitcoin".to_string()), highest_market_cap(&markets));
+        assert_eq!(None, highest_market_cap(&markets[2..]));
+        assert_eq!(
+            "https://api.coingecko.com/api/v3/coins/markets?vs_currency=usd&ids=bitcoin,osmosis-allbtc",
+            coins_markets_url(DEFAULT_BASE_URL, &["bitcoin", "osmosis-allbtc"], "usd")
+        );
+    }
+
+    #[test]
+    fn test_parse_simple_price_response() {
+        let body = r#"{"bitcoin":{"usd":65761.123456789012345,"usd_24h_change":1.8841205093585678}}"#;
+        let response = parse_simple_price_response(body, "bitcoin", "usd").unwrap();
+        assert_eq!("bitcoin", response.name);
+        assert_eq!("65761.123456789012345", response.price.to_string());
+        assert_eq!("1.8841205093585678", response.price_change_24h.to_string());
+    }
+
+    #[test]
+    fn test_parse_simple_price_response_keeps_precision() {
+        let body = r#"{"shiba-inu":{"usd":0.000007123456789012345678,"usd_24h_change":-0.5}}"#;
+        let response = parse_simple_price_response(body, "shiba-inu", "usd").unwrap();
+        assert_eq!("0.000007123456789012345678", response.price.to_string());
+    }
+
+    #[test]
+    fn test_parse_simple_price_response_malformed() {
+        let cases = [
+            ("", "JSON Parse Error"),
+            ("[]", "JSON Parse Error"),
+            (r#"{"bitcoin":"65761"}"#, "JSON Parse Error"),
+            (r#"{"bitcoin":{"usd":"abc","usd_24h_change":1.0}}"#, "JSON Parse Error"),
+            (r#"{"bitcoin":{"usd":true,"usd_24h_change":1.0}}"#, "JSON Parse Error"),
+            ("{}", "Missing Asset Error"),
+            (r#"{"ethereum":{"usd":3000,"usd_24h_change":1.0}}"#, "Missing Asset Error"),
+            (r#"{"bitcoin":{"usd_24h_change":1.0}}"#, "Missing Field Error: `usd`"),
+            (r#"{"bitcoin":{"usd":null,"usd_24h_change":1.0}}"#, "Missing Field Error: `usd`"),
+            (r#"{"bitcoin":{"usd":65761}}"#, "Missing Field Error: `usd_24h_change`"),
+            (r#"{"bitcoin":{"usd":65761,"usd_24h_change":null}}"#, "Missing Field Error: `usd_24h_change`"),
+        ];
+
+        for (body, expected) in cases {
+            let err = parse_simple_price_response(body, "bitcoin", "usd").unwrap_err();
+            assert!(
+                err.to_string().starts_with(expected),
+                "body {:?}: expected {:?}, got {:?}",
+                body, expected, err.to_string()
+            );
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use crate::quote::error::QuoteRequestError;
+use crate::quote::provider::QuoteProvider;
+use crate::quote::response::AssetQuoteResponse;
+use crate::secret::SecretString;
+use async_trait::async_trait;
+use bigdecimal::{BigDecimal, Zero};
+use reqwest::header;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Minimum interval between two updates of a ticker, the API is the user's own so only hammering it is prevented
+pub const MIN_UPDATE_INTERVAL_SECS: u64 = 10;
+
+/// A REST API returning the price as JSON, named in `custom_http` and picked by the `name` of a ticker
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomHttpEndpoint {
+    pub url: SecretString, // URL of the JSON response, `{vs_currency}` is replaced by the currency of the ticker
+    #[serde(default)]
+    pub headers: BTreeMap<String, SecretString>, // Headers sent with the request, e.g. `{"x-api-key": "..."}`
+    pub price: String, // Path of the price in the response, e.g. `data.price` or `$.result[0].last`
+    pub change: Option<String>, // Path of the change over 24h in percent, `0` without it
+}
+
+/// Quotes from the endpoints of `custom_http`, the id of the asset is the name of the endpoint
+pub struct CustomHttpProvider {
+    http_client: reqwest::Client,
+    endpoints: BTreeMap<String, CustomHttpEndpoint>,
+}
+
+impl CustomHttpProvider {
+    pub fn new(http_client: reqwest::Client, endpoints: BTreeMap<String, CustomHttpEndpoint>) -> Self {
+        Self {
+            http_client,
+            endpoints,
+        }
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for CustomHttpProvider {
+    fn name(&self) -> &str {
+        "Custom HTTP"
+    }
+
+    async fn fetch_quote(
+        &self,
+        id: &str,
+        vs_currency: &str,
+    ) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        let endpoint = self
+            .endpoints
+            .get(id)
+            .ok_or_else(|| QuoteRequestError::NotFound(format!("no `custom_http` endpoint named {}", id)))?;
+
+        let mut request = self
+            .http_client
+            .get(endpoint.url.expose().replace("{vs_currency}", vs_currency))
+            .header(header::ACCEPT, "application/json");
+        for (name, value) in &endpoint.headers {
+            request = request.header(name.as_str(), value.expose());
+        }
+
+        // the URL may hold an API key in its query
+        let body = request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(reqwest::Error::without_url)?
+            .text()
+            .await
+            .map_err(reqwest::Error::without_url)?;
+        parse_custom_http_response(&body, id, endpoint)
+    }
+}
+
+pub fn parse_custom_http_response(
+    body: &str,
+    id: &str,
+    endpoint: &CustomHttpEndpoint,
+) -> Result<AssetQuoteResponse, QuoteRequestError> {
+    let response: Value = serde_json::from_str(body)?;
+    let decimal = |path: &str| {
+        let value = select(&response, path).ok_or_else(|| QuoteRequestError::MissingField(path.to_string()))?;
+        to_decimal(value).ok_or_else(|| QuoteRequestError::InvalidField(path.to_string()))
+    };
+
+    Ok(AssetQuoteResponse {
+        name: id.to_string(),
+        price: decimal(&endpoint.price)?,
+        price_change_24h: match &endpoint.change {
+            Some(change) => decimal(change)?,
+            None => BigDecimal::zero(),
+        },
+        ..Default::default()
+    })
+}
+
+/// Value at `path` in `json`, keys separated by dots and array indexes in brackets like JSONPath, e.g.
+/// `data.price`, `$.result[0].last` or `[0][4]`. `None` if anything on the way is missing.
+pub fn select<'a>(json: &'a Value, path: &str) -> Option<&'a Value> {
+    let path = path.strip_prefix('$').unwrap_or(path);
+    let mut value = json;
+
+    for segment in path.split('.').filter(|segment| !segment.is_empty()) {
+        let (key, indexes) = segment.split_at(segment.find('[').unwrap_or(segment.len()));
+        if !key.is_empty() {
+            value = value.get(key)?;
+        }
+        for index in indexes.split('[').skip(1) {
+            let index: usize = index.strip_suffix(']')?.trim().parse().ok()?;
+            value = value.get(index)?;
+        }
+    }
+
+    Some(value)
+}
+
+/// A number, or a string holding one as many APIs send prices, e.g. `"65761.12"`
+pub fn to_decimal(value: &Value) -> Option<BigDecimal> {
+    match value {
+        Value::Number(number) => BigDecimal::from_str(&number.to_string()).ok(),
+        Value::String(text) => BigDecimal::from_str(text.trim()).ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(price: &str, change: Option<&str>) -> CustomHttpEndpoint {
+        CustomHttpEndpoint {
+            url: SecretString::from("https://example.com/price"),
+            headers: BTreeMap::new(),
+            price: price.to_string(),
+            change: change.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_url_is_not_in_errors() {
+        // nothing listens on port 1, so the request fails to connect
+        let endpoint = CustomHttpEndpoint {
+            url: SecretString::from("http://127.0.0.1:1/price?apikey=0123456789abcdef"),
+            ..endpoint("price", None)
+        };
+        let provider = CustomHttpProvider::new(reqwest::Client::new(), BTreeMap::from([("my-api".to_string(), endpoint)]));
+        let error = provider.fetch_quote("my-api", "usd").await.unwrap_err();
+        let message = crate::alert::error_chain(&error);
+        assert!(!message.contains("0123456789abcdef"), "{}", message);
+    }
+
+    #[test]
+    fn test_select() {
+        let json: Value = serde_json::from_str(r#"{"data": {"price": 1.5}, "result": [{"last": "2"}, {"last": "3"}], "k": [[0, 4]]}"#).unwrap();
+        assert_eq!(Some(&serde_json::json!(1.5)), select(&json, "data.price"));
+        assert_eq!(Some(&serde_json::json!("3")), select(&json, "$.result[1].last"));
+        assert_eq!(Some(&serde_json::json!(4)), select(&json, "k[0][1]"));
+        assert_eq!(Some(&json), select(&json, "$"));
+        assert_eq!(None, select(&json, "data.volume"));
+        assert_eq!(None, select(&json, "result[2].last"));
+        assert_eq!(None, select(&json, "result[x]"));
+    }
+
+    #[test]
+    fn test_parse_custom_http_response() {
+        let body = r#"{"data": {"price": "65761.12", "change_pct": -1.25}}"#;
+        let response = parse_custom_http_response(body, "my-api", &endpoint("data.price", Some("data.change_pct"))).unwrap();
+        assert_eq!("my-api", response.name);
+        assert_eq!("65761.12", response.price.to_string());
+        assert_eq!("-1.25", response.price_change_24h.to_string());
+
+        let response = parse_custom_http_response(body, "my-api", &endpoint("data.price", None)).unwrap();
+        assert!(response.price_change_24h.is_zero());
+
+        assert!(matches!(
+            parse_custom_http_response(body, "my-api", &endpoint("data.last", None)),
+            Err(QuoteRequestError::MissingField(field)) if field == "data.last"
+        ));
+        assert!(matches!(
+            parse_custom_http_response(body, "my-api", &endpoint("data", None)),
+            Err(QuoteRequestError::InvalidField(field)) if field == "data"
+        ));
+    }
+}
@@ -0,0 +1,170 @@
+use crate::quote::error::QuoteRequestError;
+use crate::quote::provider::QuoteProvider;
+use crate::quote::response::AssetQuoteResponse;
+use async_trait::async_trait;
+use bigdecimal::{BigDecimal, Zero};
+use reqwest::header;
+use serde::Deserialize;
+
+pub const DEFAULT_BASE_URL: &str = "https://api.llama.fi";
+
+/// Minimum interval between two updates of a ticker, DefiLlama updates TVLs about once an hour
+pub const MIN_UPDATE_INTERVAL_SECS: u64 = 300;
+
+/// Prefix of the ids of chains, e.g. `chain/Ethereum`, the other ids are slugs of protocols
+const CHAIN_PREFIX: &str = "chain/";
+const DAY_SECS: i64 = 24 * 60 * 60;
+
+/// URL of the TVL history of the protocol or chain `id`
+pub fn tvl_url(base_url: &str, id: &str) -> String {
+    match id.strip_prefix(CHAIN_PREFIX) {
+        Some(chain) => format!("{}/v2/historicalChainTvl/{}", base_url, chain),
+        None => format!("{}/protocol/{}", base_url, id),
+    }
+}
+
+/// Total value locked in a DeFi protocol, by its slug like `aave`, or in a chain, like `chain/Ethereum`, from
+/// DefiLlama, in USD. The price of the quote is the TVL, its change the one since the day before.
+pub struct DefiLlamaProvider {
+    http_client: reqwest::Client,
+    base_url: String,
+}
+
+impl DefiLlamaProvider {
+    pub fn new(http_client: reqwest::Client, base_url: String) -> Self {
+        Self {
+            http_client,
+            base_url,
+        }
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for DefiLlamaProvider {
+    fn name(&self) -> &str {
+        "DefiLlama"
+    }
+
+    async fn fetch_quote(
+        &self,
+        id: &str,
+        vs_currency: &str,
+    ) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        if !vs_currency.eq_ignore_ascii_case("usd") {
+            return Err(QuoteRequestError::Other(format!(
+                "DefiLlama only has TVLs in USD, not in {}",
+                vs_currency
+            )));
+        }
+
+        let body = self
+            .http_client
+            .get(tvl_url(&self.base_url, id))
+            .header(header::ACCEPT, "application/json")
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        let history = match id.strip_prefix(CHAIN_PREFIX) {
+            Some(_) => parse_chain_response(&body)?,
+            None => parse_protocol_response(&body)?,
+        };
+        tvl_quote(&history, id)
+    }
+}
+
+/// A TVL at a Unix timestamp, in USD
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct TvlPoint {
+    date: i64,
+    #[serde(alias = "totalLiquidityUSD")]
+    tvl: BigDecimal,
+}
+
+/// Response of the `/protocol/{slug}` endpoint, only the fields in use are listed. The TVL history has a point a
+/// day and the latest one last.
+///
+/// Example: `{"id":"111","name":"Aave","tvl":[{"date":1721260800,"totalLiquidityUSD":20845123456.7}]}`
+#[derive(Debug, Deserialize)]
+struct ProtocolResponse {
+    #[serde(default)]
+    tvl: Vec<TvlPoint>,
+}
+
+pub fn parse_protocol_response(body: &str) -> Result<Vec<TvlPoint>, QuoteRequestError> {
+    let response: ProtocolResponse = serde_json::from_str(body)?;
+    Ok(response.tvl)
+}
+
+/// Response of the `/v2/historicalChainTvl/{chain}` endpoint, e.g. `[{"date":1721260800,"tvl":61234567890.1}]`
+pub fn parse_chain_response(body: &str) -> Result<Vec<TvlPoint>, QuoteRequestError> {
+    Ok(serde_json::from_str(body)?)
+}
+
+/// The latest TVL of `history`, and its change in percent since the latest point at least a day older
+pub fn tvl_quote(history: &[TvlPoint], id: &str) -> Result<AssetQuoteResponse, QuoteRequestError> {
+    let latest = history
+        .last()
+        .ok_or_else(|| QuoteRequestError::MissingAsset(id.to_string()))?;
+
+    let price_change_24h = match history.iter().rev().find(|point| point.date <= latest.date - DAY_SECS) {
+        Some(previous) if !previous.tvl.is_zero() => {
+            (&latest.tvl - &previous.tvl) * BigDecimal::from(100) / &previous.tvl
+        }
+        _ => BigDecimal::zero(),
+    };
+
+    Ok(AssetQuoteResponse {
+        name: id.to_string(),
+        price: latest.tvl.clone(),
+        price_change_24h,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tvl_url() {
+        assert_eq!("https://api.llama.fi/protocol/aave", tvl_url(DEFAULT_BASE_URL, "aave"));
+        assert_eq!(
+            "https://api.llama.fi/v2/historicalChainTvl/Ethereum",
+            tvl_url(DEFAULT_BASE_URL, "chain/Ethereum")
+        );
+    }
+
+    #[test]
+    fn test_parse_protocol_response() {
+        // the latest point is the current TVL, a few hours after the last daily one
+        let body = r#"{"id":"111","name":"Aave","tvl":[
+            {"date":1721088000,"totalLiquidityUSD":19000000000},
+            {"date":1721174400,"totalLiquidityUSD":20000000000},
+            {"date":1721260800,"totalLiquidityUSD":20500000000},
+            {"date":1721275200,"totalLiquidityUSD":21000000000}
+        ]}"#;
+        let response = tvl_quote(&parse_protocol_response(body).unwrap(), "aave").unwrap();
+        assert_eq!("21000000000", response.price.to_string());
+        assert_eq!(BigDecimal::from(5), response.price_change_24h);
+
+        assert!(matches!(
+            tvl_quote(&parse_protocol_response(r#"{"name":"New"}"#).unwrap(), "new"),
+            Err(QuoteRequestError::MissingAsset(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_chain_response() {
+        let body = r#"[{"date":1721174400,"tvl":60000000000},{"date":1721260800,"tvl":59400000000.0}]"#;
+        let response = tvl_quote(&parse_chain_response(body).unwrap(), "chain/Ethereum").unwrap();
+        assert_eq!("59400000000.0", response.price.to_string());
+        assert_eq!(BigDecimal::from(-1), response.price_change_24h);
+
+        // a single point has no change yet
+        let response = tvl_quote(&parse_chain_response(r#"[{"date":1721260800,"tvl":1}]"#).unwrap(), "chain/New").unwrap();
+        assert!(response.price_change_24h.is_zero());
+    }
+}
@@ -1,11 +1,24 @@
 use std::error;
 use std::fmt;
+use reqwest::StatusCode;
+use std::sync::Arc;
+use std::time::Duration;
 
-#[derive(Debug)]
+/// Errors of the libraries are shared, so that the requests merged in a queue all get the same error
+#[derive(Clone, Debug)]
 pub enum QuoteRequestError {
-    HttpRequest(reqwest::Error),
-    JsonParse(serde_json::Error),
-    ParseBigDecimal(bigdecimal::ParseBigDecimalError),
+    HttpRequest(Arc<reqwest::Error>),
+    JsonParse(Arc<serde_json::Error>),
+    ParseBigDecimal(Arc<bigdecimal::ParseBigDecimalError>),
+    Timeout(String),
+    NotFound(String), // HTTP 404, e.g. an unknown symbol, asking again will not help
+    RateLimited(String), // HTTP 429
+    Unauthorized(String), // HTTP 401 or 403, e.g. a missing or revoked API key
+    ServerError(String), // HTTP 5xx, usually a temporary outage of the provider
+    CircuitOpen(Duration), // The provider failed repeatedly, it is not asked again for this long
+    MissingAsset(String),
+    MissingField(String),
+    InvalidField(String),
     Other(String),
 }
 
@@ -17,6 +30,23 @@ impl fmt::Display for QuoteRequestError {
             QuoteRequestError::ParseBigDecimal(ref err) => {
                 write!(f, "BigDecimal Parse Error: {}", err)
             }
+            QuoteRequestError::Timeout(ref err) => write!(f, "Timeout Error: {}", err),
+            QuoteRequestError::NotFound(ref err) => write!(f, "Not Found Error: {}", err),
+            QuoteRequestError::RateLimited(ref err) => write!(f, "Rate Limited Error: {}", err),
+            QuoteRequestError::Unauthorized(ref err) => write!(f, "Unauthorized Error: {}", err),
+            QuoteRequestError::ServerError(ref err) => write!(f, "Server Error: {}", err),
+            QuoteRequestError::CircuitOpen(ref remaining) => {
+                write!(f, "Circuit Open Error: the provider is down, retrying in {:?}", remaining)
+            }
+            QuoteRequestError::MissingAsset(ref name) => {
+                write!(f, "Missing Asset Error: {} is not in the response", name)
+            }
+            QuoteRequestError::MissingField(ref field) => {
+                write!(f, "Missing Field Error: `{}` is not in the response", field)
+            }
+            QuoteRequestError::InvalidField(ref field) => {
+                write!(f, "Invalid Field Error: `{}` has an unexpected value", field)
+            }
             QuoteRequestError::Other(ref err) => write!(f, "Other Error: {}", err),
         }
     }
@@ -25,9 +55,18 @@ impl fmt::Display for QuoteRequestError {
 impl error::Error for QuoteRequestError {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self {
-            QuoteRequestError::HttpRequest(ref err) => Some(err),
-            QuoteRequestError::JsonParse(ref err) => Some(err),
-            QuoteRequestError::ParseBigDecimal(ref err) => Some(err),
+            QuoteRequestError::HttpRequest(ref err) => Some(err.as_ref()),
+            QuoteRequestError::JsonParse(ref err) => Some(err.as_ref()),
+            QuoteRequestError::ParseBigDecimal(ref err) => Some(err.as_ref()),
+            QuoteRequestError::Timeout(_) => None,
+            QuoteRequestError::NotFound(_) => None,
+            QuoteRequestError::RateLimited(_) => None,
+            QuoteRequestError::Unauthorized(_) => None,
+            QuoteRequestError::ServerError(_) => None,
+            QuoteRequestError::CircuitOpen(_) => None,
+            QuoteRequestError::MissingAsset(_) => None,
+            QuoteRequestError::MissingField(_) => None,
+            QuoteRequestError::InvalidField(_) => None,
             QuoteRequestError::Other(_) => None,
         }
     }
@@ -35,19 +74,48 @@ impl error::Error for QuoteRequestError {
 
 impl From<reqwest::Error> for QuoteRequestError {
     fn from(err: reqwest::Error) -> QuoteRequestError {
-        QuoteRequestError::HttpRequest(err)
+        if err.is_timeout() {
+            return QuoteRequestError::Timeout(err.to_string());
+        }
+
+        match err.status() {
+            Some(StatusCode::NOT_FOUND) => QuoteRequestError::NotFound(err.to_string()),
+            Some(StatusCode::TOO_MANY_REQUESTS) => QuoteRequestError::RateLimited(err.to_string()),
+            Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => QuoteRequestError::Unauthorized(err.to_string()),
+            Some(status) if status.is_server_error() => QuoteRequestError::ServerError(err.to_string()),
+            _ => QuoteRequestError::HttpRequest(Arc::new(err)),
+        }
+    }
+}
+
+impl QuoteRequestError {
+    /// Whether asking the provider again right away may succeed. Retrying does not fix an unknown asset
+    /// or a bad API key, and makes rate limiting worse.
+    pub fn is_retryable(&self) -> bool {
+        !matches!(
+            self,
+            QuoteRequestError::NotFound(_)
+                | QuoteRequestError::RateLimited(_)
+                | QuoteRequestError::Unauthorized(_)
+                | QuoteRequestError::CircuitOpen(_)
+        )
+    }
+
+    /// Whether the error comes from the config rather than the provider, so that it will never go away by itself
+    pub fn is_permanent(&self) -> bool {
+        matches!(self, QuoteRequestError::NotFound(_) | QuoteRequestError::Unauthorized(_))
     }
 }
 
 impl From<serde_json::Error> for QuoteRequestError {
     fn from(err: serde_json::Error) -> QuoteRequestError {
-        QuoteRequestError::JsonParse(err)
+        QuoteRequestError::JsonParse(Arc::new(err))
     }
 }
 
 impl From<bigdecimal::ParseBigDecimalError> for QuoteRequestError {
     fn from(err: bigdecimal::ParseBigDecimalError) -> QuoteRequestError {
-        QuoteRequestError::ParseBigDecimal(err)
+        QuoteRequestError::ParseBigDecimal(Arc::new(err))
     }
 }
 
@@ -72,4 +140,10 @@ mod tests {
         let err = QuoteRequestError::Other("test".to_string());
         assert_eq!("Other Error: test", format!("{}", err));
     }
+
+    #[test]
+    fn test_display_missing_field_error() {
+        let err = QuoteRequestError::MissingField("usd".to_string());
+        assert_eq!("Missing Field Error: `usd` is not in the response", format!("{}", err));
+    }
 }
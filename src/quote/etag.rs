@@ -0,0 +1,146 @@
+use crate::quote::error::QuoteRequestError;
+use reqwest::header::HeaderMap;
+use reqwest::{header, RequestBuilder, StatusCode};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tracing::trace;
+
+/// The ETag and the parsed value of the last response of every URL, so that a provider supporting conditional
+/// requests answers an unchanged resource with 304 Not Modified instead of the whole body. Quiet markets then
+/// cost less of a free-tier quota, and the unchanged value is reused without parsing it again.
+pub struct EtagCache<T> {
+    entries: Mutex<HashMap<String, (String, T)>>,
+}
+
+impl<T> Default for EtagCache<T> {
+    fn default() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<T: Clone> EtagCache<T> {
+    /// Sends `request` for `url` with the ETag of its last response, parsing the body with `parse` unless the
+    /// provider answers that it did not change, in which case the last value is returned. Tells whether the value
+    /// is that unchanged one. `on_headers` sees the headers of every response, errors included.
+    pub async fn fetch(
+        &self,
+        url: &str,
+        mut request: RequestBuilder,
+        on_headers: impl FnOnce(&HeaderMap),
+        parse: impl FnOnce(&str) -> Result<T, QuoteRequestError>,
+    ) -> Result<(T, bool), QuoteRequestError> {
+        let cached = self.entries.lock().unwrap().get(url).cloned();
+        if let Some((etag, _)) = &cached {
+            request = request.header(header::IF_NONE_MATCH, etag);
+        }
+
+        let response = request.send().await?;
+        on_headers(response.headers());
+        if response.status() == StatusCode::NOT_MODIFIED {
+            // e.g. from a proxy, there is no body to parse; without the ETag the next request gets the whole value
+            let Some((_, value)) = cached else {
+                return Err(QuoteRequestError::Other("304 Not Modified without a cached response".to_string()));
+            };
+            trace!("{} did not change", url);
+            return Ok((value, true));
+        }
+
+        let response = response.error_for_status()?;
+        let etag = response
+            .headers()
+            .get(header::ETAG)
+            .and_then(|etag| etag.to_str().ok())
+            .map(str::to_string);
+        let value = parse(&response.text().await?)?;
+
+        let mut entries = self.entries.lock().unwrap();
+        match etag {
+            Some(etag) => entries.insert(url.to_string(), (etag, value.clone())),
+            None => entries.remove(url),
+        };
+        Ok((value, false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_fetch_not_modified() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/price"))
+            .and(header("if-none-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .with_priority(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/price"))
+            .respond_with(ResponseTemplate::new(200).insert_header("etag", "\"v1\"").set_body_string("42"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let http_client = reqwest::Client::new();
+        let url = format!("{}/price", server.uri());
+        let cache = EtagCache::default();
+        let parse = |body: &str| body.parse::<u32>().map_err(|e| QuoteRequestError::Other(e.to_string()));
+
+        assert_eq!((42, false), cache.fetch(&url, http_client.get(&url), |_| {}, parse).await.unwrap());
+        // answered with 304, the parser is not called again
+        let value = cache
+            .fetch(&url, http_client.get(&url), |_| {}, |_| Err(QuoteRequestError::Other("parsed".to_string())))
+            .await
+            .unwrap();
+        assert_eq!((42, true), value);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_not_modified_without_cached_response() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/price"))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let http_client = reqwest::Client::new();
+        let url = format!("{}/price", server.uri());
+        let cache = EtagCache::<u32>::default();
+
+        let err = cache
+            .fetch(&url, http_client.get(&url), |_| {}, |_| Err(QuoteRequestError::Other("parsed".to_string())))
+            .await
+            .unwrap_err();
+        assert_eq!("Other Error: 304 Not Modified without a cached response", err.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_without_etag() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/price"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("42"))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let http_client = reqwest::Client::new();
+        let url = format!("{}/price", server.uri());
+        let cache = EtagCache::default();
+        let parse = |body: &str| body.parse::<u32>().map_err(|e| QuoteRequestError::Other(e.to_string()));
+
+        assert_eq!((42, false), cache.fetch(&url, http_client.get(&url), |_| {}, parse).await.unwrap());
+        assert_eq!((42, false), cache.fetch(&url, http_client.get(&url), |_| {}, parse).await.unwrap());
+        let requests = server.received_requests().await.unwrap();
+        assert!(requests.iter().all(|request| !request.headers.contains_key("if-none-match")));
+    }
+}
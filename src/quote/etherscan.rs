@@ -0,0 +1,164 @@
+use crate::quote::api_keys::ApiKeys;
+use crate::quote::error::QuoteRequestError;
+use crate::quote::provider::QuoteProvider;
+use crate::quote::response::{AssetQuoteResponse, GasPrices};
+use async_trait::async_trait;
+use bigdecimal::{BigDecimal, Zero};
+use reqwest::header;
+use serde::Deserialize;
+use std::str::FromStr;
+use std::sync::Arc;
+
+pub const DEFAULT_BASE_URL: &str = "https://api.etherscan.io/v2/api";
+
+/// Minimum interval between two updates of a ticker, a new block is produced every 12 seconds
+pub const MIN_UPDATE_INTERVAL_SECS: u64 = 12;
+
+pub fn gas_oracle_url(base_url: &str, chain_id: &str, api_key: &str) -> String {
+    format!(
+        "{}?chainid={}&module=gastracker&action=gasoracle&apikey={}",
+        base_url, chain_id, api_key
+    )
+}
+
+/// Gas prices of an EVM chain from the gas oracle of Etherscan, the id of the asset is the chain id,
+/// e.g. `1` for Ethereum. The price of the quote is the standard gas price, in gwei.
+pub struct EtherscanGasProvider {
+    http_client: reqwest::Client,
+    base_url: String,
+    api_keys: Arc<ApiKeys>,
+}
+
+impl EtherscanGasProvider {
+    pub fn new(http_client: reqwest::Client, base_url: String, api_key: String) -> Self {
+        Self::with_api_keys(http_client, base_url, Arc::new(ApiKeys::single("Etherscan", api_key)))
+    }
+
+    pub fn with_api_keys(http_client: reqwest::Client, base_url: String, api_keys: Arc<ApiKeys>) -> Self {
+        Self {
+            http_client,
+            base_url,
+            api_keys,
+        }
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for EtherscanGasProvider {
+    fn name(&self) -> &str {
+        "Etherscan"
+    }
+
+    async fn fetch_quote(
+        &self,
+        id: &str,
+        _vs_currency: &str,
+    ) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        let body = self
+            .api_keys
+            .with_key(|api_key| async move {
+                let api_key = api_key.unwrap_or_default();
+                Ok(self
+                    .http_client
+                    .get(gas_oracle_url(&self.base_url, id, api_key.expose()))
+                    .header(header::ACCEPT, "application/json")
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    // the URL holds the API key
+                    .map_err(reqwest::Error::without_url)?
+                    .text()
+                    .await
+                    .map_err(reqwest::Error::without_url)?)
+            })
+            .await?;
+        parse_gas_oracle_response(&body, id)
+    }
+}
+
+/// Response of the gas oracle, errors are reported with a `status` of `0` and a message in `result`.
+///
+/// Example: `{"status":"1","message":"OK","result":{"SafeGasPrice":"0.5","ProposeGasPrice":"0.6","FastGasPrice":"0.7"}}`
+#[derive(Debug, Deserialize)]
+struct GasOracleResponse {
+    status: String,
+    result: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GasOracleResult {
+    safe_gas_price: String,
+    propose_gas_price: String,
+    fast_gas_price: String,
+}
+
+pub fn parse_gas_oracle_response(body: &str, chain_id: &str) -> Result<AssetQuoteResponse, QuoteRequestError> {
+    let response: GasOracleResponse = serde_json::from_str(body)?;
+
+    if response.status != "1" {
+        return Err(QuoteRequestError::Other(format!(
+            "gas oracle of chain {} returned an error: {}",
+            chain_id, response.result
+        )));
+    }
+
+    let result: GasOracleResult = serde_json::from_value(response.result)?;
+    let gas_prices = GasPrices {
+        slow: BigDecimal::from_str(&result.safe_gas_price)?,
+        standard: BigDecimal::from_str(&result.propose_gas_price)?,
+        fast: BigDecimal::from_str(&result.fast_gas_price)?,
+    };
+
+    Ok(AssetQuoteResponse {
+        name: chain_id.to_string(),
+        price: gas_prices.standard.clone(),
+        price_change_24h: BigDecimal::zero(),
+        gas_prices: Some(gas_prices),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gas_oracle_url() {
+        assert_eq!(
+            "https://api.etherscan.io/v2/api?chainid=1&module=gastracker&action=gasoracle&apikey=KEY",
+            gas_oracle_url(DEFAULT_BASE_URL, "1", "KEY")
+        );
+    }
+
+    #[test]
+    fn test_parse_gas_oracle_response() {
+        let body = r#"{"status":"1","message":"OK","result":{"LastBlock":"23000000","SafeGasPrice":"0.512","ProposeGasPrice":"0.6","FastGasPrice":"1.25","suggestBaseFee":"0.5","gasUsedRatio":"0.4"}}"#;
+        let response = parse_gas_oracle_response(body, "1").unwrap();
+        assert_eq!("0.6", response.price.to_string());
+
+        let gas_prices = response.gas_prices.unwrap();
+        assert_eq!("0.512", gas_prices.slow.to_string());
+        assert_eq!("0.6", gas_prices.standard.to_string());
+        assert_eq!("1.25", gas_prices.fast.to_string());
+    }
+
+    #[test]
+    fn test_parse_gas_oracle_response_malformed() {
+        let cases = [
+            ("", "JSON Parse Error"),
+            (r#"{"status":"0","message":"NOTOK","result":"Invalid API Key"}"#, "Other Error: gas oracle of chain 1 returned an error: \"Invalid API Key\""),
+            (r#"{"status":"1","message":"OK","result":{"SafeGasPrice":"0.5"}}"#, "JSON Parse Error"),
+            (r#"{"status":"1","message":"OK","result":{"SafeGasPrice":"x","ProposeGasPrice":"0.6","FastGasPrice":"0.7"}}"#, "BigDecimal Parse Error"),
+        ];
+
+        for (body, expected) in cases {
+            let err = parse_gas_oracle_response(body, "1").unwrap_err();
+            assert!(
+                err.to_string().starts_with(expected),
+                "body {:?}: expected {:?}, got {:?}",
+                body, expected, err.to_string()
+            );
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use crate::quote::custom_http::{select, to_decimal};
+use crate::quote::error::QuoteRequestError;
+use crate::quote::provider::QuoteProvider;
+use crate::quote::response::AssetQuoteResponse;
+use crate::secret::SecretString;
+use async_trait::async_trait;
+use bigdecimal::{BigDecimal, Zero};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// Minimum interval between two updates of a ticker, so that a slow command is not started over and over
+pub const MIN_UPDATE_INTERVAL_SECS: u64 = 10;
+pub const DEFAULT_TIMEOUT_SECS: u64 = 10;
+/// Output of a command beyond this is an error, killing the command, and its stderr is cut there
+pub const MAX_OUTPUT_BYTES: u64 = 64 * 1024;
+
+/// A command printing the price as JSON, named in `exec` and picked by the `name` of a ticker
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExecCommand {
+    pub command: Vec<String>, // Program and its arguments, run without a shell, `{vs_currency}` is replaced in the arguments
+    pub timeout_secs: Option<u64>, // How long the command may run before it is killed, defaults to 10 seconds
+    #[serde(default)]
+    pub env: BTreeMap<String, SecretString>, // Environment of the command, on top of `PATH`, nothing else of the bot is passed on
+    pub working_dir: Option<String>, // Directory the command runs in, defaults to the one of the bot
+}
+
+impl ExecCommand {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS))
+    }
+}
+
+/// Quotes printed by the commands of `exec`, the id of the asset is the name of the command
+pub struct ExecProvider {
+    commands: BTreeMap<String, ExecCommand>,
+}
+
+impl ExecProvider {
+    pub fn new(commands: BTreeMap<String, ExecCommand>) -> Self {
+        Self { commands }
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for ExecProvider {
+    fn name(&self) -> &str {
+        "Exec"
+    }
+
+    async fn fetch_quote(
+        &self,
+        id: &str,
+        vs_currency: &str,
+    ) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        let command = self
+            .commands
+            .get(id)
+            .ok_or_else(|| QuoteRequestError::NotFound(format!("no `exec` command named {}", id)))?;
+
+        let output = run_command(command, vs_currency).await?;
+        parse_exec_output(&output, id)
+    }
+}
+
+/// Runs `command` with nothing but `PATH` and its own `env` in its environment and no stdin, and returns its stdout.
+/// It is killed once it runs longer than its timeout.
+async fn run_command(command: &ExecCommand, vs_currency: &str) -> Result<String, QuoteRequestError> {
+    let (program, args) = command
+        .command
+        .split_first()
+        .ok_or_else(|| QuoteRequestError::Other("`command` is empty".to_string()))?;
+
+    let mut process = tokio::process::Command::new(program);
+    process
+        .args(args.iter().map(|arg| arg.replace("{vs_currency}", vs_currency)))
+        .env_clear()
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    // scripts starting with `#!/usr/bin/env python3` look their interpreter up in `PATH`
+    if let Some(path) = std::env::var_os("PATH") {
+        process.env("PATH", path);
+    }
+    process.envs(command.env.iter().map(|(name, value)| (name, value.expose())));
+    if let Some(working_dir) = &command.working_dir {
+        process.current_dir(working_dir);
+    }
+
+    let run = async {
+        let mut child = process
+            .spawn()
+            .map_err(|error| QuoteRequestError::Other(format!("cannot run {}: {}", program, error)))?;
+        let (mut stdout, mut stderr) = (Vec::new(), Vec::new());
+        let stdout_pipe = child.stdout.take().expect("stdout is piped");
+        let stderr_pipe = child.stderr.take().expect("stderr is piped");
+        let read_stdout = async {
+            match read_capped(stdout_pipe, &mut stdout).await {
+                Ok(false) => Ok(()),
+                Ok(true) => Err(QuoteRequestError::Other(format!(
+                    "{} printed more than {} bytes",
+                    program, MAX_OUTPUT_BYTES
+                ))),
+                Err(error) => Err(QuoteRequestError::Other(format!("cannot read the output of {}: {}", program, error))),
+            }
+        };
+        let read_stderr = async {
+            // only shown along with a failure, what could be read is enough
+            let _ = read_capped_discarding(stderr_pipe, &mut stderr).await;
+            Ok(())
+        };
+        let wait = async {
+            child
+                .wait()
+                .await
+                .map_err(|error| QuoteRequestError::Other(format!("{} did not finish: {}", program, error)))
+        };
+        // both pipes are drained at once, a command filling up one of them would block otherwise; the command is
+        // dropped, and killed, as soon as its stdout goes over the limit
+        let (_, _, status) = tokio::try_join!(read_stdout, read_stderr, wait)?;
+
+        if !status.success() {
+            return Err(QuoteRequestError::Other(format!(
+                "{} exited with {}: {}",
+                program,
+                status,
+                String::from_utf8_lossy(&stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&stdout).into_owned())
+    };
+
+    // the child is dropped, and killed, with the future once the timeout elapses
+    tokio::time::timeout(command.timeout(), run)
+        .await
+        .map_err(|_| QuoteRequestError::Timeout(format!("{} ran longer than {:?}", program, command.timeout())))?
+}
+
+/// Reads `pipe` into `output` until its end or until it goes over `MAX_OUTPUT_BYTES`, returning whether it did
+async fn read_capped(pipe: impl AsyncRead + Unpin, output: &mut Vec<u8>) -> std::io::Result<bool> {
+    pipe.take(MAX_OUTPUT_BYTES + 1).read_to_end(output).await?;
+    Ok(output.len() as u64 > MAX_OUTPUT_BYTES)
+}
+
+/// Reads `pipe` to its end, keeping its first `MAX_OUTPUT_BYTES` in `output` and discarding the rest, so that the
+/// command is never blocked on a full pipe
+async fn read_capped_discarding(mut pipe: impl AsyncRead + Unpin, output: &mut Vec<u8>) -> std::io::Result<()> {
+    (&mut pipe).take(MAX_OUTPUT_BYTES).read_to_end(output).await?;
+    tokio::io::copy(&mut pipe, &mut tokio::io::sink()).await?;
+    Ok(())
+}
+
+/// Output of a command, e.g. `{"price": "0.0000123", "change_24h": -3.2}`, the change is `0` if omitted
+pub fn parse_exec_output(output: &str, id: &str) -> Result<AssetQuoteResponse, QuoteRequestError> {
+    let output: Value = serde_json::from_str(output)?;
+    let decimal = |field: &str| match select(&output, field) {
+        Some(value) => to_decimal(value)
+            .map(Some)
+            .ok_or_else(|| QuoteRequestError::InvalidField(field.to_string())),
+        None => Ok(None),
+    };
+
+    Ok(AssetQuoteResponse {
+        name: id.to_string(),
+        price: decimal("price")?.ok_or_else(|| QuoteRequestError::MissingField("price".to_string()))?,
+        price_change_24h: decimal("change_24h")?.unwrap_or_else(BigDecimal::zero),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sh(script: &str, timeout_secs: Option<u64>) -> ExecCommand {
+        ExecCommand {
+            command: vec!["sh".to_string(), "-c".to_string(), script.to_string(), "sh".to_string(), "{vs_currency}".to_string()],
+            timeout_secs,
+            env: BTreeMap::from([("POOL".to_string(), SecretString::from("0xabc"))]),
+            working_dir: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_exec_output() {
+        let response = parse_exec_output(r#"{"price": "0.0000123", "change_24h": -3.2}"#, "pool").unwrap();
+        assert_eq!("0.0000123", response.price.to_string());
+        assert_eq!("-3.2", response.price_change_24h.to_string());
+        assert!(parse_exec_output(r#"{"price": 2}"#, "pool").unwrap().price_change_24h.is_zero());
+
+        assert!(matches!(parse_exec_output(r#"{"change_24h": 1}"#, "pool"), Err(QuoteRequestError::MissingField(_))));
+        assert!(matches!(
+            parse_exec_output(r#"{"price": "2", "change_24h": "n/a"}"#, "pool"),
+            Err(QuoteRequestError::InvalidField(_))
+        ));
+        assert!(matches!(parse_exec_output("price: 2", "pool"), Err(QuoteRequestError::JsonParse(_))));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_exec_provider() {
+        let provider = ExecProvider::new(BTreeMap::from([
+            // only `PATH` and `env` are passed on, e.g. not `HOME`
+            ("pool".to_string(), sh(r#"echo "{\"price\": \"1.5\", \"change_24h\": \"${#HOME}\", \"pool\": \"$POOL $1\"}""#, None)),
+            ("failing".to_string(), sh("echo broken >&2; exit 3", None)),
+            ("slow".to_string(), sh("sleep 10", Some(1))),
+        ]));
+
+        let response = provider.fetch_quote("pool", "usd").await.unwrap();
+        assert_eq!("1.5", response.price.to_string());
+        assert!(response.price_change_24h.is_zero());
+        let output = run_command(&provider.commands["pool"], "usd").await.unwrap();
+        assert!(output.contains(r#""pool": "0xabc usd""#), "{}", output);
+
+        let error = provider.fetch_quote("failing", "usd").await.unwrap_err();
+        assert!(error.to_string().contains("exited with exit status: 3: broken"), "{}", error);
+        assert!(matches!(provider.fetch_quote("slow", "usd").await, Err(QuoteRequestError::Timeout(_))));
+        assert!(matches!(provider.fetch_quote("other", "usd").await, Err(QuoteRequestError::NotFound(_))));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_exec_output_over_the_limit() {
+        // stopped once over the limit rather than timing out blocked on a full pipe, even if it never ends
+        let error = run_command(&sh("yes", Some(5)), "usd").await.unwrap_err();
+        assert_eq!(format!("Other Error: sh printed more than {} bytes", MAX_OUTPUT_BYTES), error.to_string());
+
+        let script = format!("head -c {} /dev/zero | tr '\\0' x >&2; exit 1", 2 * MAX_OUTPUT_BYTES);
+        let error = run_command(&sh(&script, Some(5)), "usd").await.unwrap_err();
+        assert!(error.to_string().contains("exited with exit status: 1: xxx"), "{}", error);
+    }
+}
@@ -0,0 +1,159 @@
+use crate::quote::error::QuoteRequestError;
+use crate::quote::provider::QuoteProvider;
+use crate::quote::response::AssetQuoteResponse;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use reqwest::header;
+use serde::Deserialize;
+use std::str::FromStr;
+
+pub const DEFAULT_BASE_URL: &str = "https://api.geckoterminal.com/api/v2";
+
+/// Minimum interval between two updates of a ticker, the free API allows 30 calls a minute for all tickers
+pub const MIN_UPDATE_INTERVAL_SECS: u64 = 30;
+
+/// URL of the pool with the id `<network>/<pool address>`, e.g. `eth/0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640`,
+/// `None` if the id has no network
+pub fn pool_url(base_url: &str, id: &str) -> Option<String> {
+    let (network, address) = id.split_once('/')?;
+    if network.is_empty() || address.is_empty() {
+        return None;
+    }
+    Some(format!("{}/networks/{}/pools/{}", base_url, network, address))
+}
+
+/// Prices of tokens in DEX pools from GeckoTerminal, e.g. of small tokens CoinGecko does not list. The price is the
+/// one of the base token of the pool, the first of its name, in USD.
+pub struct GeckoTerminalProvider {
+    http_client: reqwest::Client,
+    base_url: String,
+}
+
+impl GeckoTerminalProvider {
+    pub fn new(http_client: reqwest::Client, base_url: String) -> Self {
+        Self {
+            http_client,
+            base_url,
+        }
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for GeckoTerminalProvider {
+    fn name(&self) -> &str {
+        "GeckoTerminal"
+    }
+
+    async fn fetch_quote(
+        &self,
+        id: &str,
+        vs_currency: &str,
+    ) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        if !vs_currency.eq_ignore_ascii_case("usd") {
+            return Err(QuoteRequestError::Other(format!(
+                "GeckoTerminal only quotes in USD, not in {}",
+                vs_currency
+            )));
+        }
+        let url = pool_url(&self.base_url, id).ok_or_else(|| {
+            QuoteRequestError::NotFound(format!("{} is not a pool like `<network>/<pool address>`", id))
+        })?;
+
+        let body = self
+            .http_client
+            .get(url)
+            .header(header::ACCEPT, "application/json")
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        parse_pool_response(&body, id)
+    }
+}
+
+/// Response of the `/networks/{network}/pools/{address}` endpoint, only the fields in use are listed.
+///
+/// Example: `{"data":{"id":"eth_0x88e6...","type":"pool","attributes":{"name":"WETH / USDC 0.05%",
+/// "base_token_price_usd":"3412.56","price_change_percentage":{"h1":"0.12","h24":"-1.25"}}}}`
+#[derive(Debug, Deserialize)]
+struct PoolResponse {
+    data: Option<Pool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Pool {
+    attributes: PoolAttributes,
+}
+
+#[derive(Debug, Deserialize)]
+struct PoolAttributes {
+    base_token_price_usd: Option<BigDecimal>,
+    #[serde(default)]
+    price_change_percentage: PriceChangePercentage,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PriceChangePercentage {
+    h24: Option<String>,
+}
+
+pub fn parse_pool_response(body: &str, id: &str) -> Result<AssetQuoteResponse, QuoteRequestError> {
+    let response: PoolResponse = serde_json::from_str(body)?;
+    let attributes = response
+        .data
+        .ok_or_else(|| QuoteRequestError::MissingAsset(id.to_string()))?
+        .attributes;
+
+    let price = attributes
+        .base_token_price_usd
+        .ok_or_else(|| QuoteRequestError::MissingField("base_token_price_usd".to_string()))?;
+    let price_change_24h = attributes
+        .price_change_percentage
+        .h24
+        .as_deref()
+        .ok_or_else(|| QuoteRequestError::MissingField("price_change_percentage.h24".to_string()))
+        .and_then(|value| {
+            BigDecimal::from_str(value)
+                .map_err(|_| QuoteRequestError::InvalidField("price_change_percentage.h24".to_string()))
+        })?;
+
+    Ok(AssetQuoteResponse {
+        name: id.to_string(),
+        price,
+        price_change_24h,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool_url() {
+        assert_eq!(
+            Some("https://api.geckoterminal.com/api/v2/networks/eth/pools/0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640"),
+            pool_url(DEFAULT_BASE_URL, "eth/0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640").as_deref()
+        );
+        assert_eq!(None, pool_url(DEFAULT_BASE_URL, "0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640"));
+        assert_eq!(None, pool_url(DEFAULT_BASE_URL, "eth/"));
+    }
+
+    #[test]
+    fn test_parse_pool_response() {
+        let body = r#"{"data":{"id":"solana_Czfq3xZZDmsdGdUyrNLtRhGc47cXcZtLG4crryfu44zE","type":"pool","attributes":{
+            "name":"BONK / SOL","base_token_price_usd":"0.0000236512345678","quote_token_price_usd":"145.2",
+            "price_change_percentage":{"m5":"0.1","h1":"-0.4","h6":"2.1","h24":"-7.35"}}}}"#;
+        let response = parse_pool_response(body, "solana/Czfq3xZZDmsdGdUyrNLtRhGc47cXcZtLG4crryfu44zE").unwrap();
+        assert_eq!("0.0000236512345678", response.price.to_string());
+        assert_eq!("-7.35", response.price_change_24h.to_string());
+
+        let body = r#"{"data":{"attributes":{"base_token_price_usd":"1.5","price_change_percentage":{}}}}"#;
+        assert!(matches!(parse_pool_response(body, "eth/0x1"), Err(QuoteRequestError::MissingField(_))));
+        assert!(matches!(
+            parse_pool_response(r#"{"errors":[{"status":"404","title":"Not Found"}]}"#, "eth/0x1"),
+            Err(QuoteRequestError::MissingAsset(_))
+        ));
+    }
+}
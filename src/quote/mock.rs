@@ -0,0 +1,47 @@
+use crate::quote::error::QuoteRequestError;
+use crate::quote::provider::QuoteProvider;
+use crate::quote::response::AssetQuoteResponse;
+use async_trait::async_trait;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// A `QuoteProvider` returning canned results in order, for tests.
+/// Once the results run out, every call fails.
+pub struct MockProvider {
+    results: Mutex<VecDeque<Result<AssetQuoteResponse, QuoteRequestError>>>,
+    calls: AtomicUsize,
+}
+
+impl MockProvider {
+    pub fn new(results: Vec<Result<AssetQuoteResponse, QuoteRequestError>>) -> Self {
+        Self {
+            results: Mutex::new(results.into()),
+            calls: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for MockProvider {
+    fn name(&self) -> &str {
+        "Mock"
+    }
+
+    async fn fetch_quote(
+        &self,
+        _id: &str,
+        _vs_currency: &str,
+    ) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        self.results
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_else(|| Err("no more mock results".into()))
+    }
+}
@@ -0,0 +1,153 @@
+use crate::quote::error::QuoteRequestError;
+use crate::quote::provider::QuoteProvider;
+use crate::quote::response::AssetQuoteResponse;
+use crate::secret::SecretString;
+use async_trait::async_trait;
+use bigdecimal::{BigDecimal, Zero};
+use reqwest::{header, StatusCode};
+use serde::Deserialize;
+
+pub const DEFAULT_BASE_URL: &str = "https://api.opensea.io";
+
+/// Minimum interval between two updates of a ticker, floors of most collections move slowly
+pub const MIN_UPDATE_INTERVAL_SECS: u64 = 60;
+
+/// Currency floor prices are quoted in, wrapped ETH counting as ETH
+pub const FLOOR_CURRENCY: &str = "eth";
+
+pub fn collection_stats_url(base_url: &str, slug: &str) -> String {
+    format!("{}/api/v2/collections/{}/stats", base_url, slug)
+}
+
+/// Floor prices of NFT collections on OpenSea, by the slug of the collection, e.g. `pudgypenguins`, in ETH.
+/// OpenSea keeps no change of the floor, so it is always `0`.
+pub struct OpenSeaProvider {
+    http_client: reqwest::Client,
+    base_url: String,
+    api_key: Option<SecretString>,
+}
+
+impl OpenSeaProvider {
+    pub fn new(http_client: reqwest::Client, base_url: String, api_key: Option<SecretString>) -> Self {
+        Self {
+            http_client,
+            base_url,
+            api_key,
+        }
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for OpenSeaProvider {
+    fn name(&self) -> &str {
+        "OpenSea"
+    }
+
+    async fn fetch_quote(
+        &self,
+        id: &str,
+        vs_currency: &str,
+    ) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        if !vs_currency.eq_ignore_ascii_case(FLOOR_CURRENCY) {
+            return Err(QuoteRequestError::Other(format!(
+                "OpenSea only has floor prices in ETH, not in {}",
+                vs_currency
+            )));
+        }
+        let api_key = self
+            .api_key
+            .as_ref()
+            .ok_or_else(|| QuoteRequestError::Other("`opensea_api_key` is needed for OpenSea".to_string()))?;
+
+        let response = self
+            .http_client
+            .get(collection_stats_url(&self.base_url, id))
+            .header(header::ACCEPT, "application/json")
+            .header("x-api-key", api_key.expose())
+            .send()
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(QuoteRequestError::NotFound(format!("no OpenSea collection {}", id)));
+        }
+
+        let body = response.error_for_status()?.text().await?;
+        parse_collection_stats_response(&body, id)
+    }
+}
+
+/// Response of the `/api/v2/collections/{slug}/stats` endpoint, only the fields in use are listed.
+///
+/// Example: `{"total":{"volume":412345.6,"sales":51234,"num_owners":4890,"floor_price":11.82,
+/// "floor_price_symbol":"ETH"},"intervals":[{"interval":"one_day","volume":512.3,"sales":41}]}`
+#[derive(Debug, Deserialize)]
+struct CollectionStatsResponse {
+    total: CollectionTotal,
+}
+
+#[derive(Debug, Deserialize)]
+struct CollectionTotal {
+    floor_price: Option<BigDecimal>,
+    floor_price_symbol: Option<String>,
+}
+
+pub fn parse_collection_stats_response(body: &str, id: &str) -> Result<AssetQuoteResponse, QuoteRequestError> {
+    let response: CollectionStatsResponse = serde_json::from_str(body)?;
+    // a collection without any listing has a floor of 0
+    let price = response
+        .total
+        .floor_price
+        .filter(|floor_price| !floor_price.is_zero())
+        .ok_or_else(|| QuoteRequestError::MissingField("total.floor_price".to_string()))?;
+
+    match response.total.floor_price_symbol.as_deref() {
+        Some(symbol) if symbol.eq_ignore_ascii_case(FLOOR_CURRENCY) || symbol.eq_ignore_ascii_case("weth") => {}
+        symbol => {
+            return Err(QuoteRequestError::Other(format!(
+                "the floor of {} is in {}, not in ETH",
+                id,
+                symbol.unwrap_or("an unknown currency")
+            )))
+        }
+    }
+
+    Ok(AssetQuoteResponse {
+        name: id.to_string(),
+        price,
+        price_change_24h: BigDecimal::zero(),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collection_stats_url() {
+        assert_eq!(
+            "https://api.opensea.io/api/v2/collections/pudgypenguins/stats",
+            collection_stats_url(DEFAULT_BASE_URL, "pudgypenguins")
+        );
+    }
+
+    #[test]
+    fn test_parse_collection_stats_response() {
+        let body = r#"{"total":{"volume":412345.6,"sales":51234,"average_price":8.05,"num_owners":4890,
+            "market_cap":104512.3,"floor_price":11.82,"floor_price_symbol":"ETH"},
+            "intervals":[{"interval":"one_day","volume":512.3,"volume_diff":-20.1,"volume_change":-0.04,"sales":41}]}"#;
+        let response = parse_collection_stats_response(body, "pudgypenguins").unwrap();
+        assert_eq!("11.82", response.price.to_string());
+        assert!(response.price_change_24h.is_zero());
+
+        let body = r#"{"total":{"floor_price":0.0042,"floor_price_symbol":"WETH"},"intervals":[]}"#;
+        assert_eq!("0.0042", parse_collection_stats_response(body, "base-art").unwrap().price.to_string());
+
+        let body = r#"{"total":{"floor_price":0,"floor_price_symbol":"ETH"},"intervals":[]}"#;
+        assert!(matches!(parse_collection_stats_response(body, "unlisted"), Err(QuoteRequestError::MissingField(_))));
+        let body = r#"{"total":{"floor_price":150,"floor_price_symbol":"POL"},"intervals":[]}"#;
+        assert!(matches!(
+            parse_collection_stats_response(body, "polygon-art"),
+            Err(QuoteRequestError::Other(message)) if message == "the floor of polygon-art is in POL, not in ETH"
+        ));
+    }
+}
@@ -0,0 +1,197 @@
+use crate::quote::rate_limit::RateLimitQuota;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Factor the intervals are stretched by, or shrunk back by, at a time
+pub const STRETCH_FACTOR: f64 = 1.5;
+/// Longest stretch, so that a provider rate limiting for good does not stop its tickers altogether
+pub const MAX_STRETCH: f64 = 8.0;
+/// Rate limited responses within this long after a change of the stretch count as the same burst
+pub const STRETCH_COOLDOWN: Duration = Duration::from_secs(60);
+/// Time without a rate limited response after which the stretch is shrunk back a step
+pub const RELAX_AFTER: Duration = Duration::from_secs(10 * 60);
+
+/// How much the tickers quoted by a provider stretch their interval, so that an aggregate frequency the
+/// provider cannot sustain slows every ticker down proportionally rather than failing some of them every cycle.
+///
+/// The stretch grows by `STRETCH_FACTOR` on a rate limited response, at most once per `STRETCH_COOLDOWN`
+/// and up to `MAX_STRETCH`, and shrinks back by the same factor after every `RELAX_AFTER` without one.
+#[derive(Clone)]
+pub struct Pacing {
+    state: Arc<Mutex<PacingState>>,
+}
+
+struct PacingState {
+    stretch: f64,
+    changed_at: Instant,
+    budget_stretch: f64, // Set to spread the rest of a monthly budget of calls, see `BudgetedProvider`
+    request_spacing: Duration, // Least time between requests for the reported quota to last until it resets
+    next_request_at: Instant,
+}
+
+impl Default for Pacing {
+    fn default() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(PacingState {
+                stretch: 1.0,
+                changed_at: Instant::now(),
+                budget_stretch: 1.0,
+                request_spacing: Duration::ZERO,
+                next_request_at: Instant::now(),
+            })),
+        }
+    }
+}
+
+impl Pacing {
+    /// Factor the intervals of the tickers are stretched by, 1 while the provider keeps up and its budget lasts
+    pub fn stretch(&self) -> f64 {
+        let state = self.state.lock().unwrap();
+        state.stretch.max(state.budget_stretch)
+    }
+
+    /// Sets the stretch needed to stay within the budget of the provider, returning true if it changed by more
+    /// than a rounding
+    pub fn set_budget_stretch(&self, stretch: f64) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if (stretch - state.budget_stretch).abs() < 0.01 {
+            return false;
+        }
+        state.budget_stretch = stretch;
+        true
+    }
+
+    /// Records a rate limited response, returning the new stretch if it grew
+    pub fn record_rate_limited(&self) -> Option<f64> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        if state.stretch > 1.0 && now.duration_since(state.changed_at) < STRETCH_COOLDOWN {
+            return None;
+        }
+
+        let stretch = (state.stretch * STRETCH_FACTOR).min(MAX_STRETCH);
+        state.changed_at = now;
+        if stretch == state.stretch {
+            return None;
+        }
+        state.stretch = stretch;
+        Some(stretch)
+    }
+
+    /// Spaces the next requests so that the `quota` reported by the provider lasts until it resets, holding
+    /// them all until then if none is left
+    pub fn record_quota(&self, quota: RateLimitQuota) {
+        let mut state = self.state.lock().unwrap();
+        if quota.remaining == 0 {
+            state.next_request_at = state.next_request_at.max(Instant::now() + quota.reset_after);
+            // the first response after the reset reports the new quota
+            state.request_spacing = Duration::ZERO;
+            return;
+        }
+        state.request_spacing = quota.reset_after / quota.remaining.min(u32::MAX as u64) as u32;
+    }
+
+    /// Takes the next free slot for a request and waits for it, right away while the quota is plenty
+    pub async fn wait_for_request_slot(&self) {
+        let slot = {
+            let mut state = self.state.lock().unwrap();
+            let slot = state.next_request_at.max(Instant::now());
+            state.next_request_at = slot + state.request_spacing;
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+
+    /// Records a successful response, returning the new stretch if it shrank
+    pub fn record_success(&self) -> Option<f64> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        if state.stretch <= 1.0 || now.duration_since(state.changed_at) < RELAX_AFTER {
+            return None;
+        }
+
+        state.stretch = (state.stretch / STRETCH_FACTOR).max(1.0);
+        state.changed_at = now;
+        Some(state.stretch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stretch_and_relax() {
+        let pacing = Pacing::default();
+        assert_eq!(None, pacing.record_success());
+
+        assert_eq!(Some(1.5), pacing.record_rate_limited());
+        // the same burst
+        assert_eq!(None, pacing.record_rate_limited());
+        tokio::time::advance(STRETCH_COOLDOWN).await;
+        assert_eq!(Some(2.25), pacing.record_rate_limited());
+        assert_eq!(2.25, pacing.stretch());
+
+        tokio::time::advance(RELAX_AFTER - Duration::from_secs(1)).await;
+        assert_eq!(None, pacing.record_success());
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert_eq!(Some(1.5), pacing.record_success());
+        tokio::time::advance(RELAX_AFTER).await;
+        assert_eq!(Some(1.0), pacing.record_success());
+        assert_eq!(None, pacing.record_success());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_max_stretch() {
+        let pacing = Pacing::default();
+        for _ in 0..10 {
+            pacing.record_rate_limited();
+            tokio::time::advance(STRETCH_COOLDOWN).await;
+        }
+        assert_eq!(MAX_STRETCH, pacing.stretch());
+        assert_eq!(None, pacing.record_rate_limited());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_request_slots() {
+        let pacing = Pacing::default();
+        let started_at = Instant::now();
+        pacing.wait_for_request_slot().await;
+        pacing.wait_for_request_slot().await;
+        assert_eq!(Duration::ZERO, started_at.elapsed());
+
+        // 10 requests left for 30 seconds
+        pacing.record_quota(RateLimitQuota {
+            remaining: 10,
+            reset_after: Duration::from_secs(30),
+        });
+        pacing.wait_for_request_slot().await;
+        pacing.wait_for_request_slot().await;
+        assert_eq!(Duration::from_secs(3), started_at.elapsed());
+
+        // none left, held until the reset
+        pacing.record_quota(RateLimitQuota {
+            remaining: 0,
+            reset_after: Duration::from_secs(20),
+        });
+        pacing.wait_for_request_slot().await;
+        assert_eq!(Duration::from_secs(23), started_at.elapsed());
+        pacing.wait_for_request_slot().await;
+        assert_eq!(Duration::from_secs(23), started_at.elapsed());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_budget_stretch() {
+        let pacing = Pacing::default();
+        assert!(pacing.set_budget_stretch(2.0));
+        assert!(!pacing.set_budget_stretch(2.001));
+        assert_eq!(2.0, pacing.stretch());
+
+        // the larger of both applies
+        pacing.record_rate_limited();
+        assert_eq!(2.0, pacing.stretch());
+        assert!(pacing.set_budget_stretch(1.0));
+        assert_eq!(1.5, pacing.stretch());
+    }
+}
@@ -0,0 +1,25 @@
+use crate::quote::error::QuoteRequestError;
+use crate::quote::rate_limit::RateLimitQuota;
+use crate::quote::response::AssetQuoteResponse;
+use async_trait::async_trait;
+
+/// A source of asset quotes, e.g. the CoinGecko API.
+///
+/// Implementations make a single attempt per call, retrying is up to the caller.
+#[async_trait]
+pub trait QuoteProvider: Send + Sync {
+    /// Name of the provider, used in log messages.
+    fn name(&self) -> &str;
+
+    async fn fetch_quote(
+        &self,
+        id: &str,
+        vs_currency: &str,
+    ) -> Result<AssetQuoteResponse, QuoteRequestError>;
+
+    /// The quota reported with the last response since the previous call, for providers sending rate limit
+    /// headers. Wrappers pass it through.
+    fn take_rate_limit_quota(&self) -> Option<RateLimitQuota> {
+        None
+    }
+}
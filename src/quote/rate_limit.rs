@@ -0,0 +1,108 @@
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Values of `x-ratelimit-reset` above this are a Unix timestamp rather than seconds until the reset
+const RESET_TIMESTAMP_THRESHOLD: u64 = 1_000_000_000;
+
+/// Requests a provider still accepts until its rate limit window resets, as reported in its response headers
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimitQuota {
+    pub remaining: u64,
+    pub reset_after: Duration,
+}
+
+/// Reads the quota from `x-ratelimit-remaining` and `x-ratelimit-reset`, in seconds or as a Unix timestamp, or
+/// from the `Retry-After` seconds of a rate limited response, which leaves none until then. `None` if the
+/// provider did not send them.
+pub fn parse_rate_limit_headers(headers: &HeaderMap, now_unix: u64) -> Option<RateLimitQuota> {
+    let number = |name: &str| headers.get(name)?.to_str().ok()?.trim().parse::<u64>().ok();
+
+    if let Some(retry_after) = number(RETRY_AFTER.as_str()) {
+        return Some(RateLimitQuota {
+            remaining: 0,
+            reset_after: Duration::from_secs(retry_after),
+        });
+    }
+
+    let remaining = number("x-ratelimit-remaining")?;
+    let reset = number("x-ratelimit-reset")?;
+    let reset_after = match reset {
+        timestamp if timestamp > RESET_TIMESTAMP_THRESHOLD => timestamp.saturating_sub(now_unix),
+        secs => secs,
+    };
+    Some(RateLimitQuota {
+        remaining,
+        reset_after: Duration::from_secs(reset_after),
+    })
+}
+
+/// The last quota a provider reported, kept until the consumer takes it, see `QuoteProvider::take_rate_limit_quota`
+#[derive(Debug, Default)]
+pub struct LastQuota(Mutex<Option<RateLimitQuota>>);
+
+impl LastQuota {
+    /// Keeps the quota in `headers`, if any
+    pub fn record(&self, headers: &HeaderMap) {
+        let now_unix = chrono::Utc::now().timestamp().max(0) as u64;
+        if let Some(quota) = parse_rate_limit_headers(headers, now_unix) {
+            *self.0.lock().unwrap() = Some(quota);
+        }
+    }
+
+    pub fn take(&self) -> Option<RateLimitQuota> {
+        self.0.lock().unwrap().take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, value.parse().unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_parse_rate_limit_headers() {
+        let now_unix = 1_700_000_000;
+        assert_eq!(None, parse_rate_limit_headers(&HeaderMap::new(), now_unix));
+        assert_eq!(
+            None,
+            parse_rate_limit_headers(&headers(&[("x-ratelimit-remaining", "12")]), now_unix)
+        );
+
+        let quota = RateLimitQuota {
+            remaining: 12,
+            reset_after: Duration::from_secs(30),
+        };
+        assert_eq!(
+            Some(quota),
+            parse_rate_limit_headers(
+                &headers(&[("x-ratelimit-remaining", "12"), ("x-ratelimit-reset", "30")]),
+                now_unix
+            )
+        );
+        assert_eq!(
+            Some(quota),
+            parse_rate_limit_headers(
+                &headers(&[("x-ratelimit-remaining", "12"), ("x-ratelimit-reset", "1700000030")]),
+                now_unix
+            )
+        );
+        assert_eq!(
+            Some(RateLimitQuota {
+                remaining: 0,
+                reset_after: Duration::from_secs(45),
+            }),
+            parse_rate_limit_headers(
+                &headers(&[("retry-after", "45"), ("x-ratelimit-remaining", "12"), ("x-ratelimit-reset", "30")]),
+                now_unix
+            )
+        );
+    }
+}
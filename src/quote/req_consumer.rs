@@ -1,161 +1,350 @@
 use crate::quote::error::QuoteRequestError;
-use crate::quote::request::AssetQuoteRequest;
+use crate::quote::pacing::Pacing;
+use crate::quote::provider::QuoteProvider;
+use crate::quote::request_queue::QuoteRequestReceiver;
 use crate::quote::response::AssetQuoteResponse;
-use bigdecimal::BigDecimal;
-use reqwest::header;
-use tracing::warn;
-use std::str::FromStr;
+use crate::shutdown::StopSignal;
+use tracing::{info, warn};
+use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::mpsc::UnboundedReceiver;
-use tokio::time::sleep;
+use tokio::sync::Semaphore;
+use tokio::time::{sleep, timeout};
 use tracing::instrument;
 use tracing::debug;
 
-#[instrument(skip(job_receiver, api_key))]
-pub async fn consume_crypto_price_requests(
-    mut job_receiver: UnboundedReceiver<AssetQuoteRequest>,
-    api_key: String,
+const MAX_ATTEMPTS: u32 = 3;
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Consumes quote requests for `provider`, running at most `max_concurrency` requests at a time
+/// so that a slow response for one ticker doesn't hold up the others. Providers reporting their rate limit
+/// quota get their requests spaced by it, see `Pacing::record_quota`; the wait counts toward `deadline`.
+#[instrument(skip_all, fields(provider = provider.name()))]
+pub async fn consume_quote_requests(
+    mut job_receiver: QuoteRequestReceiver,
+    provider: Arc<dyn QuoteProvider>,
+    deadline: Duration,
+    max_concurrency: usize,
+    mut stop_signal: StopSignal,
 ) {
-    macro_rules! sleep_then_continue {
-        ($counter:expr) => {
-            $counter -= 1;
-            sleep(Duration::from_secs(1)).await;
-            continue;
+    let semaphore = Arc::new(Semaphore::new(max_concurrency));
+
+    loop {
+        // keep the request in the queue until it can run, so that later requests for the asset join it
+        let permit = match semaphore.clone().acquire_owned().await {
+            Ok(permit) => permit,
+            Err(error) => {
+                tracing::error!("Error acquiring quote request permit: {}", error);
+                break;
+            }
         };
-    }
 
-    while let Some(req) = job_receiver.recv().await {
-        let url: String = format!("https://api.coingecko.com/api/v3/simple/price?ids={}&vs_currencies={}&include_24hr_change=true", &req.name, &req.vs_currency);
-        let mut retry_count = 3;
-        let mut result = AssetQuoteResponse::dummy();
-        let mut err: QuoteRequestError = QuoteRequestError::Other("No Error".to_string());
-        let http_client = reqwest::Client::new();
-
-        while retry_count > 0 {
-            debug!(
-                "Consumer sending request for {} to CoinGecko API, retry count: {}",
-                &req.name, retry_count
-            );
-
-            let mut http_req_build = http_client
-                .get(&url)
-                .header(header::ACCEPT, "application/json");
-
-            if api_key.len() > 0 {
-                http_req_build = http_req_build.header("x-cg-demo-api-key", &api_key);
+        let job = tokio::select! {
+            job = job_receiver.recv() => match job {
+                Some(job) => job,
+                None => break,
+            },
+            _ = stop_signal.stopped() => {
+                debug!("Stop signal received, quit consuming quote requests");
+                break;
             }
+        };
 
-            let response = match http_req_build.send().await {
-                Ok(response) => response,
-                Err(e) => {
+        let provider = provider.clone();
+        let pacing = job_receiver.pacing().clone();
+
+        tokio::spawn(async move {
+            let _permit = permit;
+
+            let result = match timeout(
+                deadline,
+                fetch_with_retry(provider.as_ref(), &pacing, &job.name, &job.vs_currency),
+            )
+            .await
+            {
+                Ok(result) => result,
+                Err(_) => {
                     warn!(
-                        "Error calling CoinGecko API to get price for {}: {}, retrying...",
-                        &req.name, e
+                        "Getting price for {} from {} API did not finish within {:?}",
+                        &job.name, provider.name(), deadline
                     );
-                    err = e.into();
-                    sleep_then_continue!(retry_count);
+                    Err(QuoteRequestError::Timeout(format!(
+                        "no response within the deadline of {:?}",
+                        deadline
+                    )))
                 }
             };
 
-            // example response
-            // {"bitcoin":{"usd":65761,"usd_24h_change":1.8841205093585678}}
-            let price_json: serde_json::Value =
-                match serde_json::from_str(response.text().await.unwrap().as_str()) {
-                    Ok(json) => json,
-                    Err(e) => {
-                        tracing::error!(
-                            "Error parsing JSON response for {} using CoinGecko API: {}",
-                            req.name, e
+            match &result {
+                Err(QuoteRequestError::RateLimited(_)) => {
+                    if let Some(stretch) = pacing.record_rate_limited() {
+                        warn!(
+                            histogram.interval_stretch = stretch,
+                            "{} API is rate limiting, stretching the interval of its tickers {}x",
+                            provider.name(),
+                            stretch
                         );
-                        err = e.into();
-                        sleep_then_continue!(retry_count);
                     }
-                };
+                }
+                Ok(_) => {
+                    if let Some(stretch) = pacing.record_success() {
+                        info!(
+                            histogram.interval_stretch = stretch,
+                            "{} API stopped rate limiting, stretching the interval of its tickers {}x only",
+                            provider.name(),
+                            stretch
+                        );
+                    }
+                }
+                Err(_) => {}
+            }
 
-            let price_json = price_json.as_object().unwrap();
+            job.respond(result);
+        });
+    }
+}
 
-            if !price_json.contains_key(&req.name) || !price_json[&req.name].is_object() {
-                warn!(
-                    "Error parsing JSON response for {} using CoinGecko API: {}",
-                    &req.name, "missing id of the crypto, or is not an object"
+async fn fetch_with_retry(
+    provider: &dyn QuoteProvider,
+    pacing: &Pacing,
+    name: &str,
+    vs_currency: &str,
+) -> Result<AssetQuoteResponse, QuoteRequestError> {
+    let mut attempt = 1;
+
+    loop {
+        pacing.wait_for_request_slot().await;
+        debug!(
+            "Consumer sending request for {} to {} API, attempt: {}",
+            name, provider.name(), attempt
+        );
+
+        let result = provider.fetch_quote(name, vs_currency).await;
+        if let Some(quota) = provider.take_rate_limit_quota() {
+            if quota.remaining == 0 {
+                info!(
+                    "{} API has no requests left for {:?}, holding its requests until then",
+                    provider.name(),
+                    quota.reset_after
                 );
-                err = "missing id of the API response, or is not an object".into();
-                sleep_then_continue!(retry_count);
             }
+            pacing.record_quota(quota);
+        }
 
-            let price_json_target_symbol = price_json[&req.name].as_object().unwrap();
-
-            if !price_json_target_symbol.contains_key("usd")
-                || !price_json_target_symbol.contains_key("usd_24h_change")
-            {
+        match result {
+            Ok(result) => return Ok(result),
+            Err(e) if attempt < MAX_ATTEMPTS && e.is_retryable() => {
                 warn!(
-                    "Error parsing JSON response for {} using CoinGecko API: {}",
-                    &req.name, "missing usd and/or usd_24h_change field(s)"
+                    "Error getting price for {} using {} API: {}, retrying...",
+                    name, provider.name(), e
                 );
-                err = "missing `usd` and/or `usd_24h_change` field(s) in the API response".into();
-                sleep_then_continue!(retry_count);
             }
+            Err(e) => return Err(e),
+        }
 
-            let price_usd = match price_json_target_symbol["usd"].as_number() {
-                Some(value) => value,
-                None => {
-                    warn!(
-                        "Error parsing USD price for {} using CoinGecko API: {}",
-                        &req.name, "missing field"
-                    );
-                    err = "cannot parse USD price as Number from the API response".into();
-                    sleep_then_continue!(retry_count);
-                }
-            };
-
-            let price_usd: BigDecimal = match BigDecimal::from_str(price_usd.as_str()) {
-                Ok(value) => value,
-                Err(error) => {
-                    warn!(
-                        "Error parsing USD price as BigDecimal for {} using CoinGecko API: {}",
-                        &req.name, error
-                    );
-                    err = error.into();
-                    sleep_then_continue!(retry_count);
-                }
-            };
+        attempt += 1;
+        sleep(RETRY_DELAY).await;
+    }
+}
 
-            let price_change_24h = match price_json_target_symbol["usd_24h_change"].as_f64() {
-                Some(value) => value,
-                None => {
-                    warn!(
-                        "Error parsing 24h change for {} using CoinGecko API: {}",
-                        &req.name, "missing field"
-                    );
-                    err = "cannot parse 24h change as f64 from the API response".into();
-                    sleep_then_continue!(retry_count);
-                }
-            };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quote::coingecko::CoinGeckoProvider;
+    use crate::quote::mock::MockProvider;
+    use crate::quote::request::AssetQuoteRequest;
+    use crate::quote::request_queue::quote_request_queue;
+    use crate::shutdown::stop_channel;
+    use bigdecimal::BigDecimal;
+    use std::str::FromStr;
+    use tokio::sync::mpsc;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
-            result = AssetQuoteResponse {
-                name: req.name.clone(),
-                price_usd,
-                price_change_24h,
-            };
-            break;
+    fn quote(price: &str) -> AssetQuoteResponse {
+        AssetQuoteResponse {
+            name: "bitcoin".to_string(),
+            price: BigDecimal::from_str(price).unwrap(),
+            price_change_24h: BigDecimal::from_str("1.5").unwrap(),
+            ..Default::default()
         }
+    }
 
-        if retry_count <= 0 {
-            let _ = match req.resp_sender.send(Err(err)) {
-                Ok(_) => {}
-                Err(error) => tracing::error!(
-                    "Error sending response to channel for {}: {}",
-                    &req.name, error
-                ),
-            };
-        } else {
-            let _ = match req.resp_sender.send(Ok(result)) {
-                Ok(_) => {}
-                Err(error) => tracing::error!(
-                    "Error sending response to channel for {}: {}",
-                    &req.name, error
-                ),
-            };
-        }
+    async fn request_quote(
+        provider: Arc<dyn QuoteProvider>,
+    ) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        let (job_sender, job_receiver) = quote_request_queue(1);
+        let (resp_sender, mut resp_receiver) = mpsc::unbounded_channel();
+        tokio::spawn(consume_quote_requests(
+            job_receiver,
+            provider,
+            Duration::from_secs(30),
+            2,
+            stop_channel().1,
+        ));
+
+        job_sender
+            .send(AssetQuoteRequest {
+                name: "bitcoin".to_string(),
+                vs_currency: "usd".to_string(),
+                resp_sender,
+            })
+            .await
+            .unwrap();
+
+        resp_receiver.recv().await.unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_until_success() {
+        let provider = Arc::new(MockProvider::new(vec![
+            Err("first".into()),
+            Err("second".into()),
+            Ok(quote("65761.123")),
+        ]));
+
+        let response = request_quote(provider.clone()).await.unwrap();
+        assert_eq!("65761.123", response.price.to_string());
+        assert_eq!(3, provider.calls());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_error_propagated_after_last_attempt() {
+        let provider = Arc::new(MockProvider::new(vec![
+            Err("first".into()),
+            Err("second".into()),
+            Err("third".into()),
+            Ok(quote("65761.123")),
+        ]));
+
+        let err = request_quote(provider.clone()).await.unwrap_err();
+        assert_eq!("Other Error: third", err.to_string());
+        assert_eq!(3, provider.calls());
+    }
+
+    #[tokio::test]
+    async fn test_coingecko_end_to_end() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/simple/price"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/simple/price"))
+            .and(query_param("ids", "bitcoin"))
+            .and(query_param("vs_currencies", "usd"))
+            .and(header("x-cg-demo-api-key", "CG-KEY"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"bitcoin":{"usd":65761.456789,"usd_24h_change":-1.2345}}"#,
+            ))
+            .mount(&server)
+            .await;
+
+        let provider = Arc::new(CoinGeckoProvider::new(
+            reqwest::Client::new(),
+            server.uri(),
+            "CG-KEY".to_string(),
+        ));
+
+        let response = request_quote(provider).await.unwrap();
+        assert_eq!("65761.46", crate::format::format_price(&response.price, 2, &Default::default()));
+        assert_eq!("-1.23%", crate::format::format_price_change(&response.price_change_24h, 2, None));
+    }
+
+    #[tokio::test]
+    async fn test_coingecko_http_error_propagated() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/simple/price"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(MAX_ATTEMPTS as u64)
+            .mount(&server)
+            .await;
+
+        let provider = Arc::new(CoinGeckoProvider::new(
+            reqwest::Client::new(),
+            server.uri(),
+            String::new(),
+        ));
+
+        let err = request_quote(provider).await.unwrap_err();
+        assert!(matches!(err, QuoteRequestError::ServerError(_)), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_coingecko_retry_after_held() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/simple/price"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "2"))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/simple/price"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("x-ratelimit-remaining", "29")
+                    .insert_header("x-ratelimit-reset", "60")
+                    .set_body_string(r#"{"bitcoin":{"usd":65761.456789,"usd_24h_change":-1.2345}}"#),
+            )
+            .mount(&server)
+            .await;
+
+        let provider = Arc::new(CoinGeckoProvider::new(
+            reqwest::Client::new(),
+            server.uri(),
+            String::new(),
+        ));
+
+        let (job_sender, job_receiver) = quote_request_queue(1);
+        tokio::spawn(consume_quote_requests(
+            job_receiver,
+            provider.clone(),
+            Duration::from_secs(30),
+            2,
+            stop_channel().1,
+        ));
+        let request = || async {
+            let (resp_sender, resp_receiver) = mpsc::unbounded_channel();
+            job_sender
+                .send(AssetQuoteRequest {
+                    name: "bitcoin".to_string(),
+                    vs_currency: "usd".to_string(),
+                    resp_sender,
+                })
+                .await
+                .unwrap();
+            resp_receiver
+        };
+
+        let started_at = tokio::time::Instant::now();
+        let err = request().await.recv().await.unwrap().unwrap_err();
+        assert!(matches!(err, QuoteRequestError::RateLimited(_)), "{}", err);
+        // the next request waits for the end of the rate limit
+        assert!(request().await.recv().await.unwrap().is_ok());
+        assert!(started_at.elapsed() >= Duration::from_secs(2));
+        assert_eq!(None, provider.take_rate_limit_quota(), "taken by the consumer");
+    }
+
+    #[tokio::test]
+    async fn test_not_found_not_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/simple/price"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = Arc::new(CoinGeckoProvider::new(
+            reqwest::Client::new(),
+            server.uri(),
+            String::new(),
+        ));
+
+        let err = request_quote(provider).await.unwrap_err();
+        assert!(matches!(err, QuoteRequestError::NotFound(_)), "{}", err);
     }
 }
@@ -0,0 +1,266 @@
+use crate::quote::error::QuoteRequestError;
+use crate::quote::pacing::Pacing;
+use crate::quote::request::AssetQuoteRequest;
+use crate::quote::response::AssetQuoteResponse;
+use std::collections::HashMap;
+use std::result;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::UnboundedSender;
+
+type QuoteRequestKey = (String, String);
+type RespSender = UnboundedSender<result::Result<AssetQuoteResponse, QuoteRequestError>>;
+
+/// Bounded queue of quote requests for one provider. A request for an asset that is already waiting
+/// in the queue joins it instead of taking another slot, so the waiting tickers all get the quote
+/// fetched once the request is picked up, rather than a burst of requests for the same asset.
+/// A request for another asset waits for a free slot while the queue is full.
+///
+/// Both ends share the `Pacing` of the provider: the consumer records its responses, the tickers stretch
+/// their interval by it.
+pub fn quote_request_queue(capacity: usize) -> (QuoteRequestSender, QuoteRequestReceiver) {
+    let (sender, receiver) = mpsc::channel(capacity.max(1));
+    let pending = Arc::new(Mutex::new(HashMap::new()));
+    let pacing = Pacing::default();
+
+    (
+        QuoteRequestSender {
+            sender,
+            pending: pending.clone(),
+            pacing: pacing.clone(),
+            wait_timeout: None,
+        },
+        QuoteRequestReceiver {
+            receiver,
+            pending,
+            pacing,
+        },
+    )
+}
+
+#[derive(Clone)]
+pub struct QuoteRequestSender {
+    sender: mpsc::Sender<QuoteRequestKey>,
+    pending: Arc<Mutex<HashMap<QuoteRequestKey, Vec<RespSender>>>>,
+    pacing: Pacing,
+    wait_timeout: Option<Duration>, // Longest wait for a free slot in a full queue, unbounded if `None`
+}
+
+pub struct QuoteRequestReceiver {
+    receiver: mpsc::Receiver<QuoteRequestKey>,
+    pending: Arc<Mutex<HashMap<QuoteRequestKey, Vec<RespSender>>>>,
+    pacing: Pacing,
+}
+
+/// Requests for the same asset merged in the queue, answered with a single quote
+#[derive(Debug)]
+pub struct QuoteJob {
+    pub name: String,
+    pub vs_currency: String,
+    resp_senders: Vec<RespSender>,
+}
+
+impl QuoteRequestSender {
+    pub fn pacing(&self) -> &Pacing {
+        &self.pacing
+    }
+
+    /// Gives up waiting for a free slot in a full queue after `wait_timeout`, e.g. the deadline of a request
+    pub fn wait_timeout(mut self, wait_timeout: Duration) -> Self {
+        self.wait_timeout = Some(wait_timeout);
+        self
+    }
+
+    /// Queues `request`, waiting for a free slot if the queue is full. Fails if the queue is closed or stays full
+    /// for longer than `wait_timeout`, along with the requests for the same asset that joined it meanwhile.
+    pub async fn send(&self, request: AssetQuoteRequest) -> result::Result<(), QuoteRequestError> {
+        let key = (request.name, request.vs_currency);
+        {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(resp_senders) = pending.get_mut(&key) {
+                resp_senders.push(request.resp_sender);
+                return Ok(());
+            }
+
+            // taken before waiting, so that requests for the same asset arriving meanwhile join this one
+            pending.insert(key.clone(), vec![request.resp_sender]);
+        }
+
+        let reserved = match self.wait_timeout {
+            Some(wait_timeout) => tokio::time::timeout(wait_timeout, self.sender.reserve()).await,
+            None => Ok(self.sender.reserve().await),
+        };
+        let error = match reserved {
+            Ok(Ok(permit)) => {
+                permit.send(key);
+                return Ok(());
+            }
+            Ok(Err(_)) => QuoteRequestError::Other(format!("request queue is closed, dropped the request for {}", key.0)),
+            Err(_) => QuoteRequestError::Timeout(format!("request queue stayed full, dropped the request for {}", key.0)),
+        };
+
+        let resp_senders = self
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&key)
+            .unwrap_or_default();
+        for resp_sender in resp_senders {
+            let _ = resp_sender.send(Err(error.clone()));
+        }
+        Err(error)
+    }
+}
+
+impl QuoteRequestReceiver {
+    pub fn pacing(&self) -> &Pacing {
+        &self.pacing
+    }
+
+    pub async fn recv(&mut self) -> Option<QuoteJob> {
+        loop {
+            let key = self.receiver.recv().await?;
+            let resp_senders = self
+                .pending
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(&key)
+                .unwrap_or_default();
+
+            // every ticker waiting for it has gone away meanwhile
+            if resp_senders.iter().all(|resp_sender| resp_sender.is_closed()) {
+                continue;
+            }
+
+            let (name, vs_currency) = key;
+            return Some(QuoteJob {
+                name,
+                vs_currency,
+                resp_senders,
+            });
+        }
+    }
+}
+
+impl QuoteJob {
+    /// Sends `result` to every request merged into the job, errors included, so that each ticker reacts to
+    /// e.g. an unknown asset the same way
+    pub fn respond(self, result: result::Result<AssetQuoteResponse, QuoteRequestError>) {
+        for resp_sender in self.resp_senders {
+            if resp_sender.send(result.clone()).is_err() {
+                tracing::debug!("Requester of {} is gone before the response", self.name);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::BigDecimal;
+
+    fn request(name: &str) -> (AssetQuoteRequest, mpsc::UnboundedReceiver<result::Result<AssetQuoteResponse, QuoteRequestError>>) {
+        let (resp_sender, resp_receiver) = mpsc::unbounded_channel();
+        (
+            AssetQuoteRequest {
+                name: name.to_string(),
+                vs_currency: "usd".to_string(),
+                resp_sender,
+            },
+            resp_receiver,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_requests_for_same_asset_are_merged() {
+        let (sender, mut receiver) = quote_request_queue(2);
+        let (first, mut first_resp) = request("bitcoin");
+        let (second, mut second_resp) = request("bitcoin");
+        let (third, _third_resp) = request("ethereum");
+        sender.send(first).await.unwrap();
+        sender.send(second).await.unwrap();
+        sender.send(third).await.unwrap();
+
+        let job = receiver.recv().await.unwrap();
+        assert_eq!("bitcoin", job.name);
+        job.respond(Ok(AssetQuoteResponse {
+            name: "bitcoin".to_string(),
+            price: BigDecimal::from(65000),
+            price_change_24h: BigDecimal::from(1),
+            ..Default::default()
+        }));
+        assert_eq!(BigDecimal::from(65000), first_resp.recv().await.unwrap().unwrap().price);
+        assert_eq!(BigDecimal::from(65000), second_resp.recv().await.unwrap().unwrap().price);
+
+        let job = receiver.recv().await.unwrap();
+        assert_eq!("ethereum", job.name);
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_waits_for_a_slot() {
+        let (sender, mut receiver) = quote_request_queue(1);
+        let (bitcoin, _bitcoin_resp) = request("bitcoin");
+        sender.send(bitcoin).await.unwrap();
+
+        let (ethereum, _ethereum_resp) = request("ethereum");
+        let waiting = tokio::spawn({
+            let sender = sender.clone();
+            async move { sender.send(ethereum).await }
+        });
+        // a request for the waiting asset joins it rather than waiting as well
+        let (joined, mut joined_resp) = request("ethereum");
+        tokio::task::yield_now().await;
+        sender.send(joined).await.unwrap();
+
+        assert_eq!("bitcoin", receiver.recv().await.unwrap().name);
+        waiting.await.unwrap().unwrap();
+        let job = receiver.recv().await.unwrap();
+        assert_eq!(("ethereum", 2), (job.name.as_str(), job.resp_senders.len()));
+        job.respond(Err(QuoteRequestError::Timeout("slow".to_string())));
+        assert!(matches!(joined_resp.recv().await.unwrap(), Err(QuoteRequestError::Timeout(_))));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_full_queue_wait_timeout() {
+        let (sender, _receiver) = quote_request_queue(1);
+        let sender = sender.wait_timeout(Duration::from_secs(30));
+        sender.send(request("bitcoin").0).await.unwrap();
+
+        let err = sender.send(request("ethereum").0).await.unwrap_err();
+        assert!(err.to_string().starts_with("Timeout Error: request queue stayed full"), "{}", err);
+        // the asset is not left waiting in the queue
+        assert!(!sender.pending.lock().unwrap().contains_key(&("ethereum".to_string(), "usd".to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_merged_requests_get_the_same_error() {
+        let (sender, mut receiver) = quote_request_queue(1);
+        let (first, mut first_resp) = request("delisted");
+        let (second, mut second_resp) = request("delisted");
+        sender.send(first).await.unwrap();
+        sender.send(second).await.unwrap();
+
+        let job = receiver.recv().await.unwrap();
+        job.respond(Err(QuoteRequestError::MissingAsset("delisted".to_string())));
+        for resp in [&mut first_resp, &mut second_resp] {
+            assert!(matches!(
+                resp.recv().await.unwrap(),
+                Err(QuoteRequestError::MissingAsset(name)) if name == "delisted"
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_requests_are_skipped() {
+        let (sender, mut receiver) = quote_request_queue(2);
+        sender.send(request("bitcoin").0).await.unwrap();
+        let (ethereum, _ethereum_resp) = request("ethereum");
+        sender.send(ethereum).await.unwrap();
+
+        assert_eq!("ethereum", receiver.recv().await.unwrap().name);
+
+        drop(sender);
+        assert!(receiver.recv().await.is_none());
+    }
+}
@@ -1,19 +1,55 @@
+use crate::quote::binance_futures::DerivativesInfo;
 use bigdecimal::BigDecimal;
-use std::str::FromStr;
 
-#[derive(Debug)]
+/// A quote of an asset. Providers set the fields they have and leave the rest to `..Default::default()`, so that
+/// data only some kinds of tickers use, e.g. `gas_prices`, is added without touching every provider.
+#[derive(Clone, Debug, Default)]
 pub struct AssetQuoteResponse {
     pub name: String,
-    pub price_usd: BigDecimal,
-    pub price_change_24h: f64,
+    pub price: BigDecimal,
+    pub price_change_24h: BigDecimal, // Change over 24h in percent, as exact as the provider gives it
+    pub extended_hours: Option<ExtendedHoursQuote>, // Latest pre-market or after-hours trade, stocks only
+    pub gas_prices: Option<GasPrices>, // Gas prices by speed, gas tickers only
+    pub label: Option<String>, // Description of the value shown instead of its change, e.g. `Extreme Greed` for an index
+    pub not_modified: bool, // The provider answered that nothing changed since its last response, e.g. HTTP 304
+    pub day_range: Option<DayRange>, // Highest and lowest price of the day, if the provider gives them
+    pub derivatives: Option<DerivativesInfo>, // Funding rate and open interest of the perpetual contract, Binance Futures only
 }
 
-impl AssetQuoteResponse {
-    pub fn dummy() -> Self {
-        Self {
-            name: "dummy".to_string(),
-            price_usd: BigDecimal::from_str("-99999.00").unwrap(),
-            price_change_24h: -999.0,
+/// Highest and lowest price over the last 24h, or of the trading day for stocks
+#[derive(Clone, Debug, PartialEq)]
+pub struct DayRange {
+    pub high: BigDecimal,
+    pub low: BigDecimal,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MarketSession {
+    PreMarket,
+    AfterHours,
+}
+
+impl MarketSession {
+    /// Short tag shown in the status of the bot
+    pub fn tag(&self) -> &'static str {
+        match self {
+            MarketSession::PreMarket => "pre",
+            MarketSession::AfterHours => "after",
         }
     }
 }
+
+#[derive(Clone, Debug)]
+pub struct ExtendedHoursQuote {
+    pub session: MarketSession,
+    pub price: BigDecimal,
+    pub price_change: BigDecimal, // Change against the last regular session close, in percent
+}
+
+/// Gas prices of a chain in gwei, by how fast a transaction is expected to be included
+#[derive(Clone, Debug)]
+pub struct GasPrices {
+    pub slow: BigDecimal,
+    pub standard: BigDecimal,
+    pub fast: BigDecimal,
+}
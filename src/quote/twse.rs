@@ -0,0 +1,204 @@
+use crate::quote::error::QuoteRequestError;
+use crate::quote::provider::QuoteProvider;
+use crate::quote::response::AssetQuoteResponse;
+use async_trait::async_trait;
+use bigdecimal::{BigDecimal, Zero};
+use reqwest::header;
+use serde::Deserialize;
+use std::str::FromStr;
+
+pub const DEFAULT_BASE_URL: &str = "https://mis.twse.com.tw";
+
+/// Minimum interval between two updates of a ticker, the MIS API blocks clients polling more often
+pub const MIN_UPDATE_INTERVAL_SECS: u64 = 5;
+
+/// Whether `symbol` is listed on the Taiwan Stock Exchange (`2330.TW`) or the Taipei Exchange (`6488.TWO`)
+pub fn is_taiwan_symbol(symbol: &str) -> bool {
+    channel(symbol).is_some()
+}
+
+/// Channel of `symbol` in the MIS API, e.g. `tse_2330.tw` for `2330.TW` and `otc_6488.tw` for `6488.TWO`
+fn channel(symbol: &str) -> Option<String> {
+    let (code, suffix) = symbol.rsplit_once('.')?;
+    let market = match suffix.to_ascii_uppercase().as_str() {
+        "TW" => "tse",
+        "TWO" => "otc",
+        _ => return None,
+    };
+
+    if code.is_empty() {
+        return None;
+    }
+
+    Some(format!("{}_{}.tw", market, code.to_ascii_lowercase()))
+}
+
+pub fn stock_info_url(base_url: &str, symbol: &str) -> Option<String> {
+    Some(format!(
+        "{}/stock/api/getStockInfo.jsp?ex_ch={}&json=1&delay=0",
+        base_url,
+        channel(symbol)?
+    ))
+}
+
+/// Real-time quotes of stocks listed in Taiwan from the Market Information System of TWSE
+pub struct TwseProvider {
+    http_client: reqwest::Client,
+    base_url: String,
+}
+
+impl TwseProvider {
+    pub fn new(http_client: reqwest::Client, base_url: String) -> Self {
+        Self {
+            http_client,
+            base_url,
+        }
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for TwseProvider {
+    fn name(&self) -> &str {
+        "TWSE"
+    }
+
+    async fn fetch_quote(
+        &self,
+        id: &str,
+        _vs_currency: &str,
+    ) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        let url = stock_info_url(&self.base_url, id).ok_or_else(|| {
+            QuoteRequestError::Other(format!("{} is not a symbol listed in Taiwan", id))
+        })?;
+
+        let body = self
+            .http_client
+            .get(url)
+            .header(header::ACCEPT, "application/json")
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        parse_stock_info_response(&body, id)
+    }
+}
+
+/// Response of the `getStockInfo.jsp` endpoint, prices are strings and `-` when there is none.
+///
+/// Example: `{"msgArray":[{"c":"2330","n":"台積電","z":"1085.0000","y":"1080.0000","b":"1080.0000_1075.0000_"}],"rtcode":"0000"}`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StockInfoResponse {
+    #[serde(default)]
+    msg_array: Vec<StockInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StockInfo {
+    z: Option<String>, // Price of the latest trade
+    y: Option<String>, // Close of the previous trading day
+    b: Option<String>, // Best bid prices, separated by `_`
+}
+
+fn parse_price(value: &str) -> Option<BigDecimal> {
+    BigDecimal::from_str(value.trim()).ok().filter(|price| !price.is_zero())
+}
+
+pub fn parse_stock_info_response(
+    body: &str,
+    symbol: &str,
+) -> Result<AssetQuoteResponse, QuoteRequestError> {
+    let response: StockInfoResponse = serde_json::from_str(body)?;
+
+    let info = response
+        .msg_array
+        .into_iter()
+        .next()
+        .ok_or_else(|| QuoteRequestError::MissingAsset(symbol.to_string()))?;
+
+    let previous_close = info
+        .y
+        .as_deref()
+        .and_then(parse_price)
+        .ok_or_else(|| QuoteRequestError::MissingField("y".to_string()))?;
+
+    // the latest trade price is `-` between trades during the session, fall back to the best bid then
+    let price = info
+        .z
+        .as_deref()
+        .and_then(parse_price)
+        .or_else(|| info.b.as_deref().and_then(|bids| bids.split('_').find_map(parse_price)))
+        .ok_or_else(|| QuoteRequestError::MissingField("z".to_string()))?;
+
+    let price_change_24h = (&price - &previous_close) * BigDecimal::from(100) / &previous_close;
+
+    Ok(AssetQuoteResponse {
+        name: symbol.to_string(),
+        price,
+        price_change_24h,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_taiwan_symbol() {
+        assert!(is_taiwan_symbol("2330.TW"));
+        assert!(is_taiwan_symbol("6488.TWO"));
+        assert!(is_taiwan_symbol("0050.tw"));
+        assert!(!is_taiwan_symbol("AAPL"));
+        assert!(!is_taiwan_symbol("7203.T"));
+        assert!(!is_taiwan_symbol(".TW"));
+    }
+
+    #[test]
+    fn test_stock_info_url() {
+        assert_eq!(
+            Some("https://mis.twse.com.tw/stock/api/getStockInfo.jsp?ex_ch=tse_2330.tw&json=1&delay=0".to_string()),
+            stock_info_url(DEFAULT_BASE_URL, "2330.TW")
+        );
+        assert_eq!(
+            Some("http://localhost:8080/stock/api/getStockInfo.jsp?ex_ch=otc_6488.tw&json=1&delay=0".to_string()),
+            stock_info_url("http://localhost:8080", "6488.TWO")
+        );
+        assert_eq!(None, stock_info_url(DEFAULT_BASE_URL, "AAPL"));
+    }
+
+    #[test]
+    fn test_parse_stock_info_response() {
+        let body = r#"{"msgArray":[{"c":"2330","n":"台積電","z":"1085.0000","y":"1080.0000","b":"1080.0000_1075.0000_"}],"rtcode":"0000"}"#;
+        let response = parse_stock_info_response(body, "2330.TW").unwrap();
+        assert_eq!("2330.TW", response.name);
+        assert_eq!("1085.0000", response.price.to_string());
+        assert_eq!(BigDecimal::from_str("0.46296").unwrap(), response.price_change_24h.round(5));
+
+        let body = r#"{"msgArray":[{"c":"2330","z":"-","y":"1000.0000","b":"-_990.0000_"}],"rtcode":"0000"}"#;
+        let response = parse_stock_info_response(body, "2330.TW").unwrap();
+        assert_eq!("990.0000", response.price.to_string());
+        assert_eq!(BigDecimal::from(-1), response.price_change_24h);
+    }
+
+    #[test]
+    fn test_parse_stock_info_response_malformed() {
+        let cases = [
+            ("", "JSON Parse Error"),
+            (r#"{"msgArray":[],"rtcode":"0000"}"#, "Missing Asset Error"),
+            (r#"{"rtcode":"0000"}"#, "Missing Asset Error"),
+            (r#"{"msgArray":[{"z":"1085.0000","y":"-"}]}"#, "Missing Field Error: `y`"),
+            (r#"{"msgArray":[{"z":"-","y":"1080.0000","b":"-"}]}"#, "Missing Field Error: `z`"),
+        ];
+
+        for (body, expected) in cases {
+            let err = parse_stock_info_response(body, "2330.TW").unwrap_err();
+            assert!(
+                err.to_string().starts_with(expected),
+                "body {:?}: expected {:?}, got {:?}",
+                body, expected, err.to_string()
+            );
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use crate::quote::error::QuoteRequestError;
+use crate::quote::provider::QuoteProvider;
+use crate::quote::response::{AssetQuoteResponse, DayRange, ExtendedHoursQuote, MarketSession};
+use async_trait::async_trait;
+use bigdecimal::{BigDecimal, Zero};
+use reqwest::header;
+use serde::Deserialize;
+
+pub const DEFAULT_BASE_URL: &str = "https://query1.finance.yahoo.com";
+
+/// Minimum interval between two updates of a ticker, the chart API is unofficial and easily rate limited
+pub const MIN_UPDATE_INTERVAL_SECS: u64 = 10;
+
+pub fn chart_url(base_url: &str, symbol: &str) -> String {
+    format!(
+        "{}/v8/finance/chart/{}?interval=1m&range=1d&includePrePost=true",
+        base_url, symbol
+    )
+}
+
+/// Quotes of US stocks from the chart API of Yahoo Finance, including pre-market and after-hours trades
+pub struct YahooFinanceProvider {
+    http_client: reqwest::Client,
+    base_url: String,
+}
+
+impl YahooFinanceProvider {
+    pub fn new(http_client: reqwest::Client, base_url: String) -> Self {
+        Self {
+            http_client,
+            base_url,
+        }
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for YahooFinanceProvider {
+    fn name(&self) -> &str {
+        "Yahoo Finance"
+    }
+
+    async fn fetch_quote(
+        &self,
+        id: &str,
+        _vs_currency: &str,
+    ) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        let body = self
+            .http_client
+            .get(chart_url(&self.base_url, id))
+            .header(header::ACCEPT, "application/json")
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        parse_chart_response(&body, id)
+    }
+}
+
+/// Response of the `/v8/finance/chart/{symbol}` endpoint, only the fields in use are listed.
+#[derive(Debug, Deserialize)]
+struct ChartResponse {
+    chart: Chart,
+}
+
+#[derive(Debug, Deserialize)]
+struct Chart {
+    result: Option<Vec<ChartResult>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChartResult {
+    meta: ChartMeta,
+    #[serde(default)]
+    timestamp: Vec<i64>,
+    indicators: Indicators,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChartMeta {
+    regular_market_price: Option<BigDecimal>,
+    chart_previous_close: Option<BigDecimal>,
+    current_trading_period: Option<TradingPeriods>,
+    regular_market_day_high: Option<BigDecimal>,
+    regular_market_day_low: Option<BigDecimal>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TradingPeriods {
+    pre: TradingPeriod,
+    post: TradingPeriod,
+}
+
+#[derive(Debug, Deserialize)]
+struct TradingPeriod {
+    start: i64,
+    end: i64,
+}
+
+impl TradingPeriod {
+    fn contains(&self, timestamp: i64) -> bool {
+        self.start <= timestamp && timestamp < self.end
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Indicators {
+    quote: Vec<QuoteIndicator>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QuoteIndicator {
+    #[serde(default)]
+    close: Vec<Option<BigDecimal>>,
+}
+
+pub fn parse_chart_response(body: &str, symbol: &str) -> Result<AssetQuoteResponse, QuoteRequestError> {
+    let response: ChartResponse = serde_json::from_str(body)?;
+
+    let result = response
+        .chart
+        .result
+        .and_then(|results| results.into_iter().next())
+        .ok_or_else(|| QuoteRequestError::MissingAsset(symbol.to_string()))?;
+
+    let price = result
+        .meta
+        .regular_market_price
+        .clone()
+        .ok_or_else(|| QuoteRequestError::MissingField("regularMarketPrice".to_string()))?;
+    let previous_close = result
+        .meta
+        .chart_previous_close
+        .as_ref()
+        .ok_or_else(|| QuoteRequestError::MissingField("chartPreviousClose".to_string()))?;
+    let price_change_24h = percent_change(&price, previous_close, "chartPreviousClose")?;
+
+    let extended_hours = match latest_extended_hours_trade(&result) {
+        Some((session, extended_price)) => Some(ExtendedHoursQuote {
+            session,
+            price_change: percent_change(&extended_price, &price, "regularMarketPrice")?,
+            price: extended_price,
+        }),
+        None => None,
+    };
+    let day_range = result
+        .meta
+        .regular_market_day_high
+        .zip(result.meta.regular_market_day_low)
+        .map(|(high, low)| DayRange { high, low });
+
+    Ok(AssetQuoteResponse {
+        name: symbol.to_string(),
+        price,
+        price_change_24h,
+        extended_hours,
+        day_range,
+        ..Default::default()
+    })
+}
+
+/// The last trade of the chart, if it happened before or after the regular session
+fn latest_extended_hours_trade(result: &ChartResult) -> Option<(MarketSession, BigDecimal)> {
+    let periods = result.meta.current_trading_period.as_ref()?;
+    let closes = &result.indicators.quote.first()?.close;
+
+    let (timestamp, price) = result
+        .timestamp
+        .iter()
+        .zip(closes.iter())
+        .rev()
+        .find_map(|(timestamp, close)| close.as_ref().map(|close| (*timestamp, close)))?;
+
+    if periods.pre.contains(timestamp) {
+        Some((MarketSession::PreMarket, price.clone()))
+    } else if periods.post.contains(timestamp) {
+        Some((MarketSession::AfterHours, price.clone()))
+    } else {
+        None
+    }
+}
+
+fn percent_change(price: &BigDecimal, base: &BigDecimal, field: &str) -> Result<BigDecimal, QuoteRequestError> {
+    if base.is_zero() {
+        return Err(QuoteRequestError::InvalidField(field.to_string()));
+    }
+
+    Ok((price - base) * BigDecimal::from(100) / base)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn chart_body(last_timestamp: i64, last_close: &str) -> String {
+        format!(
+            r#"{{"chart":{{"result":[{{
+                "meta":{{
+                    "symbol":"AAPL","regularMarketPrice":200.0,"chartPreviousClose":196.0,
+                    "regularMarketDayHigh":201.5,"regularMarketDayLow":197.25,
+                    "currentTradingPeriod":{{
+                        "pre":{{"start":1000,"end":2000}},
+                        "regular":{{"start":2000,"end":3000}},
+                        "post":{{"start":3000,"end":4000}}
+                    }}
+                }},
+                "timestamp":[1500,2500,{}],
+                "indicators":{{"quote":[{{"close":[198.0,199.5,{}]}}]}}
+            }}],"error":null}}}}"#,
+            last_timestamp, last_close
+        )
+    }
+
+    #[test]
+    fn test_chart_url() {
+        assert_eq!(
+            "https://query1.finance.yahoo.com/v8/finance/chart/AAPL?interval=1m&range=1d&includePrePost=true",
+            chart_url(DEFAULT_BASE_URL, "AAPL")
+        );
+    }
+
+    #[test]
+    fn test_parse_chart_response_regular_session() {
+        let response = parse_chart_response(&chart_body(2600, "200.0"), "AAPL").unwrap();
+        assert_eq!("AAPL", response.name);
+        assert_eq!("200.0", response.price.to_string());
+        assert_eq!("2.0408", response.price_change_24h.round(4).to_string());
+        assert!(response.extended_hours.is_none());
+        let day_range = response.day_range.unwrap();
+        assert_eq!("201.5", day_range.high.to_string());
+        assert_eq!("197.25", day_range.low.to_string());
+    }
+
+    #[test]
+    fn test_parse_chart_response_extended_hours() {
+        let response = parse_chart_response(&chart_body(3500, "202.5"), "AAPL").unwrap();
+        let extended_hours = response.extended_hours.unwrap();
+        assert_eq!(MarketSession::AfterHours, extended_hours.session);
+        assert_eq!("202.5", extended_hours.price.to_string());
+        assert_eq!(BigDecimal::from_str("1.25").unwrap(), extended_hours.price_change);
+
+        // a missing last close falls back to the trade before it
+        let response = parse_chart_response(&chart_body(3500, "null"), "AAPL").unwrap();
+        assert!(response.extended_hours.is_none());
+
+        let body = chart_body(3500, "null").replace("[1500,2500", "[1500,1600");
+        let extended_hours = parse_chart_response(&body, "AAPL").unwrap().extended_hours.unwrap();
+        assert_eq!(MarketSession::PreMarket, extended_hours.session);
+        assert_eq!("pre", extended_hours.session.tag());
+    }
+
+    #[test]
+    fn test_parse_chart_response_malformed() {
+        let cases = [
+            ("", "JSON Parse Error"),
+            (r#"{"chart":{"result":null,"error":{"code":"Not Found"}}}"#, "Missing Asset Error"),
+            (r#"{"chart":{"result":[]}}"#, "Missing Asset Error"),
+            (
+                r#"{"chart":{"result":[{"meta":{"chartPreviousClose":1.0},"indicators":{"quote":[]}}]}}"#,
+                "Missing Field Error: `regularMarketPrice`",
+            ),
+            (
+                r#"{"chart":{"result":[{"meta":{"regularMarketPrice":1.0,"chartPreviousClose":0},"indicators":{"quote":[]}}]}}"#,
+                "Invalid Field Error: `chartPreviousClose`",
+            ),
+        ];
+
+        for (body, expected) in cases {
+            let err = parse_chart_response(body, "AAPL").unwrap_err();
+            assert!(
+                err.to_string().starts_with(expected),
+                "body {:?}: expected {:?}, got {:?}",
+                body, expected, err.to_string()
+            );
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use crate::quote::error::QuoteRequestError;
+use crate::quote::response::AssetQuoteResponse;
+use bigdecimal::{BigDecimal, Zero};
+
+/// Price of `base` in units of `quote`, e.g. ETH in BTC, and its change over 24h in percent
+pub fn price_ratio(
+    base: &AssetQuoteResponse,
+    quote: &AssetQuoteResponse,
+) -> Result<(BigDecimal, BigDecimal), QuoteRequestError> {
+    if quote.price.is_zero() {
+        return Err(QuoteRequestError::Other(format!(
+            "price of {} is zero, cannot divide by it",
+            quote.name
+        )));
+    }
+
+    let ratio = &base.price / &quote.price;
+
+    // the change is infinite if the quote asset was worth nothing 24h ago
+    let hundred = BigDecimal::from(100);
+    let quote_factor = &hundred + &quote.price_change_24h;
+    if quote_factor.is_zero() {
+        return Ok((ratio, BigDecimal::zero()));
+    }
+
+    let change = (&hundred + &base.price_change_24h) * &hundred / quote_factor - hundred;
+    Ok((ratio, change))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn quote(name: &str, price: &str, price_change_24h: &str) -> AssetQuoteResponse {
+        AssetQuoteResponse {
+            name: name.to_string(),
+            price: BigDecimal::from_str(price).unwrap(),
+            price_change_24h: BigDecimal::from_str(price_change_24h).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_price_ratio() {
+        let (ratio, change) = price_ratio(&quote("ethereum", "3000", "10"), &quote("bitcoin", "60000", "0")).unwrap();
+        assert_eq!(BigDecimal::from_str("0.05").unwrap(), ratio);
+        assert_eq!(BigDecimal::from(10), change);
+
+        let (_, change) = price_ratio(&quote("ethereum", "3000", "0"), &quote("bitcoin", "60000", "25")).unwrap();
+        assert_eq!(BigDecimal::from(-20), change);
+
+        let (_, change) = price_ratio(&quote("ethereum", "3000", "0"), &quote("bitcoin", "60000", "-100")).unwrap();
+        assert_eq!(BigDecimal::zero(), change);
+    }
+
+    #[test]
+    fn test_price_ratio_zero_quote() {
+        let err = price_ratio(&quote("ethereum", "3000", "0"), &quote("bitcoin", "0", "0")).unwrap_err();
+        assert_eq!("Other Error: price of bitcoin is zero, cannot divide by it", err.to_string());
+    }
+}
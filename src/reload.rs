@@ -0,0 +1,164 @@
+use crate::config::{read_config, Config, TickerConfig};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::{error, info};
+
+/// What a reload changes of the running tickers, told apart by their `ticker`. A ticker whose entry changed in
+/// any way, e.g. its `frequency`, is restarted with the new one.
+#[derive(Debug, Default, PartialEq)]
+pub struct TickerChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+    pub unchanged: usize,
+}
+
+impl TickerChanges {
+    /// Compares the `ticker` and `TickerConfig::fingerprint` of the `running` tickers with the `reloaded` ones
+    pub fn between(running: &[(String, u64)], reloaded: &[TickerConfig]) -> Self {
+        let mut before: BTreeMap<&str, BTreeSet<u64>> = BTreeMap::new();
+        for (ticker, fingerprint) in running {
+            before.entry(ticker.as_str()).or_default().insert(*fingerprint);
+        }
+        let mut after: BTreeMap<&str, BTreeSet<u64>> = BTreeMap::new();
+        for ticker_config in reloaded {
+            after
+                .entry(ticker_config.ticker.as_str())
+                .or_default()
+                .insert(ticker_config.fingerprint);
+        }
+
+        let mut changes = TickerChanges::default();
+        for (ticker, fingerprints) in &after {
+            match before.get(ticker) {
+                None => changes.added.push(ticker.to_string()),
+                Some(running) if running != fingerprints => changes.changed.push(ticker.to_string()),
+                Some(_) => changes.unchanged += 1,
+            }
+        }
+        changes.removed = before
+            .keys()
+            .filter(|ticker| !after.contains_key(*ticker))
+            .map(|ticker| ticker.to_string())
+            .collect();
+        changes
+    }
+
+    /// Tickers to stop: the removed and the changed ones
+    pub fn stopped(&self) -> impl Iterator<Item = &String> {
+        self.removed.iter().chain(&self.changed)
+    }
+
+    /// Tickers to start from the reloaded config: the added and the changed ones
+    pub fn started(&self) -> impl Iterator<Item = &String> {
+        self.added.iter().chain(&self.changed)
+    }
+}
+
+impl fmt::Display for TickerChanges {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let list = |tickers: &[String]| match tickers.is_empty() {
+            true => String::new(),
+            false => format!(" ({})", tickers.join(", ")),
+        };
+        write!(
+            f,
+            "{} added{}, {} removed{}, {} changed{}, {} unchanged",
+            self.added.len(),
+            list(&self.added),
+            self.removed.len(),
+            list(&self.removed),
+            self.changed.len(),
+            list(&self.changed),
+            self.unchanged
+        )
+    }
+}
+
+/// Reads the config at `config_path` again on every SIGHUP, like classic daemons, hands it to `on_reload`, e.g. to
+/// apply its log level, and sends it to the bot, see `TickerBotBuilder::reloads`. A config that cannot be read is
+/// logged and the running one kept. Returns once the bot is gone.
+#[cfg(unix)]
+pub async fn reload_on_sighup<F>(config_path: String, reload_sender: UnboundedSender<Config>, on_reload: F)
+where
+    F: Fn(&Config),
+{
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = match signal(SignalKind::hangup()) {
+        Ok(hangups) => hangups,
+        Err(error) => {
+            error!("Error listening for SIGHUP, the config cannot be reloaded: {}", error);
+            return;
+        }
+    };
+
+    while hangups.recv().await.is_some() {
+        info!("SIGHUP received, reloading config from {}", config_path);
+        let config = match read_config(&config_path).await {
+            Ok(config) => config,
+            Err(error) => {
+                error!("Error reloading config, keeping the running one: {}", error);
+                continue;
+            }
+        };
+        on_reload(&config);
+        if reload_sender.send(config).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tickers(entries: serde_json::Value) -> Vec<TickerConfig> {
+        let config: Config = serde_json::from_value(serde_json::json!({ "tickers": entries })).unwrap();
+        config.tickers
+    }
+
+    #[test]
+    fn test_ticker_changes() {
+        let running: Vec<(String, u64)> = tickers(serde_json::json!([
+            {"ticker": "BTC", "name": "bitcoin"},
+            {"ticker": "ETH", "name": "ethereum"},
+            {"ticker": "SOL", "name": "solana"}
+        ]))
+        .into_iter()
+        .map(|ticker_config| (ticker_config.ticker, ticker_config.fingerprint))
+        .collect();
+        let reloaded = tickers(serde_json::json!([
+            {"ticker": "BTC", "name": "bitcoin"},
+            {"ticker": "ETH", "name": "ethereum", "frequency": 300},
+            {"ticker": "DOGE", "name": "dogecoin"}
+        ]));
+
+        let changes = TickerChanges::between(&running, &reloaded);
+        assert_eq!(
+            TickerChanges {
+                added: vec!["DOGE".to_string()],
+                removed: vec!["SOL".to_string()],
+                changed: vec!["ETH".to_string()],
+                unchanged: 1,
+            },
+            changes
+        );
+        assert_eq!(vec!["SOL", "ETH"], changes.stopped().collect::<Vec<_>>());
+        assert_eq!(vec!["DOGE", "ETH"], changes.started().collect::<Vec<_>>());
+        assert_eq!(
+            "1 added (DOGE), 1 removed (SOL), 1 changed (ETH), 1 unchanged",
+            changes.to_string()
+        );
+        assert_eq!(
+            "0 added, 0 removed, 0 changed, 3 unchanged",
+            TickerChanges::between(&running, &tickers(serde_json::json!([
+                {"ticker": "BTC", "name": "bitcoin"},
+                {"ticker": "ETH", "name": "ethereum"},
+                {"ticker": "SOL", "name": "solana"}
+            ])))
+            .to_string()
+        );
+    }
+}
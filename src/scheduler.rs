@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use cron::Schedule;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Times a ticker fetches its price, given by a cron expression instead of a fixed `frequency`,
+/// e.g. every 5 minutes during the trading hours of weekdays.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CronSchedule {
+    pub cron: Schedule, // Cron expression with seconds, `sec min hour day-of-month month day-of-week [year]`, e.g. `0 */5 9-16 * * Mon-Fri`
+    #[serde(default = "default_timezone")]
+    pub timezone: Tz, // IANA timezone the expression is in, defaults to `UTC`
+}
+
+fn default_timezone() -> Tz {
+    Tz::UTC
+}
+
+impl CronSchedule {
+    /// Time until the next time of the schedule after `now`, `None` if the schedule has no more times,
+    /// e.g. an expression limited to past years
+    pub fn until_next(&self, now: DateTime<Utc>) -> Option<Duration> {
+        let next = self.cron.after(&now.with_timezone(&self.timezone)).next()?;
+        (next.with_timezone(&Utc) - now).to_std().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value).unwrap().with_timezone(&Utc)
+    }
+
+    fn schedule(cron: &str, timezone: &str) -> CronSchedule {
+        serde_json::from_value(serde_json::json!({"cron": cron, "timezone": timezone})).unwrap()
+    }
+
+    #[test]
+    fn test_until_next() {
+        let schedule = schedule("0 */5 9-15 * * Mon-Fri", "America/New_York");
+
+        // Monday 10:02 in New York
+        assert_eq!(
+            Some(Duration::from_secs(3 * 60)),
+            schedule.until_next(utc("2024-03-04T15:02:00Z"))
+        );
+        // Friday 15:58 in New York, next on Monday 09:00
+        assert_eq!(
+            Some(Duration::from_secs(2 * 60 + 65 * 60 * 60)),
+            schedule.until_next(utc("2024-03-01T20:58:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_default_timezone() {
+        let schedule: CronSchedule = serde_json::from_value(serde_json::json!({"cron": "0 0 * * * *"})).unwrap();
+        assert_eq!(
+            Some(Duration::from_secs(30 * 60)),
+            schedule.until_next(utc("2024-03-04T15:30:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_invalid_cron() {
+        let result: Result<CronSchedule, _> = serde_json::from_value(serde_json::json!({"cron": "every minute"}));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,65 @@
+use serde::Deserialize;
+use std::fmt;
+
+/// A token, an API key or a URL embedding one, e.g. a Discord webhook URL.
+///
+/// `Debug` prints `[redacted]` instead of the value and there is no `Display`,
+/// so the secret only leaves through `expose`, where it is actually sent.
+#[derive(Clone, Default, Deserialize, PartialEq)]
+#[serde(transparent)]
+pub struct SecretString(String);
+
+impl SecretString {
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self(secret.into())
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(secret: String) -> Self {
+        Self(secret)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(secret: &str) -> Self {
+        Self(secret.to_string())
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // an empty secret tells that it is missing, which is worth seeing
+        if self.0.is_empty() {
+            write!(f, "\"\"")
+        } else {
+            write!(f, "[redacted]")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug() {
+        assert_eq!("[redacted]", format!("{:?}", SecretString::from("123:ABC")));
+        assert_eq!("\"\"", format!("{:?}", SecretString::default()));
+        assert_eq!("Some([redacted])", format!("{:?}", Some(SecretString::from("123:ABC"))));
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let secret: SecretString = serde_json::from_str(r#""123:ABC""#).unwrap();
+        assert_eq!("123:ABC", secret.expose());
+    }
+}
@@ -0,0 +1,92 @@
+use tokio::sync::watch;
+
+/// Sending half of the stop signal, held by the Ctrl+C handler
+pub struct StopTrigger(watch::Sender<bool>);
+
+/// Receiving half of the stop signal, cloned into every task that should stop on shutdown
+#[derive(Clone)]
+pub struct StopSignal(watch::Receiver<bool>);
+
+pub fn stop_channel() -> (StopTrigger, StopSignal) {
+    let (sender, receiver) = watch::channel(false);
+    (StopTrigger(sender), StopSignal(receiver))
+}
+
+/// Stop channel of a single task, e.g. a ticker removed by a reload, also stopped along with `parent`
+pub fn child_stop_channel(parent: &StopSignal) -> (StopTrigger, StopSignal) {
+    let (trigger, stop_signal) = stop_channel();
+    let sender = trigger.0.clone();
+    let mut parent = parent.clone();
+    // ends once the task dropped its signals, so that stopped children do not pile up
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = parent.stopped() => {
+                sender.send_replace(true);
+            }
+            _ = sender.closed() => {}
+        }
+    });
+    (trigger, stop_signal)
+}
+
+impl StopTrigger {
+    pub fn stop(&self) {
+        self.0.send_replace(true);
+    }
+}
+
+impl StopSignal {
+    pub fn is_stopped(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Completes once the stop is triggered, never if the trigger is dropped without it
+    pub async fn stopped(&mut self) {
+        if self.0.wait_for(|stopped| *stopped).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stop_signal() {
+        let (trigger, mut stop_signal) = stop_channel();
+        let mut other_stop_signal = stop_signal.clone();
+        assert!(!stop_signal.is_stopped());
+        assert!(timeout(Duration::from_secs(1), stop_signal.stopped()).await.is_err());
+
+        trigger.stop();
+        assert!(stop_signal.is_stopped());
+        stop_signal.stopped().await;
+        other_stop_signal.stopped().await;
+
+        // signals cloned after the stop see it as well
+        let mut late_stop_signal = stop_signal.clone();
+        late_stop_signal.stopped().await;
+
+        let (trigger, mut stop_signal) = stop_channel();
+        drop(trigger);
+        assert!(timeout(Duration::from_secs(1), stop_signal.stopped()).await.is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_child_stop_channel() {
+        let (parent_trigger, parent) = stop_channel();
+        let (child_trigger, mut child) = child_stop_channel(&parent);
+        let (_other_trigger, mut other_child) = child_stop_channel(&parent);
+
+        child_trigger.stop();
+        child.stopped().await;
+        assert!(!parent.is_stopped());
+        assert!(timeout(Duration::from_secs(1), other_child.stopped()).await.is_err());
+
+        parent_trigger.stop();
+        other_child.stopped().await;
+    }
+}
@@ -0,0 +1,6 @@
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+pub mod output;
+pub mod price_alert;
+pub mod stdout;
+pub mod telegram;
@@ -0,0 +1,135 @@
+use crate::bot_update::BotUpdateInfo;
+use crate::sink::output::OutputSink;
+use async_trait::async_trait;
+use rumqttc::{AsyncClient, ConnectionError, Event, EventLoop, MqttOptions, Packet, QoS};
+use std::error::Error;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+pub const DEFAULT_PORT: u16 = 1883;
+pub const DEFAULT_TOPIC: &str = "ticker/{name}";
+
+/// Capacity of the queue of publishes waiting for the connection to the broker
+const REQUEST_CAPACITY: usize = 16;
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Publishes every quote of a ticker as JSON to an MQTT broker, for dashboards like Home Assistant or Grafana
+pub struct MqttSink {
+    client: AsyncClient,
+    topic: String,
+    retain: bool,
+}
+
+impl MqttSink {
+    /// Creates a sink publishing to `topic`, where `{name}` is replaced by the ticker.
+    /// The connection to the broker is kept by a background task, which reconnects after failures.
+    pub fn new(options: MqttOptions, topic: &str, ticker: &str, retain: bool) -> Self {
+        let (client, event_loop) = AsyncClient::new(options, REQUEST_CAPACITY);
+        tokio::spawn(poll_event_loop(event_loop));
+
+        Self {
+            client,
+            topic: topic.replace("{name}", ticker),
+            retain,
+        }
+    }
+}
+
+/// Drives the connection until every client of it is dropped, publishes are only sent while it is polled
+async fn poll_event_loop(mut event_loop: EventLoop) {
+    let (host, port) = event_loop.mqtt_options.broker_address();
+    let broker = format!("{}:{}", host, port);
+    loop {
+        match event_loop.poll().await {
+            Ok(Event::Incoming(Packet::ConnAck(_))) => info!("Connected to MQTT broker {}", broker),
+            Ok(_) => {}
+            Err(ConnectionError::RequestsDone) => break,
+            Err(e) => {
+                warn!("MQTT connection to {} failed, retrying in {:?}: {}", broker, RECONNECT_DELAY, e);
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        }
+    }
+}
+
+fn payload(update: &BotUpdateInfo) -> Option<serde_json::Value> {
+    let quote = update.quote.as_ref()?;
+    Some(serde_json::json!({
+        "ticker": update.ticker,
+        // keep every digit of the price rather than rounding it through a float
+        "price": quote.price.to_string().parse::<serde_json::Number>().ok(),
+        "price_change_24h": quote.price_change_24h.to_string().parse::<serde_json::Number>().ok(),
+        "vs_currency": quote.vs_currency,
+        "name": update.name,
+        "status": update.status,
+        "updated_at": chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+#[async_trait]
+impl OutputSink for MqttSink {
+    fn name(&self) -> &str {
+        "MQTT"
+    }
+
+    async fn publish(&self, update: &BotUpdateInfo) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let Some(payload) = payload(update) else {
+            debug!("No quote in the update of {}, nothing to publish to {}", update.ticker, self.topic);
+            return Ok(());
+        };
+
+        // only queues the message, a full queue means the broker has been unreachable for a while
+        self.client
+            .try_publish(&self.topic, QoS::AtLeastOnce, self.retain, payload.to_string())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot_update::{BotUpdateTarget, QuoteData};
+    use bigdecimal::BigDecimal;
+    use std::str::FromStr;
+
+    fn update(quote: Option<QuoteData>) -> BotUpdateInfo {
+        BotUpdateInfo {
+            ticker: "BTC".to_string(),
+            name: "$65,761.12".to_string(),
+            status: "+1.50% | BTC".to_string(),
+            target: BotUpdateTarget::BoardOnly,
+            channel_name: None,
+            status_rotation: None,
+            quote,
+            fetched_at: None,
+        }
+    }
+
+    #[test]
+    fn test_payload() {
+        let payload = payload(&update(Some(QuoteData {
+            price: BigDecimal::from_str("65761.123456789").unwrap(),
+            price_change_24h: BigDecimal::from_str("1.5").unwrap(),
+            vs_currency: "usd".to_string(),
+        })))
+        .unwrap();
+
+        assert_eq!("BTC", payload["ticker"]);
+        assert_eq!("65761.123456789", payload["price"].to_string());
+        assert_eq!("1.5", payload["price_change_24h"].to_string());
+        assert_eq!("usd", payload["vs_currency"]);
+        assert_eq!("$65,761.12", payload["name"]);
+        assert_eq!("+1.50% | BTC", payload["status"]);
+    }
+
+    #[test]
+    fn test_payload_without_quote() {
+        assert!(payload(&update(None)).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_topic() {
+        let sink = MqttSink::new(MqttOptions::new("test", "localhost", DEFAULT_PORT), DEFAULT_TOPIC, "BTC", true);
+        assert_eq!("ticker/BTC", sink.topic);
+    }
+}
@@ -0,0 +1,21 @@
+use crate::bot_update::BotUpdateInfo;
+use async_trait::async_trait;
+use std::error::Error;
+use std::fmt;
+
+/// A place the formatted price of a ticker is sent to, e.g. the nickname of a Discord bot.
+///
+/// Implementations make a single attempt per call, a failed update is superseded by the next one.
+#[async_trait]
+pub trait OutputSink: Send + Sync {
+    /// Name of the sink, used in log messages.
+    fn name(&self) -> &str;
+
+    async fn publish(&self, update: &BotUpdateInfo) -> Result<(), Box<dyn Error + Send + Sync>>;
+}
+
+impl fmt::Debug for dyn OutputSink {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "OutputSink({})", self.name())
+    }
+}
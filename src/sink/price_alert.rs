@@ -0,0 +1,153 @@
+use crate::bot_update::BotUpdateInfo;
+use crate::discord::client::DiscordClient;
+use crate::discord::commands::format_amount;
+use crate::price_alert::{AlertDelivery, PriceAlert, PriceAlertStore};
+use crate::sink::output::OutputSink;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use std::error::Error;
+use std::future::Future;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Notifies the members whose `/alert` the price of an update triggered, through the bot answering the commands
+pub struct PriceAlertSink {
+    store: Arc<PriceAlertStore>,
+    discord_client: DiscordClient,
+}
+
+impl PriceAlertSink {
+    pub fn new(store: Arc<PriceAlertStore>, discord_client: DiscordClient) -> Self {
+        Self { store, discord_client }
+    }
+
+    async fn notify(&self, alert: &PriceAlert, message: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if alert.delivery == AlertDelivery::Dm {
+            match self.discord_client.send_direct_message(alert.user_id, message).await {
+                Ok(()) => return Ok(()),
+                // e.g. the user only accepts direct messages from friends
+                Err(error) => warn!("Error sending alert {} to user {}, mentioning instead: {}", alert.id, alert.user_id, error),
+            }
+        }
+
+        self.discord_client
+            .send_message(alert.channel_id, &format!("<@{}> {}", alert.user_id, message))
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OutputSink for PriceAlertSink {
+    fn name(&self) -> &str {
+        "price alerts"
+    }
+
+    async fn publish(&self, update: &BotUpdateInfo) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let Some(quote) = &update.quote else {
+            return Ok(());
+        };
+
+        notify_triggered(&self.store, &update.ticker, &quote.price, &quote.vs_currency, |alert, message| async move {
+            self.notify(&alert, &message).await
+        })
+        .await
+    }
+}
+
+/// Sends `notify` the alerts of `ticker` in `vs_currency` triggered by `price` along with their message. The alerts which could not
+/// be delivered are put back in `store`, to be tried again once an update reaches their price.
+async fn notify_triggered<F, Fut>(
+    store: &PriceAlertStore,
+    ticker: &str,
+    price: &BigDecimal,
+    vs_currency: &str,
+    notify: F,
+) -> Result<(), Box<dyn Error + Send + Sync>>
+where
+    F: Fn(PriceAlert, String) -> Fut,
+    Fut: Future<Output = Result<(), Box<dyn Error + Send + Sync>>>,
+{
+    let mut undelivered = Vec::new();
+    let mut last_error = None;
+    for alert in store.take_triggered(ticker, vs_currency, price).await {
+        let message = alert_message(&alert, price, vs_currency);
+        info!("Price alert {} of user {} triggered", alert.id, alert.user_id);
+        if let Err(error) = notify(alert.clone(), message).await {
+            warn!("Error notifying user {} of alert {}, keeping it: {}", alert.user_id, alert.id, error);
+            undelivered.push(alert);
+            last_error = Some(error);
+        }
+    }
+    store.restore(undelivered).await;
+
+    match last_error {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+fn alert_message(alert: &PriceAlert, price: &BigDecimal, vs_currency: &str) -> String {
+    let vs_currency = vs_currency.to_uppercase();
+    format!(
+        "🔔 {} is {} {} {}, now at {} {}",
+        alert.ticker,
+        alert.direction.name(),
+        format_amount(&alert.price),
+        vs_currency,
+        format_amount(price),
+        vs_currency
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::price_alert::PriceDirection;
+    use crate::state_file::temp_state_file;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_alert_message() {
+        let alert = PriceAlert {
+            id: 1,
+            user_id: 2,
+            channel_id: 3,
+            ticker: "BTC".to_string(),
+            vs_currency: "usd".to_string(),
+            direction: PriceDirection::Above,
+            price: BigDecimal::from(70000),
+            delivery: AlertDelivery::Dm,
+        };
+        assert_eq!(
+            "🔔 BTC is above 70,000 USD, now at 70,123.46 USD",
+            alert_message(&alert, &BigDecimal::from_str("70123.456").unwrap(), "usd")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_notify_triggered_keeps_undelivered() {
+        let state_file = temp_state_file("alert-sink");
+        let store = PriceAlertStore::load(state_file.clone(), 1).await;
+        for user_id in [1, 2] {
+            store
+                .add(user_id, 10, "BTC", "usd", PriceDirection::Above, BigDecimal::from(70000), AlertDelivery::Dm)
+                .await
+                .unwrap();
+        }
+
+        // neither the direct message nor the mention reached user 2
+        let result = notify_triggered(&store, "BTC", &BigDecimal::from(71000), "usd", |alert, _| async move {
+            match alert.user_id {
+                2 => Err("Missing Access".into()),
+                _ => Ok(()),
+            }
+        })
+        .await;
+        assert_eq!("Missing Access", result.unwrap_err().to_string());
+        assert!(store.list(1).await.is_empty());
+        assert_eq!(1, store.list(2).await.len());
+
+        tokio::fs::remove_file(&state_file).await.unwrap();
+    }
+}
@@ -0,0 +1,44 @@
+use crate::bot_update::BotUpdateInfo;
+use crate::sink::output::OutputSink;
+use async_trait::async_trait;
+use std::error::Error;
+
+/// Prints every update of the ticker, for debugging a config without touching Discord
+pub struct StdoutSink;
+
+#[async_trait]
+impl OutputSink for StdoutSink {
+    fn name(&self) -> &str {
+        "stdout"
+    }
+
+    async fn publish(&self, update: &BotUpdateInfo) -> Result<(), Box<dyn Error + Send + Sync>> {
+        println!("{}", format_update(update));
+        Ok(())
+    }
+}
+
+fn format_update(update: &BotUpdateInfo) -> String {
+    format!("{}: {} | {}", update.ticker, update.name, update.status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot_update::BotUpdateTarget;
+
+    #[test]
+    fn test_format_update() {
+        let update = BotUpdateInfo {
+            ticker: "BTC".to_string(),
+            name: "$65,761.12".to_string(),
+            status: "+1.50% | BTC".to_string(),
+            target: BotUpdateTarget::BoardOnly,
+            channel_name: None,
+            status_rotation: None,
+            quote: None,
+            fetched_at: None,
+        };
+        assert_eq!("BTC: $65,761.12 | +1.50% | BTC", format_update(&update));
+    }
+}
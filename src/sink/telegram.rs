@@ -0,0 +1,293 @@
+use crate::bot_update::BotUpdateInfo;
+use crate::sink::output::OutputSink;
+use async_trait::async_trait;
+use reqwest::header;
+use serde::Deserialize;
+use std::error::Error;
+use tokio::sync::Mutex;
+use tracing::{debug, info};
+
+pub const DEFAULT_BASE_URL: &str = "https://api.telegram.org";
+
+pub fn method_url(base_url: &str, bot_token: &str, method: &str) -> String {
+    format!("{}/bot{}/{}", base_url, bot_token, method)
+}
+
+/// What a Telegram sink keeps up to date in its chat
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TelegramTarget {
+    Title, // Title of the chat, the bot must be an admin allowed to change it
+    Message, // A single message posted by the bot
+}
+
+/// Mirrors the price of a ticker to a Telegram channel or group through the Bot API
+pub struct TelegramSink {
+    http_client: reqwest::Client,
+    base_url: String,
+    bot_token: String,
+    chat_id: String,
+    target: TelegramTarget,
+    state: Mutex<TelegramState>,
+}
+
+/// Message kept up to date and the last text sent, so that an unchanged price makes no request
+#[derive(Default)]
+struct TelegramState {
+    message_id: Option<i64>,
+    text: Option<String>,
+}
+
+/// Response of every Bot API method, `result` is only given if `ok`.
+///
+/// Example: `{"ok":false,"error_code":400,"description":"Bad Request: message to edit not found"}`
+#[derive(Debug, Deserialize)]
+struct ApiResponse {
+    ok: bool,
+    description: Option<String>,
+    result: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Message {
+    message_id: i64,
+}
+
+impl TelegramSink {
+    /// Creates a sink editing the message `message_id` if given, otherwise a new message is posted on the first update
+    pub fn new(
+        http_client: reqwest::Client,
+        base_url: String,
+        bot_token: String,
+        chat_id: String,
+        target: TelegramTarget,
+        message_id: Option<i64>,
+    ) -> Self {
+        Self {
+            http_client,
+            base_url,
+            bot_token,
+            chat_id,
+            target,
+            state: Mutex::new(TelegramState {
+                message_id,
+                text: None,
+            }),
+        }
+    }
+
+    async fn call(
+        &self,
+        method: &str,
+        body: serde_json::Value,
+    ) -> Result<ApiResponse, Box<dyn Error + Send + Sync>> {
+        // the Bot API answers errors with a JSON body and a 4xx status, which tells more than the status
+        let text = self
+            .http_client
+            .post(method_url(&self.base_url, &self.bot_token, method))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            // the URL holds the token of the bot
+            .map_err(reqwest::Error::without_url)?
+            .text()
+            .await
+            .map_err(reqwest::Error::without_url)?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    async fn set_title(&self, text: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let response = self
+            .call("setChatTitle", serde_json::json!({ "chat_id": self.chat_id, "title": text }))
+            .await?;
+        check(response).map(|_| ())
+    }
+
+    async fn update_message(
+        &self,
+        state: &mut TelegramState,
+        text: &str,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if let Some(message_id) = state.message_id {
+            let response = self
+                .call(
+                    "editMessageText",
+                    serde_json::json!({ "chat_id": self.chat_id, "message_id": message_id, "text": text }),
+                )
+                .await?;
+
+            match response.description.as_deref() {
+                _ if response.ok => return Ok(()),
+                Some(description) if description.contains("message is not modified") => return Ok(()),
+                Some(description) if description.contains("message to edit not found") => {
+                    info!("Telegram message {} is gone, posting a new one", message_id);
+                }
+                _ => return check(response).map(|_| ()),
+            }
+        }
+
+        let response = self
+            .call("sendMessage", serde_json::json!({ "chat_id": self.chat_id, "text": text }))
+            .await?;
+        let message: Message = serde_json::from_value(check(response)?)?;
+        info!("Posted Telegram message {}", message.message_id);
+        state.message_id = Some(message.message_id);
+        Ok(())
+    }
+}
+
+fn check(response: ApiResponse) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>> {
+    if !response.ok {
+        return Err(format!(
+            "Telegram API error: {}",
+            response.description.unwrap_or_else(|| "no description".to_string())
+        )
+        .into());
+    }
+
+    Ok(response.result.unwrap_or(serde_json::Value::Null))
+}
+
+#[async_trait]
+impl OutputSink for TelegramSink {
+    fn name(&self) -> &str {
+        "Telegram"
+    }
+
+    async fn publish(&self, update: &BotUpdateInfo) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let text = format!("{} | {}", update.name, update.status);
+        let mut state = self.state.lock().await;
+
+        if state.text.as_deref() == Some(text.as_str()) {
+            debug!("Telegram {:?} of {} is already {}", self.target, update.ticker, text);
+            return Ok(());
+        }
+
+        match self.target {
+            TelegramTarget::Title => self.set_title(&text).await?,
+            TelegramTarget::Message => self.update_message(&mut state, &text).await?,
+        }
+
+        state.text = Some(text);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot_update::BotUpdateTarget;
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn update(name: &str) -> BotUpdateInfo {
+        BotUpdateInfo {
+            ticker: "BTC".to_string(),
+            name: name.to_string(),
+            status: "+1.50% | BTC".to_string(),
+            target: BotUpdateTarget::BoardOnly,
+            channel_name: None,
+            status_rotation: None,
+            quote: None,
+            fetched_at: None,
+        }
+    }
+
+    fn sink(server: &MockServer, target: TelegramTarget, message_id: Option<i64>) -> TelegramSink {
+        TelegramSink::new(
+            reqwest::Client::new(),
+            server.uri(),
+            "123:ABC".to_string(),
+            "@prices".to_string(),
+            target,
+            message_id,
+        )
+    }
+
+    #[test]
+    fn test_method_url() {
+        assert_eq!(
+            "https://api.telegram.org/bot123:ABC/sendMessage",
+            method_url(DEFAULT_BASE_URL, "123:ABC", "sendMessage")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_publish_title_skips_unchanged_text() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/bot123:ABC/setChatTitle"))
+            .and(body_json(serde_json::json!({ "chat_id": "@prices", "title": "$65,761 | +1.50% | BTC" })))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"ok":true,"result":true}"#))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let sink = sink(&server, TelegramTarget::Title, None);
+        sink.publish(&update("$65,761")).await.unwrap();
+        sink.publish(&update("$65,761")).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_publish_message_posts_then_edits() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/bot123:ABC/sendMessage"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(r#"{"ok":true,"result":{"message_id":42,"chat":{"id":-100}}}"#),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/bot123:ABC/editMessageText"))
+            .and(body_json(serde_json::json!({ "chat_id": "@prices", "message_id": 42, "text": "$65,800 | +1.50% | BTC" })))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"ok":true,"result":{"message_id":42}}"#))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let sink = sink(&server, TelegramTarget::Message, None);
+        sink.publish(&update("$65,761")).await.unwrap();
+        sink.publish(&update("$65,800")).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_publish_message_reposts_deleted_message() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/bot123:ABC/editMessageText"))
+            .respond_with(ResponseTemplate::new(400).set_body_string(
+                r#"{"ok":false,"error_code":400,"description":"Bad Request: message to edit not found"}"#,
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/bot123:ABC/sendMessage"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"ok":true,"result":{"message_id":8}}"#))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let sink = sink(&server, TelegramTarget::Message, Some(7));
+        sink.publish(&update("$65,761")).await.unwrap();
+        assert_eq!(Some(8), sink.state.lock().await.message_id);
+    }
+
+    #[tokio::test]
+    async fn test_publish_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/bot123:ABC/setChatTitle"))
+            .respond_with(ResponseTemplate::new(400).set_body_string(
+                r#"{"ok":false,"error_code":400,"description":"Bad Request: not enough rights to change chat title"}"#,
+            ))
+            .mount(&server)
+            .await;
+
+        let sink = sink(&server, TelegramTarget::Title, None);
+        let err = sink.publish(&update("$65,761")).await.unwrap_err();
+        assert_eq!("Telegram API error: Bad Request: not enough rights to change chat title", err.to_string());
+    }
+}
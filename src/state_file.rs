@@ -0,0 +1,58 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::Path;
+use tracing::warn;
+
+/// Reads the `what` state kept as JSON in `state_file`, e.g. `price alerts`. A missing file gives the default state,
+/// as on the first start, and so does one that cannot be parsed, after a warning.
+pub(crate) async fn load_json_state<T: DeserializeOwned + Default>(state_file: &Path, what: &str) -> T {
+    match tokio::fs::read_to_string(state_file).await {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            warn!("Error parsing {} {}: {}", what, state_file.display(), e);
+            T::default()
+        }),
+        Err(_) => T::default(),
+    }
+}
+
+/// Writes the `what` state to `state_file` as JSON, only warning if it cannot: the state in memory stays valid
+pub(crate) async fn save_json_state<T: Serialize>(state_file: &Path, what: &str, state: &T) {
+    let content = match serde_json::to_string_pretty(state) {
+        Ok(content) => content,
+        Err(e) => {
+            warn!("Error serializing {}: {}", what, e);
+            return;
+        }
+    };
+
+    if let Err(e) = tokio::fs::write(state_file, content).await {
+        warn!("Error saving {} to {}: {}", what, state_file.display(), e);
+    }
+}
+
+/// State file of the tests of `name`, apart from those of other processes running the tests
+#[cfg(test)]
+pub(crate) fn temp_state_file(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("discord-price-ticker-{}-{}.json", name, std::process::id()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[tokio::test]
+    async fn test_json_state() {
+        let state_file = temp_state_file("state");
+        assert!(load_json_state::<BTreeMap<String, u64>>(&state_file, "state").await.is_empty());
+
+        save_json_state(&state_file, "state", &BTreeMap::from([("BTC".to_string(), 1234)])).await;
+        let state: BTreeMap<String, u64> = load_json_state(&state_file, "state").await;
+        assert_eq!(Some(&1234), state.get("BTC"));
+
+        tokio::fs::write(&state_file, "not json").await.unwrap();
+        assert!(load_json_state::<BTreeMap<String, u64>>(&state_file, "state").await.is_empty());
+
+        tokio::fs::remove_file(&state_file).await.unwrap();
+    }
+}
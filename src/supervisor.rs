@@ -0,0 +1,165 @@
+use crate::shutdown::StopSignal;
+use std::any::Any;
+use std::future::Future;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Instant};
+use tracing::{error, info, warn};
+
+const INITIAL_RESTART_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(300);
+
+/// Runs the task created by `make_task` until it returns or the stop signal is received.
+/// If the task panics, it is respawned with an exponential backoff.
+pub async fn supervise<F, Fut>(name: String, mut stop_signal: StopSignal, make_task: F)
+where
+    F: Fn(StopSignal) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let mut backoff = INITIAL_RESTART_BACKOFF;
+
+    loop {
+        let started_at = Instant::now();
+        let mut handle = tokio::spawn(make_task(stop_signal.clone()));
+
+        tokio::select! {
+            result = &mut handle => match result {
+                Ok(()) => {
+                    info!("Task for {} finished", name);
+                    return;
+                }
+                Err(e) if e.is_panic() => {
+                    error!("Task for {} panicked: {}", name, panic_message(e.into_panic()));
+                }
+                Err(e) => {
+                    warn!("Task for {} was cancelled: {}", name, e);
+                    return;
+                }
+            },
+            _ = stop_signal.stopped() => {
+                info!("Stop signal received for {}, waiting for task to finish", name);
+                let _ = handle.await;
+                return;
+            }
+        }
+
+        // the task had been running fine for a while, so don't punish it for an old crash
+        if started_at.elapsed() >= MAX_RESTART_BACKOFF {
+            backoff = INITIAL_RESTART_BACKOFF;
+        }
+
+        warn!("Restarting task for {} in {:?}", name, backoff);
+        tokio::select! {
+            _ = sleep(backoff) => {}
+            _ = stop_signal.stopped() => {
+                info!("Stop signal received for {} while waiting to restart", name);
+                return;
+            }
+        }
+
+        backoff = (backoff * 2).min(MAX_RESTART_BACKOFF);
+    }
+}
+
+/// Waits for every task to finish, until `deadline` completes. The tasks still running then are aborted
+/// and their names are returned.
+pub async fn join_until<D>(mut tasks: Vec<(String, JoinHandle<()>)>, deadline: D) -> Vec<String>
+where
+    D: Future<Output = ()>,
+{
+    tokio::pin!(deadline);
+
+    while let Some((_, handle)) = tasks.first_mut() {
+        tokio::select! {
+            _ = handle => {
+                tasks.remove(0);
+            }
+            _ = &mut deadline => break,
+        }
+    }
+
+    tasks
+        .into_iter()
+        .filter(|(_, handle)| !handle.is_finished())
+        .map(|(name, handle)| {
+            handle.abort();
+            name
+        })
+        .collect()
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        return message.to_string();
+    }
+
+    if let Some(message) = payload.downcast_ref::<String>() {
+        return message.clone();
+    }
+
+    "unknown panic payload".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shutdown::stop_channel;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_supervise_restarts_panicked_task() {
+        let runs = Arc::new(AtomicU32::new(0));
+        let (_stop_trigger, stop_signal) = stop_channel();
+
+        let runs_clone = runs.clone();
+        supervise("TEST".to_string(), stop_signal, move |_| {
+            let runs = runs_clone.clone();
+            async move {
+                if runs.fetch_add(1, Ordering::SeqCst) < 2 {
+                    panic!("boom");
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(3, runs.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_supervise_forwards_stop_signal() {
+        let (stop_trigger, stop_signal) = stop_channel();
+        let supervisor = tokio::spawn(supervise(
+            "TEST".to_string(),
+            stop_signal,
+            |mut stop_signal| async move {
+                stop_signal.stopped().await;
+            },
+        ));
+
+        stop_trigger.stop();
+        supervisor.await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_join_until_aborts_stuck_tasks() {
+        let tasks = vec![
+            ("FAST".to_string(), tokio::spawn(sleep(Duration::from_secs(1)))),
+            ("STUCK".to_string(), tokio::spawn(std::future::pending())),
+            ("SLOW".to_string(), tokio::spawn(sleep(Duration::from_secs(5)))),
+        ];
+
+        let stuck = join_until(tasks, sleep(Duration::from_secs(10))).await;
+        assert_eq!(vec!["STUCK".to_string()], stuck);
+
+        let tasks = vec![("FAST".to_string(), tokio::spawn(sleep(Duration::from_secs(1))))];
+        assert!(join_until(tasks, std::future::pending()).await.is_empty());
+    }
+
+    #[test]
+    fn test_panic_message() {
+        assert_eq!("boom", panic_message(Box::new("boom")));
+        assert_eq!("boom", panic_message(Box::new("boom".to_string())));
+        assert_eq!("unknown panic payload", panic_message(Box::new(42)));
+    }
+}
@@ -0,0 +1,65 @@
+use crate::discord::client::DiscordClient;
+use crate::health::Liveness;
+use crate::shutdown::StopSignal;
+use sd_notify::NotifyState;
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::{debug, info, warn};
+
+/// How often the connection of the Discord clients is checked until they are all connected
+const READY_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Tells systemd that the service is up once every Discord client is connected. Does nothing when not
+/// started by systemd, i.e. without `NOTIFY_SOCKET`.
+pub async fn notify_ready(discord_clients: Vec<DiscordClient>) {
+    for discord_client in discord_clients {
+        while !discord_client.is_connected().await {
+            sleep(READY_POLL_INTERVAL).await;
+        }
+    }
+
+    info!("All Discord clients connected");
+    notify(&[NotifyState::Ready]);
+}
+
+/// Tells systemd that the service is stopping
+pub fn notify_stopping() {
+    notify(&[NotifyState::Stopping]);
+}
+
+/// Sends the watchdog keepalives at half the `WatchdogSec=` of the unit, as long as every ticker makes
+/// progress, so that systemd restarts an instance of which a ticker is stuck. Returns at once if the watchdog
+/// is not enabled for the service.
+pub async fn run_watchdog(liveness: Liveness, mut stop_signal: StopSignal) {
+    let mut timeout_usec = 0;
+    if !sd_notify::watchdog_enabled(false, &mut timeout_usec) {
+        debug!("systemd watchdog is not enabled");
+        return;
+    }
+
+    let interval = Duration::from_micros(timeout_usec) / 2;
+    info!("Sending systemd watchdog keepalives every {:?}", interval);
+
+    loop {
+        let stalled = liveness.stalled();
+        if stalled.is_empty() {
+            notify(&[NotifyState::Watchdog]);
+        } else {
+            warn!(
+                "Tickers not making progress, holding back the systemd watchdog keepalive: {}",
+                stalled.join(", ")
+            );
+        }
+
+        tokio::select! {
+            _ = sleep(interval) => {}
+            _ = stop_signal.stopped() => return,
+        }
+    }
+}
+
+fn notify(state: &[NotifyState]) {
+    if let Err(error) = sd_notify::notify(false, state) {
+        warn!("Error notifying systemd: {}", error);
+    }
+}
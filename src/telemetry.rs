@@ -0,0 +1,83 @@
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{ExporterBuildError, MetricExporter, SpanExporter};
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing::{error, Subscriber};
+use tracing_opentelemetry::MetricsLayer;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// Environment variables of which any turns the export on, the exporters read them as well
+const ENDPOINT_VARS: [&str; 3] = [
+    "OTEL_EXPORTER_OTLP_ENDPOINT",
+    "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+    "OTEL_EXPORTER_OTLP_METRICS_ENDPOINT",
+];
+
+/// Export of the tracing spans and of the `histogram.*` fields of events to an OTLP collector over HTTP,
+/// e.g. Jaeger or Tempo.
+///
+/// Everything is configured by the standard `OTEL_*` environment variables: the export is on once an
+/// OTLP endpoint is set and `OTEL_SDK_DISABLED` is not `true`, `OTEL_SERVICE_NAME` and
+/// `OTEL_RESOURCE_ATTRIBUTES` describe the instance.
+pub struct Telemetry {
+    tracer_provider: SdkTracerProvider,
+    meter_provider: SdkMeterProvider,
+}
+
+impl Telemetry {
+    /// `None` if no OTLP endpoint is set
+    pub fn from_env() -> Result<Option<Self>, ExporterBuildError> {
+        let disabled = std::env::var("OTEL_SDK_DISABLED")
+            .is_ok_and(|value| value.eq_ignore_ascii_case("true"));
+        if disabled
+            || !ENDPOINT_VARS
+                .iter()
+                .any(|var| std::env::var_os(var).is_some())
+        {
+            return Ok(None);
+        }
+
+        let resource = Resource::builder().build();
+
+        let tracer_provider = SdkTracerProvider::builder()
+            .with_batch_exporter(SpanExporter::builder().with_http().build()?)
+            .with_resource(resource.clone())
+            .build();
+
+        let meter_provider = SdkMeterProvider::builder()
+            .with_periodic_exporter(MetricExporter::builder().with_http().build()?)
+            .with_resource(resource)
+            .build();
+
+        Ok(Some(Self {
+            tracer_provider,
+            meter_provider,
+        }))
+    }
+
+    /// Layer sending the spans and the metrics recorded through `tracing` to the exporters. It takes everything up to
+    /// `debug`, the level the metric events are emitted at, whatever the `log_level` of the log.
+    pub fn layer<S>(&self) -> impl Layer<S>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        let tracer = self.tracer_provider.tracer(env!("CARGO_PKG_NAME"));
+        tracing_opentelemetry::layer()
+            .with_tracer(tracer)
+            .and_then(MetricsLayer::new(self.meter_provider.clone()))
+            .with_filter(LevelFilter::DEBUG)
+    }
+
+    /// Exports what is still buffered, to be called before exiting
+    pub fn shutdown(&self) {
+        if let Err(error) = self.tracer_provider.shutdown() {
+            error!("Error shutting down the trace export: {}", error);
+        }
+        if let Err(error) = self.meter_provider.shutdown() {
+            error!("Error shutting down the metric export: {}", error);
+        }
+    }
+}
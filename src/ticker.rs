@@ -0,0 +1,1261 @@
+use bigdecimal::BigDecimal;
+use std::time;
+use tokio::sync::{mpsc, watch};
+use tracing::debug;
+use tracing::error;
+use tracing::warn;
+use tracing::info;
+use tracing::trace;
+use tracing::instrument;
+use tracing::field::Empty;
+use tracing::Span;
+
+use crate::quote::binance_futures::DerivativesInfo;
+use crate::quote::error::QuoteRequestError;
+use crate::quote::opensea;
+use crate::quote::response::AssetQuoteResponse;
+use crate::quote::request::AssetQuoteRequest;
+use crate::quote::request_queue::QuoteRequestSender;
+use crate::shutdown::StopSignal;
+use crate::alert::{error_chain, AlertSender, FailureStreak};
+use crate::bot_update::{BotUpdateInfo, BotUpdateTarget, ChannelNameUpdate, QuoteData, StatusRotation};
+use crate::config::{QuoteSource, TickerConfig, TickerKind};
+use crate::countdown::countdown_update;
+use crate::format::{change_emoji, fits_nickname, format_price, format_price_change, truncate_nickname, PriceFormat};
+use crate::health::{FailedTickers, Liveness, TickerHealth};
+use crate::history::{self, PriceHistory};
+use crate::portfolio::portfolio_value;
+use crate::ratio::price_ratio;
+use crate::ticker_control::{override_changed, TickerOverride};
+
+/// The quote providers of a ticker in the order they are tried, with the request channel of each
+pub(crate) type QuoteJobSenders = Vec<(QuoteSource, QuoteRequestSender)>;
+
+/// Requests the quote from each provider in turn until one succeeds.
+/// Returns the quote along with the provider it came from, or the error of the last provider. `NotFound` is only
+/// returned when the primary provider does not know the asset, a fallback may not list it while the asset exists.
+pub(crate) async fn request_quote_with_fallback(
+    job_senders: &QuoteJobSenders,
+    quote_id: impl Fn(QuoteSource) -> String,
+    vs_currency: &str,
+) -> std::result::Result<(QuoteSource, AssetQuoteResponse), QuoteRequestError> {
+    let mut last_error = QuoteRequestError::Other("no quote provider is configured".to_string());
+    let mut primary_not_found = None;
+
+    for (index, (source, job_sender)) in job_senders.iter().enumerate() {
+        let (resp_sender, mut resp_receiver) = mpsc::unbounded_channel();
+        let request = AssetQuoteRequest {
+            name: quote_id(*source),
+            vs_currency: vs_currency.to_string(),
+            resp_sender,
+        };
+
+        if let Err(e) = job_sender.send(request).await {
+            debug!("Cannot send price request to {:?}: {}", source, e);
+            last_error = e;
+            continue;
+        }
+
+        let result = resp_receiver.recv().await.unwrap_or_else(|| {
+            Err(QuoteRequestError::Other(format!("response channel of {:?} is closed", source)))
+        });
+
+        match result {
+            Ok(response) => return Ok((*source, response)),
+            Err(error) => {
+                debug!("Error getting price from {:?}: {}", source, error);
+                match error {
+                    QuoteRequestError::NotFound(_) if index == 0 => primary_not_found = Some(error),
+                    QuoteRequestError::NotFound(_) => {}
+                    _ => last_error = error,
+                }
+            }
+        }
+    }
+
+    Err(primary_not_found.unwrap_or(last_error))
+}
+
+/// Requests the quote of every holding of a portfolio ticker, and sums them up into a quote
+/// of the whole portfolio. Fails if any holding cannot be quoted, so that no partial value is shown.
+async fn request_portfolio_quote(
+    job_senders: &QuoteJobSenders,
+    ticker_config: &TickerConfig,
+) -> std::result::Result<(QuoteSource, AssetQuoteResponse), QuoteRequestError> {
+    let mut holdings = Vec::new();
+
+    for (id, quantity) in &ticker_config.holdings {
+        let (source, response) = request_quote_with_fallback(
+            job_senders,
+            |source| ticker_config.asset_quote_id(id, source),
+            &ticker_config.vs_currency(),
+        )
+        .await?;
+        debug!("Price of holding {} of {} from {:?} is {}", id, ticker_config.ticker, source, response.price);
+        holdings.push((quantity.clone(), response));
+    }
+
+    let (value, price_change_24h) = portfolio_value(&holdings);
+    Ok((
+        ticker_config.quote_source(),
+        AssetQuoteResponse {
+            name: ticker_config.name.clone(),
+            price: value,
+            // a holding that lost its whole value leaves the value 24h ago, and so the change, unknown
+            label: price_change_24h
+                .is_none()
+                .then(|| generate_unknown_change_status(ticker_config.ticker.as_str())),
+            price_change_24h: price_change_24h.unwrap_or_default(),
+            ..Default::default()
+        },
+    ))
+}
+
+/// Requests the quotes of both assets of a ratio ticker in `vs_currency`, and divides them
+async fn request_ratio_quote(
+    job_senders: &QuoteJobSenders,
+    ticker_config: &TickerConfig,
+) -> std::result::Result<(QuoteSource, AssetQuoteResponse), QuoteRequestError> {
+    let ratio = ticker_config
+        .ratio
+        .as_ref()
+        .ok_or_else(|| QuoteRequestError::Other("no `ratio` assets are configured".to_string()))?;
+
+    let vs_currency = ticker_config.vs_currency();
+    let (source, base) =
+        request_quote_with_fallback(job_senders, |source| ticker_config.asset_quote_id(&ratio.base, source), &vs_currency)
+            .await?;
+    let (_, quote) =
+        request_quote_with_fallback(job_senders, |source| ticker_config.asset_quote_id(&ratio.quote, source), &vs_currency)
+            .await?;
+
+    let (price, price_change_24h) = price_ratio(&base, &quote)?;
+    Ok((
+        source,
+        AssetQuoteResponse {
+            name: ticker_config.name.clone(),
+            price,
+            price_change_24h,
+            ..Default::default()
+        },
+    ))
+}
+
+/// Requests the floor price of an NFT collection in ETH from the NFT providers of the ticker, and the price of ETH
+/// in `vs_currency` from the others for the value of the floor, shown as the status. The floor is still shown if
+/// the conversion fails.
+async fn request_nft_floor_quote(
+    job_senders: &QuoteJobSenders,
+    ticker_config: &TickerConfig,
+) -> std::result::Result<(QuoteSource, AssetQuoteResponse), QuoteRequestError> {
+    let (floor_senders, conversion_senders): (QuoteJobSenders, QuoteJobSenders) = job_senders
+        .iter()
+        .cloned()
+        .partition(|(source, _)| *source == QuoteSource::OpenSea);
+
+    let (source, mut response) =
+        request_quote_with_fallback(&floor_senders, |_| ticker_config.name.clone(), opensea::FLOOR_CURRENCY).await?;
+
+    let vs_currency = ticker_config.vs_currency();
+    let converted = if vs_currency == opensea::FLOOR_CURRENCY {
+        None
+    } else {
+        let eth_id = |source| match source {
+            QuoteSource::Binance => "ETH".to_string(),
+            _ => "ethereum".to_string(),
+        };
+        match request_quote_with_fallback(&conversion_senders, eth_id, &vs_currency).await {
+            Ok((_, eth)) => {
+                let (prefix, suffix) = ticker_config.vs_currency_symbols();
+                let value = format_price(&(&response.price * eth.price), ticker_config.decimals, &ticker_config.price_format);
+                Some(generate_discord_bot_name(value.as_str(), prefix.as_str(), suffix.as_str()))
+            }
+            Err(error) => {
+                warn!("Error converting the floor of {} to {}: {}", ticker_config.ticker, vs_currency, error);
+                None
+            }
+        }
+    };
+
+    response.label = Some(generate_nft_floor_status(converted.as_deref(), ticker_config.ticker.as_str()));
+    Ok((source, response))
+}
+
+/// The last price, name and status sent to Discord for a ticker, and when its price was fetched
+struct LastQuote {
+    updated_at: time::Instant,
+    price: BigDecimal,
+    name: String,
+    status: String,
+    marked_stale: bool,
+    marked_closed: bool,
+    marked_paused: bool,
+}
+
+/// Fetches the price every `frequency` seconds and sends the updates of the bot until stopped. With `run_once`,
+/// it stops after the first fetch instead, adding the ticker to `run_once` if that failed.
+/// Changes made through `/ticker`, received on `control`, apply right away.
+///
+/// The span records how long fetching the price took on the last tick, with every provider tried
+#[allow(clippy::too_many_arguments)]
+#[instrument(skip_all, fields(ticker = ticker_config.ticker, fetch_ms = Empty))]
+pub(crate) async fn run_periodic_fetch_job_loop(
+    mut ticker_config: TickerConfig,
+    mut stop_signal: StopSignal,
+    job_senders: QuoteJobSenders,
+    bot_update_sender: mpsc::UnboundedSender<BotUpdateInfo>,
+    update_target: BotUpdateTarget,
+    alert_sender: AlertSender,
+    derivatives_sender: Option<QuoteRequestSender>,
+    liveness: Liveness,
+    health: TickerHealth,
+    run_once: Option<FailedTickers>,
+    mut control: watch::Receiver<TickerOverride>,
+) {
+    let configured_frequency = ticker_config.frequency;
+    let configured_schedule = ticker_config.schedule.clone();
+    let stale_after = ticker_config.stale_after();
+    let mut last_quote: Option<LastQuote> = None;
+    let mut failure_streak = FailureStreak::new(alert_sender.failure_threshold());
+    let mut missing_asset_streak = 0;
+
+    // only keep the prices needed by the moving averages and the high and low of the status
+    let sma_windows = ticker_config
+        .status_format
+        .as_deref()
+        .map(history::sma_windows)
+        .unwrap_or_default();
+    let uses_high_low = ticker_config.status_format.as_deref().is_some_and(history::uses_high_low);
+    let mut price_history = sma_windows
+        .iter()
+        .map(|(_, duration)| *duration)
+        .chain(uses_high_low.then_some(history::HIGH_LOW_WINDOW))
+        .max()
+        .map(PriceHistory::new);
+
+    loop {
+        if stop_signal.is_stopped() {
+            info!("Stop signal received for {}", ticker_config.ticker);
+            break;
+        }
+
+        let ticker_override = control.borrow_and_update().clone();
+        // like `frequency` in the config, kept within what the provider allows
+        ticker_config.frequency = ticker_override
+            .frequency
+            .map(|frequency| frequency.clamp(ticker_config.min_frequency(), TickerConfig::MAX_FREQUENCY_SECS))
+            .unwrap_or(configured_frequency);
+        ticker_config.schedule = match ticker_override.frequency {
+            Some(_) => None,
+            None => configured_schedule.clone(),
+        };
+        health.record_frequency(
+            &ticker_config.ticker,
+            ticker_config.schedule.is_none().then_some(ticker_config.frequency),
+            ticker_override.paused,
+        );
+
+        if ticker_override.paused {
+            // a paused ticker is not expected to make progress until resumed
+            liveness.remove(&ticker_config.ticker);
+            if last_quote.as_ref().is_none_or(|last_quote| !last_quote.marked_paused) {
+                info!("{} is paused through /ticker", ticker_config.ticker);
+
+                if let Err(e) = bot_update_sender.send(BotUpdateInfo {
+                    ticker: ticker_config.ticker.clone(),
+                    name: last_quote
+                        .as_ref()
+                        .map(|last_quote| last_quote.name.clone())
+                        .unwrap_or_else(|| ticker_config.ticker.clone()),
+                    status: generate_paused_status(ticker_config.ticker.as_str()),
+                    target: update_target.clone(),
+                    channel_name: None,
+                    status_rotation: None,
+                    quote: None,
+                    fetched_at: None,
+                }) {
+                    warn!("Failed to send bot update: {}", e);
+                }
+                if let Some(last_quote) = last_quote.as_mut() {
+                    last_quote.marked_paused = true;
+                }
+            }
+
+            tokio::select! {
+                _ = stop_signal.stopped() => {
+                    info!("Received stop signal for {}, quit loop", ticker_config.ticker);
+                    break;
+                }
+                _ = override_changed(&mut control) => continue,
+            }
+        }
+        liveness.beat(&ticker_config.ticker, next_tick_delay(&ticker_config, &job_senders));
+
+        // a countdown is computed locally, there is nothing to fetch
+        if let Some(countdown) = ticker_config.countdown.as_ref().filter(|_| ticker_config.kind == TickerKind::Countdown) {
+            let (name, status) = countdown_update(countdown, &ticker_config.name, chrono::Utc::now());
+            debug!("Update countdown {} to {} ({})", ticker_config.ticker, name, status);
+
+            if let Err(e) = bot_update_sender.send(BotUpdateInfo {
+                ticker: ticker_config.ticker.clone(),
+                name,
+                status,
+                target: update_target.clone(),
+                channel_name: None,
+                status_rotation: None,
+                quote: None,
+                fetched_at: None,
+            }) {
+                warn!("Failed to send bot update: {}", e);
+            }
+
+            if run_once.is_some() {
+                break;
+            }
+            if wait_next_tick(next_tick_delay(&ticker_config, &job_senders), &mut stop_signal, &mut control).await {
+                info!("Received stop signal for {}, quit loop", ticker_config.ticker);
+                break;
+            }
+            continue;
+        }
+
+        let market_closed = ticker_config
+            .market_hours
+            .as_ref()
+            .is_some_and(|market_hours| !market_hours.is_open(chrono::Utc::now()));
+
+        // still fetch once while closed, so that the bot shows the last price after a restart
+        if market_closed {
+            if let Some(last_quote) = last_quote.as_mut() {
+                if !last_quote.marked_closed {
+                    info!("Market of {} is closed, pausing price updates", ticker_config.ticker);
+
+                    if let Err(e) = bot_update_sender.send(BotUpdateInfo {
+                        ticker: ticker_config.ticker.clone(),
+                        name: last_quote.name.clone(),
+                        status: generate_market_closed_status(ticker_config.ticker.as_str()),
+                        target: update_target.clone(),
+                        channel_name: None,
+                        status_rotation: None,
+                        quote: None,
+                        fetched_at: None,
+                    }) {
+                        warn!("Failed to send bot update: {}", e);
+                    }
+                    last_quote.marked_closed = true;
+                }
+
+                if wait_next_tick(next_tick_delay(&ticker_config, &job_senders), &mut stop_signal, &mut control).await {
+                    info!(
+                        "Received stop signal for {}, quit loop",
+                        ticker_config.ticker
+                    );
+                    break;
+                }
+                continue;
+            }
+        }
+
+        debug!(
+            "Timer ticked for {}, fetching price...",
+            ticker_config.ticker
+        );
+
+        let fetch_started_at = tokio::time::Instant::now();
+        let get_price_chan_response = match ticker_config.kind {
+            TickerKind::Asset
+            | TickerKind::Gas
+            | TickerKind::FearAndGreed
+            | TickerKind::GlobalMarket
+            | TickerKind::StakingApr => {
+                request_quote_with_fallback(
+                    &job_senders,
+                    |source| ticker_config.quote_id(source),
+                    &ticker_config.vs_currency(),
+                )
+                .await
+            }
+            TickerKind::Portfolio => request_portfolio_quote(&job_senders, &ticker_config).await,
+            TickerKind::Ratio => request_ratio_quote(&job_senders, &ticker_config).await,
+            TickerKind::NftFloor => request_nft_floor_quote(&job_senders, &ticker_config).await,
+            // only without a target, the others are computed above
+            TickerKind::Countdown => Err(QuoteRequestError::Other("countdown has no `countdown` target".to_string())),
+        };
+
+        let fetch_ms = fetch_started_at.elapsed().as_millis() as u64;
+        Span::current().record("fetch_ms", fetch_ms);
+        debug!(
+            histogram.fetch_ms = fetch_ms,
+            "Fetching the price of {} took {} ms",
+            ticker_config.ticker,
+            fetch_ms
+        );
+
+        let get_price_response = match get_price_chan_response {
+            Ok(r) => r,
+            // retrying forever cannot fix an asset the provider does not know, e.g. a typo in `name`
+            Err(error @ QuoteRequestError::NotFound(_)) => {
+                error!(
+                    "{} is not found by its quote provider, disabling the ticker: {}",
+                    ticker_config.ticker, error
+                );
+
+                if let Err(e) = bot_update_sender.send(BotUpdateInfo {
+                    ticker: ticker_config.ticker.clone(),
+                    name: last_quote
+                        .as_ref()
+                        .map(|last_quote| last_quote.name.clone())
+                        .unwrap_or_else(|| ticker_config.ticker.clone()),
+                    status: generate_config_error_status(ticker_config.ticker.as_str()),
+                    target: update_target.clone(),
+                    channel_name: None,
+                    status_rotation: None,
+                    quote: None,
+                    fetched_at: None,
+                }) {
+                    warn!("Failed to send bot update: {}", e);
+                }
+
+                let message = format!(
+                    "🛑 {}: disabled, the asset is not found by its quote provider: {}",
+                    ticker_config.ticker,
+                    error_chain(&error)
+                );
+                alert_sender.send(update_target.discord_client(), &message).await;
+                record_run_once_failure(&run_once, &ticker_config.ticker);
+                break;
+            }
+            Err(error) => {
+                missing_asset_streak = match error {
+                    QuoteRequestError::MissingAsset(_) => missing_asset_streak + 1,
+                    _ => 0,
+                };
+
+                // a renamed or delisted asset will not come back, stop asking for it
+                let missing_asset_threshold = ticker_config.missing_asset_threshold();
+                if missing_asset_threshold > 0 && missing_asset_streak >= missing_asset_threshold {
+                    error!(
+                        "{} was missing from {} responses in a row, pausing the ticker: {}",
+                        ticker_config.ticker, missing_asset_streak, error
+                    );
+
+                    if let Err(e) = bot_update_sender.send(BotUpdateInfo {
+                        ticker: ticker_config.ticker.clone(),
+                        name: last_quote
+                            .as_ref()
+                            .map(|last_quote| last_quote.name.clone())
+                            .unwrap_or_else(|| ticker_config.ticker.clone()),
+                        status: generate_config_error_status(ticker_config.ticker.as_str()),
+                        target: update_target.clone(),
+                        channel_name: None,
+                        status_rotation: None,
+                        quote: None,
+                        fetched_at: None,
+                    }) {
+                        warn!("Failed to send bot update: {}", e);
+                    }
+
+                    let message = format!(
+                        "🛑 {}: paused, the asset was missing from {} responses in a row, check its `name`: {}",
+                        ticker_config.ticker,
+                        missing_asset_streak,
+                        error_chain(&error)
+                    );
+                    alert_sender.send(update_target.discord_client(), &message).await;
+                    record_run_once_failure(&run_once, &ticker_config.ticker);
+                    break;
+                }
+
+                if error.is_permanent() {
+                    error!("Error getting price for {}, check the config: {}", ticker_config.ticker, error);
+                } else {
+                    warn!(
+                        "Error getting price for {}: {}",
+                        ticker_config.ticker, error
+                    );
+                }
+
+                health.record_failure(&ticker_config.ticker, error_chain(&error));
+                if failure_streak.record_failure() {
+                    let message = format!(
+                        "⚠️ {}: fetching the price failed {} times in a row: {}",
+                        ticker_config.ticker,
+                        failure_streak.count(),
+                        error_chain(&error)
+                    );
+                    alert_sender.send(update_target.discord_client(), &message).await;
+                }
+
+                if let Some(last_quote) = last_quote.as_mut() {
+                    if !last_quote.marked_stale && last_quote.updated_at.elapsed() >= stale_after {
+                        warn!(
+                            "Price of {} has not been updated for {:?}, marking it as stale",
+                            ticker_config.ticker, last_quote.updated_at.elapsed()
+                        );
+
+                        if let Err(e) = bot_update_sender.send(BotUpdateInfo {
+                            ticker: ticker_config.ticker.clone(),
+                            name: last_quote.name.clone(),
+                            status: mark_discord_bot_status_stale(&last_quote.status, &ticker_config.stale_marker()),
+                            target: update_target.clone(),
+                            channel_name: None,
+                            status_rotation: None,
+                            quote: None,
+                            fetched_at: None,
+                        }) {
+                            warn!("Failed to send bot update: {}", e);
+                        }
+                        last_quote.marked_stale = true;
+                    }
+                }
+
+                if run_once.is_some() {
+                    record_run_once_failure(&run_once, &ticker_config.ticker);
+                    break;
+                }
+
+                if wait_next_tick(next_tick_delay(&ticker_config, &job_senders), &mut stop_signal, &mut control).await {
+                    info!(
+                        "Received stop signal for {}, quit loop",
+                        ticker_config.ticker
+                    );
+                    break;
+                }
+                continue;
+            }
+        };
+
+        let (quote_source, get_price_response) = get_price_response;
+        missing_asset_streak = 0;
+        trace!("Got price response for {} from {:?}", get_price_response.name, quote_source);
+        health.record_success(&ticker_config.ticker, quote_source, chrono::Utc::now());
+
+        let failure_count = failure_streak.count();
+        if failure_streak.record_success() {
+            let message = format!(
+                "✅ {}: fetching the price works again after {} failures",
+                ticker_config.ticker, failure_count
+            );
+            alert_sender.send(update_target.discord_client(), &message).await;
+        }
+
+        let not_modified = get_price_response.not_modified;
+        // outside the regular session, show the latest extended-hours trade instead if asked to
+        let (price, price_change_24h, session) = match get_price_response.extended_hours {
+            Some(extended_hours) if ticker_config.extended_hours => (
+                extended_hours.price,
+                extended_hours.price_change,
+                Some(extended_hours.session),
+            ),
+            _ => (get_price_response.price, get_price_response.price_change_24h, None),
+        };
+
+        if let Some(price_history) = price_history.as_mut() {
+            price_history.record(tokio::time::Instant::now(), price.clone());
+        }
+
+        // leave the bot as is if the price barely moved or the provider said it did not change, unless it shows
+        // the stale or closed marker
+        if let Some(last_quote) = last_quote.as_mut() {
+            if !last_quote.marked_stale
+                && !last_quote.marked_closed
+                && !last_quote.marked_paused
+                && ((not_modified && last_quote.price == price)
+                    || !ticker_config.is_significant_price_change(&last_quote.price, &price))
+            {
+                debug!(
+                    "Price of {} moved from {} to {} only, skip updating the bot",
+                    ticker_config.ticker, last_quote.price, price
+                );
+                last_quote.updated_at = time::Instant::now();
+
+                if wait_next_tick(next_tick_delay(&ticker_config, &job_senders), &mut stop_signal, &mut control).await {
+                    info!(
+                        "Received stop signal for {}, quit loop",
+                        ticker_config.ticker
+                    );
+                    break;
+                }
+                continue;
+            }
+        }
+
+        let formatted_price = format_price(&price, ticker_config.decimals, &ticker_config.price_format);
+        let formatted_price_change_24h = format_price_change(
+            &price_change_24h,
+            ticker_config.change_decimals(),
+            ticker_config.price_format.locale,
+        );
+
+        debug!(
+            "Price for {} from {:?} is {} {} (original value: {}), change in 24h is {}%",
+            ticker_config.ticker, quote_source, formatted_price, ticker_config.vs_currency().to_uppercase(), price, formatted_price_change_24h
+        );
+
+        if stop_signal.is_stopped() {
+            info!("Stop signal received for {}", ticker_config.ticker);
+            break;
+        }
+
+        let discord_bot_name = generate_discord_bot_nickname(
+            formatted_price.as_str(),
+            ticker_config.currency_symbol_prefix().as_str(),
+            ticker_config.currency_symbol_suffix().as_str(),
+        );
+        let gas_prices = get_price_response.gas_prices;
+        let label = get_price_response.label;
+        let day_range = get_price_response.day_range;
+        let mut discord_bot_status = match &ticker_config.status_format {
+            Some(status_format) => {
+                let now = tokio::time::Instant::now();
+                let moving_averages: Vec<MovingAverage> = sma_windows
+                    .iter()
+                    .filter_map(|(window, duration)| {
+                        let sma = price_history.as_ref()?.sma(*duration, now)?;
+                        Some(MovingAverage {
+                            window: window.clone(),
+                            price: generate_discord_bot_name(
+                                format_price(&sma, ticker_config.decimals, &ticker_config.price_format).as_str(),
+                                ticker_config.currency_symbol_prefix().as_str(),
+                                ticker_config.currency_symbol_suffix().as_str(),
+                            ),
+                            below_price: sma <= price,
+                        })
+                    })
+                    .collect();
+
+                // the provider's range of the day, the recorded prices otherwise
+                let high_low = day_range
+                    .map(|day_range| (day_range.high, day_range.low))
+                    .or_else(|| price_history.as_ref()?.high_low(history::HIGH_LOW_WINDOW, now))
+                    .map(|(high, low)| {
+                        let format = |price: &BigDecimal| {
+                            generate_discord_bot_name(
+                                format_price(price, ticker_config.decimals, &ticker_config.price_format).as_str(),
+                                ticker_config.currency_symbol_prefix().as_str(),
+                                ticker_config.currency_symbol_suffix().as_str(),
+                            )
+                        };
+                        (format(&high), format(&low))
+                    });
+
+                let derivatives_info = match &derivatives_sender {
+                    Some(derivatives_sender) => {
+                        let derivatives_senders = vec![(QuoteSource::BinanceFutures, derivatives_sender.clone())];
+                        match request_quote_with_fallback(&derivatives_senders, |_| ticker_config.asset_symbol(), "usd")
+                            .await
+                        {
+                            Ok((_, response)) => response.derivatives,
+                            Err(error) => {
+                                warn!("Error getting funding rate and open interest of {}: {}", ticker_config.ticker, error);
+                                None
+                            }
+                        }
+                    }
+                    None => None,
+                };
+
+                apply_derivatives_template(
+                    apply_high_low_template(
+                        apply_moving_average_template(
+                            apply_price_template(
+                                status_format,
+                                ticker_config.ticker.as_str(),
+                                discord_bot_name.as_str(),
+                                formatted_price_change_24h.as_str(),
+                                ticker_config.trend_symbols.symbol(&price_change_24h),
+                                change_emoji(&ticker_config.change_emojis, &price_change_24h),
+                            )
+                            .as_str(),
+                            &moving_averages,
+                        )
+                        .as_str(),
+                        high_low.as_ref(),
+                    )
+                    .as_str(),
+                    derivatives_info.as_ref(),
+                )
+            }
+            None => match (&label, &gas_prices) {
+                (Some(label), _) => label.clone(),
+                (None, Some(gas_prices)) => generate_gas_status(
+                    format_price(&gas_prices.fast, ticker_config.decimals, &ticker_config.price_format).as_str(),
+                    format_price(&gas_prices.standard, ticker_config.decimals, &ticker_config.price_format).as_str(),
+                    format_price(&gas_prices.slow, ticker_config.decimals, &ticker_config.price_format).as_str(),
+                ),
+                (None, None) => prefix_change_emoji(
+                    &generate_discord_bot_status(formatted_price_change_24h.as_str(), ticker_config.ticker.as_str()),
+                    change_emoji(&ticker_config.change_emojis, &price_change_24h),
+                ),
+            },
+        };
+        if let Some(session) = session {
+            discord_bot_status = tag_extended_hours_status(&discord_bot_status, session.tag());
+        }
+
+        debug!(
+            "Update Discord bot name for {}, set to {} ({})...",
+            ticker_config.ticker, discord_bot_name, discord_bot_status
+        );
+
+        let mut secondary_statuses = Vec::new();
+        // assets whose symbol could not be resolved at startup are left out
+        for asset in ticker_config.secondary_assets.iter().filter(|asset| !asset.name.is_empty()) {
+            let result = request_quote_with_fallback(
+                &job_senders,
+                |source| asset.quote_id(source),
+                &ticker_config.vs_currency(),
+            )
+            .await;
+
+            match result {
+                Ok((_, response)) => {
+                    let formatted_price = format_price(
+                        &response.price,
+                        asset.decimals.unwrap_or(ticker_config.decimals),
+                        &ticker_config.price_format,
+                    );
+                    secondary_statuses.push(generate_secondary_asset_status(
+                        asset.ticker.as_str(),
+                        generate_discord_bot_name(
+                            formatted_price.as_str(),
+                            ticker_config.currency_symbol_prefix().as_str(),
+                            ticker_config.currency_symbol_suffix().as_str(),
+                        )
+                        .as_str(),
+                    ));
+                }
+                Err(error) => warn!(
+                    "Error getting price of secondary asset {} for {}: {}",
+                    asset.ticker, ticker_config.ticker, error
+                ),
+            }
+        }
+
+        let status_rotation = if secondary_statuses.is_empty() {
+            None
+        } else {
+            Some(StatusRotation {
+                statuses: secondary_statuses,
+                interval: ticker_config.status_rotation_interval(),
+            })
+        };
+
+        let channel_name = ticker_config.voice_channel_id.map(|channel_id| ChannelNameUpdate {
+            channel_id,
+            name: apply_price_template(
+                ticker_config.voice_channel_name_format(),
+                ticker_config.ticker.as_str(),
+                discord_bot_name.as_str(),
+                formatted_price_change_24h.as_str(),
+                ticker_config.trend_symbols.symbol(&price_change_24h),
+                change_emoji(&ticker_config.change_emojis, &price_change_24h),
+            ),
+        });
+
+        last_quote = Some(LastQuote {
+            updated_at: time::Instant::now(),
+            price: price.clone(),
+            name: discord_bot_name.clone(),
+            status: discord_bot_status.clone(),
+            marked_stale: false,
+            marked_closed: false,
+            marked_paused: false,
+        });
+
+        if let Err(e) = bot_update_sender.send(BotUpdateInfo {
+            ticker: ticker_config.ticker.clone(),
+            name: discord_bot_name,
+            status: discord_bot_status,
+            target: update_target.clone(),
+            channel_name,
+            status_rotation,
+            quote: Some(QuoteData {
+                price,
+                price_change_24h,
+                vs_currency: ticker_config.vs_currency(),
+            }),
+            fetched_at: Some(fetch_started_at),
+        }) {
+            warn!("Failed to send bot update: {}", e);
+        }
+
+        if run_once.is_some() {
+            break;
+        }
+
+        if stop_signal.is_stopped() {
+            info!("Stop signal received for {}", ticker_config.ticker);
+            break;
+        }
+
+        if wait_next_tick(next_tick_delay(&ticker_config, &job_senders), &mut stop_signal, &mut control).await {
+            info!(
+                "Received stop signal for {}, quit loop",
+                ticker_config.ticker
+            );
+            break;
+        }
+    }
+
+    // a disabled or paused ticker is not expected to make progress anymore
+    liveness.remove(&ticker_config.ticker);
+}
+
+/// Waits `delay` for the next tick, returning early once the override of `/ticker` changes so that it applies
+/// right away. Returns whether the ticker was stopped meanwhile.
+async fn wait_next_tick(
+    delay: time::Duration,
+    stop_signal: &mut StopSignal,
+    control: &mut watch::Receiver<TickerOverride>,
+) -> bool {
+    tokio::select! {
+        _ = stop_signal.stopped() => true,
+        _ = override_changed(control) => false,
+        _ = tokio::time::sleep(delay) => false,
+    }
+}
+
+/// Time until the next tick. While a provider of the ticker rate limits, see `Pacing`, it is `frequency` stretched
+/// by the provider, without aligning it to the clock; scheduled tickers keep their times.
+fn next_tick_delay(ticker_config: &TickerConfig, job_senders: &QuoteJobSenders) -> time::Duration {
+    let stretch = job_senders
+        .iter()
+        .map(|(_, job_sender)| job_sender.pacing().stretch())
+        .fold(1.0, f64::max);
+    if stretch > 1.0 && ticker_config.schedule.is_none() {
+        return time::Duration::from_secs(ticker_config.frequency).mul_f64(stretch);
+    }
+    ticker_config.next_tick_delay(chrono::Utc::now())
+}
+
+/// Counts the ticker as failed in the run-once mode
+fn record_run_once_failure(run_once: &Option<FailedTickers>, ticker: &str) {
+    if let Some(failed_tickers) = run_once {
+        failed_tickers.lock().unwrap().insert(ticker.to_string());
+    }
+}
+
+/// Name of the bot as a nickname, dropping the currency suffix and then the prefix, e.g. an emoji, if the
+/// name would not fit in a nickname, and cutting the price only as the last resort
+fn generate_discord_bot_nickname(
+    formatted_price: &str,
+    vs_currency_symbol_prefix: &str,
+    vs_currency_symbol_suffix: &str,
+) -> String {
+    [
+        (vs_currency_symbol_prefix, vs_currency_symbol_suffix),
+        (vs_currency_symbol_prefix, ""),
+        ("", ""),
+    ]
+    .into_iter()
+    .map(|(prefix, suffix)| generate_discord_bot_name(formatted_price, prefix, suffix))
+    .find(|name| fits_nickname(name))
+    .unwrap_or_else(|| truncate_nickname(formatted_price))
+}
+
+fn generate_discord_bot_name(
+    formatted_price: &str,
+    vs_currency_symbol_prefix: &str,
+    vs_currency_symbol_suffix: &str,
+) -> String {
+    if vs_currency_symbol_suffix.is_empty() {
+        return format!("{}{}", vs_currency_symbol_prefix, formatted_price);
+    }
+
+    format!(
+        "{}{} {}",
+        vs_currency_symbol_prefix, formatted_price, vs_currency_symbol_suffix
+    )
+}
+
+fn generate_discord_bot_status(formatted_price_change: &str, ticker: &str) -> String {
+    format!("{}% | {}", formatted_price_change, ticker)
+}
+
+/// Puts the emoji of the bucket of the change in front of the status, if the change is in one
+fn prefix_change_emoji(status: &str, change_emoji: &str) -> String {
+    if change_emoji.is_empty() {
+        return status.to_string();
+    }
+
+    format!("{} {}", change_emoji, status)
+}
+
+fn generate_gas_status(fast: &str, standard: &str, slow: &str) -> String {
+    format!("fast {} | std {} | slow {}", fast, standard, slow)
+}
+
+fn generate_nft_floor_status(converted_floor: Option<&str>, ticker: &str) -> String {
+    match converted_floor {
+        Some(converted_floor) => format!("≈ {} | {}", converted_floor, ticker),
+        None => format!("floor | {}", ticker),
+    }
+}
+
+fn generate_secondary_asset_status(ticker: &str, price: &str) -> String {
+    format!("{} {}", ticker, price)
+}
+
+fn apply_price_template(
+    name_format: &str,
+    ticker: &str,
+    price: &str,
+    formatted_price_change: &str,
+    trend: &str,
+    change_emoji: &str,
+) -> String {
+    name_format
+        .replace("{trend}", trend)
+        .replace("{change_emoji}", change_emoji)
+        .replace("{ticker}", ticker)
+        .replace("{price}", price)
+        .replace("{change}", formatted_price_change)
+}
+
+/// A moving average for the `{sma_<window>}` and `{vs_sma_<window>}` placeholders
+struct MovingAverage {
+    window: String,
+    price: String,
+    below_price: bool,
+}
+
+fn apply_moving_average_template(template: &str, moving_averages: &[MovingAverage]) -> String {
+    moving_averages
+        .iter()
+        .fold(template.to_string(), |template, moving_average| {
+            let position = if moving_average.below_price { "above ▲" } else { "below ▼" };
+            template
+                .replace(&format!("{{sma_{}}}", moving_average.window), &moving_average.price)
+                .replace(&format!("{{vs_sma_{}}}", moving_average.window), position)
+        })
+}
+
+/// Fills in `{high_24h}` and `{low_24h}`, with `n/a` if neither the provider nor the history has them yet
+fn apply_high_low_template(template: &str, high_low: Option<&(String, String)>) -> String {
+    let (high, low) = match high_low {
+        Some((high, low)) => (high.as_str(), low.as_str()),
+        None => ("n/a", "n/a"),
+    };
+
+    template.replace("{high_24h}", high).replace("{low_24h}", low)
+}
+
+/// Fills in `{funding}` and `{open_interest}`, with `n/a` if they could not be fetched
+fn apply_derivatives_template(template: &str, derivatives_info: Option<&DerivativesInfo>) -> String {
+    let (funding, open_interest) = match derivatives_info {
+        Some(info) => (
+            format!("{:+.4}%", info.funding_rate_percent),
+            format_price(
+                &info.open_interest,
+                2,
+                &PriceFormat {
+                    compact: true,
+                    ..Default::default()
+                },
+            ),
+        ),
+        None => ("n/a".to_string(), "n/a".to_string()),
+    };
+
+    template
+        .replace("{funding}", &funding)
+        .replace("{open_interest}", &open_interest)
+}
+
+fn generate_market_closed_status(ticker: &str) -> String {
+    format!("closed | {}", ticker)
+}
+
+fn generate_unknown_change_status(ticker: &str) -> String {
+    format!("n/a | {}", ticker)
+}
+
+fn generate_paused_status(ticker: &str) -> String {
+    format!("⏸ paused | {}", ticker)
+}
+
+fn generate_config_error_status(ticker: &str) -> String {
+    format!("⚠ config error | {}", ticker)
+}
+
+fn tag_extended_hours_status(status: &str, session_tag: &str) -> String {
+    format!("{} {}", session_tag, status)
+}
+
+fn mark_discord_bot_status_stale(status: &str, stale_marker: &str) -> String {
+    format!("{} {}", status, stale_marker)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quote::mock::MockProvider;
+    use crate::quote::req_consumer::consume_quote_requests;
+    use crate::quote::request_queue::quote_request_queue;
+    use crate::shutdown::stop_channel;
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    fn mock_job_sender(
+        results: Vec<std::result::Result<AssetQuoteResponse, QuoteRequestError>>,
+    ) -> QuoteRequestSender {
+        let (job_sender, job_receiver) = quote_request_queue(1);
+        tokio::spawn(consume_quote_requests(
+            job_receiver,
+            Arc::new(MockProvider::new(results)),
+            time::Duration::from_secs(30),
+            1,
+            stop_channel().1,
+        ));
+        job_sender
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_request_quote_with_fallback() {
+        let job_senders: QuoteJobSenders = vec![
+            (QuoteSource::CoinGecko, mock_job_sender((0..3).map(|_| Err("rate limited".into())).collect())),
+            (
+                QuoteSource::Binance,
+                mock_job_sender(vec![Ok(AssetQuoteResponse {
+                    name: "BTC".to_string(),
+                    price: BigDecimal::from_str("65761.12").unwrap(),
+                    price_change_24h: BigDecimal::from_str("1.5").unwrap(),
+                    ..Default::default()
+                })]),
+            ),
+        ];
+
+        let (source, response) = request_quote_with_fallback(
+            &job_senders,
+            |source| format!("{:?}", source),
+            "usd",
+        )
+        .await
+        .unwrap();
+        assert_eq!(QuoteSource::Binance, source);
+        assert_eq!("65761.12", response.price.to_string());
+
+        // both providers are out of results now, the error of the last one is returned
+        let err = request_quote_with_fallback(&job_senders, |source| format!("{:?}", source), "usd")
+            .await
+            .unwrap_err();
+        assert_eq!("Other Error: no more mock results", err.to_string());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_request_quote_with_fallback_not_found() {
+        let not_found = || Err(QuoteRequestError::NotFound("unknown asset".to_string()));
+        let job_senders: QuoteJobSenders = vec![
+            (QuoteSource::CoinGecko, mock_job_sender(vec![Err(QuoteRequestError::RateLimited("slow down".to_string())), not_found()])),
+            (QuoteSource::Binance, mock_job_sender(vec![not_found(), Err(QuoteRequestError::RateLimited("slow down".to_string()))])),
+        ];
+
+        // a fallback not listing the asset does not make it unknown
+        let err = request_quote_with_fallback(&job_senders, |source| format!("{:?}", source), "usd")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, QuoteRequestError::RateLimited(_)), "{}", err);
+
+        // the primary provider not knowing it does
+        let err = request_quote_with_fallback(&job_senders, |source| format!("{:?}", source), "usd")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, QuoteRequestError::NotFound(_)), "{}", err);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_request_nft_floor_quote() {
+        let quote = |price: &str| AssetQuoteResponse {
+            name: String::new(),
+            price: BigDecimal::from_str(price).unwrap(),
+            price_change_24h: BigDecimal::from(0),
+            ..Default::default()
+        };
+        let job_senders: QuoteJobSenders = vec![
+            (QuoteSource::OpenSea, mock_job_sender(vec![Ok(quote("11.82")), Ok(quote("11.8"))])),
+            (QuoteSource::CoinGecko, mock_job_sender(vec![Ok(quote("3400"))])),
+        ];
+        let ticker_config: TickerConfig = serde_json::from_value(serde_json::json!({
+            "ticker": "PPG",
+            "name": "pudgypenguins",
+            "kind": "nft_floor",
+            "decimals": 0
+        }))
+        .unwrap();
+
+        let (source, response) = request_nft_floor_quote(&job_senders, &ticker_config).await.unwrap();
+        assert_eq!(QuoteSource::OpenSea, source);
+        assert_eq!("11.82", response.price.to_string());
+        assert_eq!(Some("≈ $40188 | PPG"), response.label.as_deref());
+
+        // CoinGecko is out of results, the floor is shown without its value
+        let (_, response) = request_nft_floor_quote(&job_senders, &ticker_config).await.unwrap();
+        assert_eq!("11.8", response.price.to_string());
+        assert_eq!(Some("floor | PPG"), response.label.as_deref());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_next_tick_delay_stretched() {
+        let ticker_config: TickerConfig =
+            serde_json::from_value(serde_json::json!({"ticker": "BTC", "frequency": 60, "align_to_clock": true})).unwrap();
+        let (job_sender, _job_receiver) = quote_request_queue(1);
+        let job_senders: QuoteJobSenders = vec![(QuoteSource::Binance, job_sender.clone())];
+        assert!(next_tick_delay(&ticker_config, &job_senders) <= time::Duration::from_secs(60));
+
+        job_sender.pacing().record_rate_limited();
+        assert_eq!(time::Duration::from_secs(90), next_tick_delay(&ticker_config, &job_senders));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stop_while_fetching_fails() {
+        let ticker_config: TickerConfig =
+            serde_json::from_value(serde_json::json!({"ticker": "BTC", "frequency": 3600})).unwrap();
+        let (stop_trigger, stop_signal) = stop_channel();
+        let health = TickerHealth::default();
+        let handle = tokio::spawn(run_periodic_fetch_job_loop(
+            ticker_config,
+            stop_signal,
+            vec![(QuoteSource::CoinGecko, mock_job_sender(Vec::new()))],
+            mpsc::unbounded_channel().0,
+            BotUpdateTarget::BoardOnly,
+            AlertSender::new(reqwest::Client::new(), Default::default()),
+            None,
+            Liveness::default(),
+            health.clone(),
+            None,
+            watch::channel(TickerOverride::default()).1,
+        ));
+
+        while health.report("BTC").is_none_or(|report| report.error_streak == 0) {
+            tokio::time::sleep(time::Duration::from_millis(100)).await;
+        }
+
+        // waiting for the next tick after the failure, not for a whole `frequency`
+        stop_trigger.stop();
+        tokio::time::timeout(time::Duration::from_secs(1), handle)
+            .await
+            .expect("the loop ignored the stop signal")
+            .unwrap();
+    }
+
+    #[test]
+    fn test_generate_config_error_status() {
+        assert_eq!("⚠ config error | BTC", generate_config_error_status("BTC"));
+    }
+
+    #[test]
+    fn test_generate_discord_bot_nickname() {
+        assert_eq!("$65,761.23 USD", generate_discord_bot_nickname("65,761.23", "$", "USD"));
+        assert_eq!(
+            "🟢▲ 0.000000071234567 BTC",
+            generate_discord_bot_nickname("0.000000071234567", "🟢▲ ", "BTC")
+        );
+        assert_eq!(
+            "🟢▲ 0.000000071234567890123456",
+            generate_discord_bot_nickname("0.000000071234567890123456", "🟢▲ ", "BTC")
+        );
+        assert_eq!(
+            "0.000000071234567890123456789",
+            generate_discord_bot_nickname("0.000000071234567890123456789", "🟢▲ ", "BTC")
+        );
+        assert_eq!(
+            "0.00000007123456789012345678901…",
+            generate_discord_bot_nickname("0.0000000712345678901234567890123", "", "")
+        );
+    }
+
+    #[test]
+    fn test_generate_discord_bot_name() {
+        assert_eq!("$1234.56", generate_discord_bot_name("1234.56", "$", ""));
+        assert_eq!("$1234.56   space  ", generate_discord_bot_name("1234.56", "$", "  space  "));
+        assert_eq!("$1234.56 USD", generate_discord_bot_name("1234.56", "$", "USD"));
+        assert_eq!("1234.56 USD", generate_discord_bot_name("1234.56", "", "USD"));
+        assert_eq!("1234.56", generate_discord_bot_name("1234.56", "", ""));
+    }
+
+    #[test]
+    fn test_generate_discord_bot_status() {
+        assert_eq!("+12.34% | TICKER", generate_discord_bot_status("+12.34", "TICKER"));
+        assert_eq!("-0.12% | TICKER", generate_discord_bot_status("-0.12", "TICKER"));
+        assert_eq!("+0.00% | TICKER", generate_discord_bot_status("+0.00", "TICKER"));
+    }
+
+    #[test]
+    fn test_prefix_change_emoji() {
+        assert_eq!("🚀 +12.34% | TICKER", prefix_change_emoji("+12.34% | TICKER", "🚀"));
+        assert_eq!("+1.20% | TICKER", prefix_change_emoji("+1.20% | TICKER", ""));
+    }
+
+    #[test]
+    fn test_generate_gas_status() {
+        assert_eq!("fast 1.25 | std 0.60 | slow 0.51", generate_gas_status("1.25", "0.60", "0.51"));
+    }
+
+    #[test]
+    fn test_generate_nft_floor_status() {
+        assert_eq!("≈ $40,188 | PPG", generate_nft_floor_status(Some("$40,188"), "PPG"));
+        assert_eq!("floor | PPG", generate_nft_floor_status(None, "PPG"));
+    }
+
+    #[test]
+    fn test_generate_secondary_asset_status() {
+        assert_eq!("ETH $3,412", generate_secondary_asset_status("ETH", "$3,412"));
+    }
+
+    #[test]
+    fn test_apply_price_template() {
+        assert_eq!("📈 BTC: $65,761", apply_price_template("{trend} {ticker}: {price}", "BTC", "$65,761", "+1.88%", "📈", ""));
+        assert_eq!("📉 ETH $3,412 (-0.50%)", apply_price_template("{trend} {ticker} {price} ({change})", "ETH", "$3,412", "-0.50%", "📉", ""));
+        assert_eq!(
+            "🩸 SOL -7.25%",
+            apply_price_template("{change_emoji} {ticker} {change}", "SOL", "$142", "-7.25%", "📉", "🩸")
+        );
+    }
+
+    #[test]
+    fn test_tag_extended_hours_status() {
+        assert_eq!("pre +1.25% | AAPL", tag_extended_hours_status("+1.25% | AAPL", "pre"));
+        assert_eq!("after -0.50% | AAPL", tag_extended_hours_status("-0.50% | AAPL", "after"));
+    }
+
+    #[test]
+    fn test_apply_moving_average_template() {
+        let moving_averages = [
+            MovingAverage { window: "1h".to_string(), price: "$65,100".to_string(), below_price: true },
+            MovingAverage { window: "24h".to_string(), price: "$66,300".to_string(), below_price: false },
+        ];
+        assert_eq!(
+            "above ▲ 1h MA $65,100 | below ▼ 24h MA $66,300",
+            apply_moving_average_template("{vs_sma_1h} 1h MA {sma_1h} | {vs_sma_24h} 24h MA {sma_24h}", &moving_averages)
+        );
+        // windows without enough history are left as is
+        assert_eq!("{vs_sma_7d}", apply_moving_average_template("{vs_sma_7d}", &moving_averages));
+    }
+
+    #[test]
+    fn test_apply_high_low_template() {
+        let high_low = ("$66.1k".to_string(), "$63.8k".to_string());
+        assert_eq!("H $66.1k L $63.8k | BTC", apply_high_low_template("H {high_24h} L {low_24h} | BTC", Some(&high_low)));
+        assert_eq!("H n/a L n/a", apply_high_low_template("H {high_24h} L {low_24h}", None));
+    }
+
+    #[test]
+    fn test_apply_derivatives_template() {
+        let info = DerivativesInfo {
+            funding_rate_percent: 0.01,
+            open_interest: BigDecimal::from_str("10659.509").unwrap(),
+        };
+        assert_eq!(
+            "funding +0.0100% | OI 10.66K",
+            apply_derivatives_template("funding {funding} | OI {open_interest}", Some(&info))
+        );
+        assert_eq!("funding n/a | OI n/a", apply_derivatives_template("funding {funding} | OI {open_interest}", None));
+    }
+
+    #[test]
+    fn test_mark_discord_bot_status_stale() {
+        assert_eq!("+12.34% | TICKER (stale)", mark_discord_bot_status_stale("+12.34% | TICKER", "(stale)"));
+        assert_eq!("+12.34% | TICKER 🕒", mark_discord_bot_status_stale("+12.34% | TICKER", "🕒"));
+    }
+}
@@ -0,0 +1,115 @@
+use crate::state_file::{load_json_state, save_json_state};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use tokio::sync::{watch, Mutex};
+
+/// Changes made to a running ticker through `/ticker`, applied over its config
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct TickerOverride {
+    #[serde(default)]
+    pub paused: bool, // No price is fetched until resumed
+    pub frequency: Option<u64>, // Seconds between fetches, replacing `frequency` and `schedule` of the config
+}
+
+#[derive(Default)]
+struct ControlState {
+    overrides: BTreeMap<String, TickerOverride>,
+    senders: HashMap<String, watch::Sender<TickerOverride>>,
+}
+
+/// The overrides of every ticker, saved to a JSON file on every change so that they survive restarts.
+/// Running tickers subscribe to theirs and see changes right away.
+pub struct TickerControls {
+    state_file: PathBuf,
+    state: Mutex<ControlState>,
+}
+
+impl TickerControls {
+    pub async fn load(state_file: PathBuf) -> Self {
+        let overrides = load_json_state(&state_file, "ticker controls").await;
+
+        Self {
+            state_file,
+            state: Mutex::new(ControlState {
+                overrides,
+                senders: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Receiver of the override of `ticker`, starting with the saved one
+    pub async fn subscribe(&self, ticker: &str) -> watch::Receiver<TickerOverride> {
+        let mut state = self.state.lock().await;
+        let current = state.overrides.get(ticker).cloned().unwrap_or_default();
+        state
+            .senders
+            .entry(ticker.to_string())
+            .or_insert_with(|| watch::channel(current).0)
+            .subscribe()
+    }
+
+    /// Changes the override of `ticker`, returning the new one
+    pub async fn update(&self, ticker: &str, change: impl FnOnce(&mut TickerOverride)) -> TickerOverride {
+        let mut state = self.state.lock().await;
+        let ticker_override = state.overrides.entry(ticker.to_string()).or_default();
+        change(ticker_override);
+        let ticker_override = ticker_override.clone();
+
+        if let Some(sender) = state.senders.get(ticker) {
+            sender.send_replace(ticker_override.clone());
+        }
+        save_json_state(&self.state_file, "ticker controls", &state.overrides).await;
+        ticker_override
+    }
+}
+
+/// Completes once the override changes, never if it cannot change anymore, e.g. without `/ticker`
+pub async fn override_changed(receiver: &mut watch::Receiver<TickerOverride>) {
+    if receiver.changed().await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_file::temp_state_file;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_ticker_controls() {
+        let state_file = temp_state_file("controls");
+        let controls = TickerControls::load(state_file.clone()).await;
+
+        let mut receiver = controls.subscribe("BTC").await;
+        assert_eq!(TickerOverride::default(), *receiver.borrow());
+
+        controls.update("BTC", |ticker_override| ticker_override.paused = true).await;
+        override_changed(&mut receiver).await;
+        assert!(receiver.borrow_and_update().paused);
+
+        // a restarted ticker starts with the saved override
+        let controls = TickerControls::load(state_file.clone()).await;
+        assert!(controls.subscribe("BTC").await.borrow().paused);
+        let ticker_override = controls.update("BTC", |ticker_override| ticker_override.frequency = Some(30)).await;
+        assert_eq!(
+            TickerOverride {
+                paused: true,
+                frequency: Some(30)
+            },
+            ticker_override
+        );
+        assert_eq!(TickerOverride::default(), *controls.subscribe("ETH").await.borrow());
+
+        tokio::fs::remove_file(&state_file).await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_override_changed_without_sender() {
+        let (_, mut receiver) = watch::channel(TickerOverride::default());
+        assert!(tokio::time::timeout(Duration::from_secs(60), override_changed(&mut receiver))
+            .await
+            .is_err());
+    }
+}
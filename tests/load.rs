@@ -0,0 +1,162 @@
+//! Synthetic load test of the whole pipeline: 200 tickers fetching every 10 seconds from a mock provider and
+//! updating a mock Discord, a webhook served by a mock server, measuring how long after the start of its fetch
+//! every update is applied and how many updates are applied per second.
+//!
+//! It runs for 35 seconds, so it is ignored by default:
+//! `cargo test --release --test load -- --ignored --nocapture`
+
+use async_trait::async_trait;
+use discord_price_ticker::bot::TickerBot;
+use discord_price_ticker::bot_update::BotUpdateInfo;
+use discord_price_ticker::config::{Config, QuoteSource};
+use discord_price_ticker::quote::error::QuoteRequestError;
+use discord_price_ticker::quote::provider::QuoteProvider;
+use discord_price_ticker::quote::response::AssetQuoteResponse;
+use discord_price_ticker::sink::output::OutputSink;
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+use wiremock::matchers::{method, path, path_regex};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const TICKERS: usize = 200;
+const FREQUENCY_SECS: u64 = 10;
+const RUN_FOR: Duration = Duration::from_secs(35);
+/// Time the mock provider and the mock Discord take to answer
+const PROVIDER_LATENCY: Duration = Duration::from_millis(20);
+const DISCORD_LATENCY: Duration = Duration::from_millis(50);
+
+/// Quotes every asset with a price moving on every call, so that no update is skipped as unchanged
+struct MovingPriceProvider {
+    calls: AtomicU64,
+}
+
+#[async_trait]
+impl QuoteProvider for MovingPriceProvider {
+    fn name(&self) -> &str {
+        "Moving price"
+    }
+
+    async fn fetch_quote(&self, id: &str, _vs_currency: &str) -> Result<AssetQuoteResponse, QuoteRequestError> {
+        tokio::time::sleep(PROVIDER_LATENCY).await;
+        let call = self.calls.fetch_add(1, Ordering::SeqCst);
+        Ok(AssetQuoteResponse {
+            name: id.to_string(),
+            price: (100 + call).into(),
+            price_change_24h: "1.5".parse().unwrap(),
+            ..Default::default()
+        })
+    }
+}
+
+/// Records the latency of every update once it is applied to Discord
+#[derive(Default)]
+struct LatencySink {
+    latencies: Mutex<HashMap<String, Vec<Duration>>>,
+}
+
+#[async_trait]
+impl OutputSink for LatencySink {
+    fn name(&self) -> &str {
+        "latency"
+    }
+
+    async fn publish(&self, update: &BotUpdateInfo) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if let Some(fetched_at) = update.fetched_at {
+            let mut latencies = self.latencies.lock().unwrap();
+            latencies.entry(update.ticker.clone()).or_default().push(fetched_at.elapsed());
+        }
+        Ok(())
+    }
+}
+
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    sorted[(sorted.len() - 1) * percent / 100]
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore]
+async fn test_load() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/coins/list"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path_regex("^/webhooks/[0-9]+$"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id": "1"}"#).set_delay(DISCORD_LATENCY))
+        .mount(&server)
+        .await;
+    Mock::given(method("PATCH"))
+        .and(path_regex("^/webhooks/[0-9]+/messages/1$"))
+        .respond_with(ResponseTemplate::new(200).set_delay(DISCORD_LATENCY))
+        .mount(&server)
+        .await;
+
+    let tickers: Vec<serde_json::Value> = (0..TICKERS)
+        .map(|i| {
+            serde_json::json!({
+                "ticker": format!("T{}", i),
+                "name": format!("asset-{}", i),
+                "frequency": FREQUENCY_SECS,
+                "display_mode": "webhook",
+                "webhook_url": format!("{}/webhooks/{}", server.uri(), i)
+            })
+        })
+        .collect();
+    // the tickers tick together, a burst larger than the default queue which waits for its slots
+    let config: Config = serde_json::from_value(serde_json::json!({
+        "coingecko_api_key": "",
+        "coingecko_base_url": server.uri(),
+        "tickers": tickers
+    }))
+    .unwrap();
+
+    let sink = Arc::new(LatencySink::default());
+    let bot = TickerBot::builder()
+        .config(config)
+        .provider(
+            QuoteSource::YahooFinance,
+            Arc::new(MovingPriceProvider {
+                calls: AtomicU64::new(0),
+            }),
+        )
+        .sink(sink.clone())
+        .build()
+        .unwrap();
+    let started_at = Instant::now();
+    bot.run(tokio::time::sleep(RUN_FOR)).await.unwrap();
+    let elapsed = started_at.elapsed();
+
+    let latencies = sink.latencies.lock().unwrap();
+    let mut all: Vec<Duration> = latencies.values().flatten().copied().collect();
+    all.sort();
+    println!(
+        "{} updates of {} tickers in {:.1?}, {:.1} updates/s",
+        all.len(),
+        latencies.len(),
+        elapsed,
+        all.len() as f64 / elapsed.as_secs_f64()
+    );
+    println!(
+        "latency p50 {:?}, p90 {:?}, p99 {:?}, max {:?}",
+        percentile(&all, 50),
+        percentile(&all, 90),
+        percentile(&all, 99),
+        all[all.len() - 1]
+    );
+
+    // 35 seconds at a frequency of 10 seconds are at least 3 fetches per ticker
+    assert_eq!(TICKERS, latencies.len(), "tickers never updated");
+    let behind: Vec<&String> = latencies
+        .iter()
+        .filter(|(_, latencies)| latencies.len() < 3)
+        .map(|(ticker, _)| ticker)
+        .collect();
+    assert!(behind.is_empty(), "tickers updated less than 3 times: {:?}", behind);
+    assert!(percentile(&all, 99) < Duration::from_secs(FREQUENCY_SECS), "updates lag a whole tick behind");
+}
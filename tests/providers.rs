@@ -0,0 +1,235 @@
+//! Golden tests of the quote providers against recorded responses in `tests/fixtures`,
+//! so that a change in parsing shows up as a changed outcome of a known response.
+
+use discord_price_ticker::config::ApiKeyRotation;
+use discord_price_ticker::quote::api_keys::ApiKeys;
+use discord_price_ticker::quote::binance::BinanceProvider;
+use discord_price_ticker::quote::chainlink::ChainlinkProvider;
+use discord_price_ticker::quote::coingecko::CoinGeckoProvider;
+use discord_price_ticker::quote::custom_http::{CustomHttpEndpoint, CustomHttpProvider};
+use discord_price_ticker::quote::error::QuoteRequestError;
+use discord_price_ticker::quote::provider::QuoteProvider;
+use discord_price_ticker::quote::response::AssetQuoteResponse;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use wiremock::matchers::{body_string_contains, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// A recorded response and the outcome of parsing it, see `outcome`
+struct Case {
+    fixture: &'static str,
+    status: u16,
+    expected: &'static str,
+}
+
+const COINGECKO_CASES: &[Case] = &[
+    Case {
+        fixture: "coingecko/simple_price.json",
+        status: 200,
+        expected: "Ok(price=65761.123456789, change=1.8841205093585678)",
+    },
+    Case {
+        fixture: "coingecko/simple_price_missing_change.json",
+        status: 200,
+        expected: "MissingField(usd_24h_change)",
+    },
+    Case {
+        fixture: "coingecko/simple_price_null_price.json",
+        status: 200,
+        expected: "MissingField(usd)",
+    },
+    Case {
+        fixture: "coingecko/simple_price_unknown_id.json",
+        status: 200,
+        expected: "MissingAsset(bitcoin)",
+    },
+    Case {
+        fixture: "coingecko/rate_limited.json",
+        status: 429,
+        expected: "RateLimited",
+    },
+    Case {
+        fixture: "coingecko/malformed.json",
+        status: 200,
+        expected: "JsonParse",
+    },
+];
+
+const BINANCE_CASES: &[Case] = &[
+    Case {
+        fixture: "binance/ticker_24hr.json",
+        status: 200,
+        expected: "Ok(price=65761.12000000, change=1.884)",
+    },
+    Case {
+        fixture: "binance/ticker_24hr_missing_price.json",
+        status: 200,
+        expected: "MissingField(lastPrice)",
+    },
+    Case {
+        fixture: "binance/ticker_24hr_invalid_change.json",
+        status: 200,
+        expected: "InvalidField(priceChangePercent)",
+    },
+    Case {
+        fixture: "binance/invalid_symbol.json",
+        status: 400,
+        expected: "HttpRequest",
+    },
+    Case {
+        fixture: "binance/rate_limited.json",
+        status: 429,
+        expected: "RateLimited",
+    },
+    Case {
+        fixture: "binance/malformed.html",
+        status: 200,
+        expected: "JsonParse",
+    },
+];
+
+fn fixture(name: &str) -> String {
+    let file_path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read_to_string(&file_path).unwrap_or_else(|e| panic!("cannot read {}: {}", file_path, e))
+}
+
+/// Short description of a result, leaving out the parts that vary between runs like the port in URLs
+fn outcome(result: Result<AssetQuoteResponse, QuoteRequestError>) -> String {
+    match result {
+        Ok(response) => format!("Ok(price={}, change={})", response.price, response.price_change_24h),
+        Err(QuoteRequestError::MissingAsset(name)) => format!("MissingAsset({})", name),
+        Err(QuoteRequestError::MissingField(field)) => format!("MissingField({})", field),
+        Err(QuoteRequestError::InvalidField(field)) => format!("InvalidField({})", field),
+        Err(QuoteRequestError::HttpRequest(_)) => "HttpRequest".to_string(),
+        Err(QuoteRequestError::JsonParse(_)) => "JsonParse".to_string(),
+        Err(QuoteRequestError::ParseBigDecimal(_)) => "ParseBigDecimal".to_string(),
+        Err(QuoteRequestError::Timeout(_)) => "Timeout".to_string(),
+        Err(QuoteRequestError::NotFound(_)) => "NotFound".to_string(),
+        Err(QuoteRequestError::RateLimited(_)) => "RateLimited".to_string(),
+        Err(QuoteRequestError::Unauthorized(_)) => "Unauthorized".to_string(),
+        Err(QuoteRequestError::ServerError(_)) => "ServerError".to_string(),
+        Err(QuoteRequestError::CircuitOpen(_)) => "CircuitOpen".to_string(),
+        Err(QuoteRequestError::Other(err)) => format!("Other({})", err),
+    }
+}
+
+/// Serves every case in turn at `endpoint` and checks the outcome of `provider` asking for `id`
+async fn check_cases(
+    server: &MockServer,
+    provider: &dyn QuoteProvider,
+    endpoint: &str,
+    id: &str,
+    cases: &[Case],
+) {
+    let mut failures = Vec::new();
+
+    for case in cases {
+        server.reset().await;
+        Mock::given(method("GET"))
+            .and(path(endpoint))
+            .respond_with(ResponseTemplate::new(case.status).set_body_string(fixture(case.fixture)))
+            .mount(server)
+            .await;
+
+        let actual = outcome(provider.fetch_quote(id, "usd").await);
+        if actual != case.expected {
+            failures.push(format!("{}: expected {}, got {}", case.fixture, case.expected, actual));
+        }
+    }
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+#[tokio::test]
+async fn test_coingecko_fixtures() {
+    let server = MockServer::start().await;
+    let provider = CoinGeckoProvider::new(reqwest::Client::new(), server.uri(), String::new());
+    check_cases(&server, &provider, "/simple/price", "bitcoin", COINGECKO_CASES).await;
+}
+
+#[tokio::test]
+async fn test_binance_fixtures() {
+    let server = MockServer::start().await;
+    let provider = BinanceProvider::new(reqwest::Client::new(), server.uri());
+    check_cases(&server, &provider, "/api/v3/ticker/24hr", "BTC", BINANCE_CASES).await;
+}
+
+#[tokio::test]
+async fn test_coingecko_api_key_failover() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/simple/price"))
+        .and(header("x-cg-demo-api-key", "first"))
+        .respond_with(ResponseTemplate::new(429).set_body_string(fixture("coingecko/rate_limited.json")))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/simple/price"))
+        .and(header("x-cg-demo-api-key", "second"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(fixture("coingecko/simple_price.json")))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let api_keys = ApiKeys::new("CoinGecko", vec!["first".into(), "second".into()], ApiKeyRotation::Failover);
+    let provider = CoinGeckoProvider::with_api_keys(reqwest::Client::new(), server.uri(), Arc::new(api_keys));
+    // the rate limited key is left out of the next requests
+    let outcomes: Vec<String> = vec![
+        outcome(provider.fetch_quote("bitcoin", "usd").await),
+        outcome(provider.fetch_quote("bitcoin", "usd").await),
+        outcome(provider.fetch_quote("bitcoin", "usd").await),
+    ];
+    let ok = "Ok(price=65761.123456789, change=1.8841205093585678)";
+    assert_eq!(vec!["RateLimited", ok, ok], outcomes);
+}
+
+#[tokio::test]
+async fn test_custom_http_endpoint() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/price/eur"))
+        .and(header("x-api-key", "secret"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"result": [{"last": "61234.5", "change": 2.5}]}"#))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let endpoint: CustomHttpEndpoint = serde_json::from_value(serde_json::json!({
+        "url": format!("{}/v1/price/{{vs_currency}}", server.uri()),
+        "headers": {"x-api-key": "secret"},
+        "price": "$.result[0].last",
+        "change": "result[0].change"
+    }))
+    .unwrap();
+    let provider = CustomHttpProvider::new(reqwest::Client::new(), BTreeMap::from([("my-api".to_string(), endpoint)]));
+    assert_eq!("Ok(price=61234.5, change=2.5)", outcome(provider.fetch_quote("my-api", "eur").await));
+    assert_eq!("NotFound", outcome(provider.fetch_quote("other-api", "eur").await));
+}
+
+#[tokio::test]
+async fn test_chainlink_feed() {
+    let server = MockServer::start().await;
+    // `decimals()` is only asked once per feed
+    Mock::given(method("POST"))
+        .and(body_string_contains("0x313ce567"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"{"jsonrpc":"2.0","id":1,"result":"0x0000000000000000000000000000000000000000000000000000000000000008"}"#,
+        ))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("0xfeaf968c"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(fixture("chainlink/latest_round_data.json")))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let provider = ChainlinkProvider::new(reqwest::Client::new(), server.uri().into());
+    let feed = "0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419";
+    let ok = "Ok(price=3412.56789000, change=0)";
+    assert_eq!(ok, outcome(provider.fetch_quote(feed, "usd").await));
+    assert_eq!(ok, outcome(provider.fetch_quote(feed, "usd").await));
+    assert_eq!("NotFound", outcome(provider.fetch_quote("ethereum", "usd").await));
+}